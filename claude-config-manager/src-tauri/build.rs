@@ -1,4 +1,10 @@
 fn main() {
+    #[cfg(feature = "gui")]
+    build_app();
+}
+
+#[cfg(feature = "gui")]
+fn build_app() {
     let mut windows = tauri_build::WindowsAttributes::new();
     windows = windows.app_manifest(include_str!("app.manifest"));
     
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    claude_config_manager_lib::cli::main()
+}
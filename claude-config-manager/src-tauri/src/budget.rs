@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::memory::{ImportStatus, MemoryEntry, MemorySource};
+
+/// Approximate token count without shipping a tokenizer. Tuned against
+/// typical BPE behaviour on English markdown: short words are one token,
+/// long words split every ~5 letters, digits group in threes, and each
+/// punctuation mark or non-Latin character costs a token of its own.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphabetic() {
+            let mut len: usize = 1;
+            while chars.next_if(|n| n.is_ascii_alphabetic()).is_some() {
+                len += 1;
+            }
+            tokens += len.div_ceil(5);
+        } else if c.is_ascii_digit() {
+            let mut len: usize = 1;
+            while chars.next_if(|n| n.is_ascii_digit()).is_some() {
+                len += 1;
+            }
+            tokens += len.div_ceil(3);
+        } else if c == '\n' {
+            while chars.next_if(|n| *n == '\n').is_some() {}
+            tokens += 1;
+        } else if c.is_whitespace() {
+            // A single space folds into the following word
+            let mut len: usize = 1;
+            while chars.next_if(|n| n.is_whitespace() && *n != '\n').is_some() {
+                len += 1;
+            }
+            tokens += (len - 1).div_ceil(4);
+        } else {
+            tokens += 1;
+        }
+    }
+    tokens
+}
+
+/// Token limits above which a file is flagged. Every field is optional from
+/// the frontend.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct BudgetThresholds {
+    pub memory: usize,      // Each CLAUDE.md / CLAUDE.local.md
+    pub rule: usize,
+    pub agent: usize,
+    pub command: usize,
+    pub skill: usize,
+    pub total: usize,       // Everything loaded at session start
+}
+
+impl Default for BudgetThresholds {
+    fn default() -> Self {
+        BudgetThresholds {
+            memory: 5_000,
+            rule: 2_000,
+            agent: 3_000,
+            command: 2_000,
+            skill: 5_000,
+            total: 20_000,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetCategory {
+    Memory,
+    Rule,
+    Agent,
+    Command,
+    Skill,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetLevel {
+    Enterprise,
+    User,
+    Project,
+}
+
+#[derive(Serialize)]
+pub struct FileBudget {
+    pub path: String,
+    pub category: BudgetCategory,
+    pub level: BudgetLevel,
+    pub chars: usize,
+    pub estimated_tokens: usize,
+    pub threshold: usize,
+    pub over_threshold: bool,
+}
+
+#[derive(Serialize)]
+pub struct BudgetReport {
+    pub files: Vec<FileBudget>,         // Largest first
+    pub startup_tokens: usize,          // Memory and rules, loaded at session start
+    pub total_tokens: usize,
+    pub total_threshold: usize,
+    pub over_total: bool,               // startup_tokens exceeds `total`
+    pub flagged: usize,                 // Files over their threshold
+}
+
+/// Markdown files under `dir`, recursively. With `name`, only files called
+/// exactly that (e.g. SKILL.md).
+fn markdown_files(dir: &Path, name: Option<&str>, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            markdown_files(&path, name, out);
+            continue;
+        }
+        let matches = match name {
+            Some(name) => path.file_name().and_then(|n| n.to_str()) == Some(name),
+            None => path.extension().and_then(|x| x.to_str()) == Some("md"),
+        };
+        if matches {
+            out.push(path);
+        }
+    }
+}
+
+/// Memory and rule files loaded at session start, as the memory resolver
+/// finds them (ancestors of the project included), plus every file they
+/// import.
+fn memory_files(project_path: Option<&Path>) -> Vec<(BudgetCategory, BudgetLevel, PathBuf)> {
+    let entries = match project_path {
+        Some(project) => crate::memory::memory_stack(project).entries,
+        None => crate::memory::global_memory(),
+    };
+    loaded_files(&entries, project_path)
+}
+
+/// The files behind memory entries and their resolved imports. Each file is
+/// counted once, at the first place it's loaded.
+fn loaded_files(entries: &[MemoryEntry], project_path: Option<&Path>) -> Vec<(BudgetCategory, BudgetLevel, PathBuf)> {
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for entry in entries.iter().filter(|e| !e.on_demand) {
+        let category = if entry.source == MemorySource::Rule { BudgetCategory::Rule } else { BudgetCategory::Memory };
+        let level = match entry.source {
+            MemorySource::Enterprise => BudgetLevel::Enterprise,
+            _ if project_path.is_some_and(|p| Path::new(&entry.directory).starts_with(p)) => BudgetLevel::Project,
+            _ => BudgetLevel::User,
+        };
+        let imported = entry.imports.iter().filter(|i| i.status == ImportStatus::Resolved).map(|i| PathBuf::from(&i.target));
+        for path in std::iter::once(PathBuf::from(&entry.path)).chain(imported) {
+            if seen.insert(fs::canonicalize(&path).unwrap_or_else(|_| path.clone())) {
+                files.push((category, level, path));
+            }
+        }
+    }
+    files
+}
+
+fn collect_files(project_path: Option<&Path>) -> Vec<(BudgetCategory, BudgetLevel, PathBuf)> {
+    let paths = crate::get_config_paths();
+    let mut files = memory_files(project_path);
+
+    let add_dirs = |level: BudgetLevel, agents: &str, commands: &str, skills: &str| {
+        let mut found = Vec::new();
+        let mut push = |category: BudgetCategory, dir: &str, name: Option<&str>| {
+            let mut paths = Vec::new();
+            markdown_files(Path::new(dir), name, &mut paths);
+            paths.sort();
+            found.extend(paths.into_iter().map(|p| (category, level, p)));
+        };
+        push(BudgetCategory::Agent, agents, None);
+        push(BudgetCategory::Command, commands, None);
+        push(BudgetCategory::Skill, skills, Some("SKILL.md"));
+        found
+    };
+
+    files.extend(add_dirs(BudgetLevel::User, &paths.user.agents.path, &paths.user.commands.path, &paths.user.skills.path));
+
+    if let Some(project) = project_path {
+        let project_files = crate::project_config_files(project);
+        files.extend(add_dirs(
+            BudgetLevel::Project,
+            &project_files.agents.path,
+            &project_files.commands.path,
+            &project_files.skills.path,
+        ));
+    }
+
+    files.retain(|(_, _, path)| path.is_file());
+    files
+}
+
+/// Size and approximate token cost of every memory, rule, agent, command and
+/// skill file for a project (or only user-level files without one).
+pub fn budget_report(project_path: Option<&Path>, thresholds: &BudgetThresholds) -> BudgetReport {
+    let mut files: Vec<FileBudget> = collect_files(project_path)
+        .into_iter()
+        .filter_map(|(category, level, path)| {
+            let text = fs::read_to_string(&path).ok()?;
+            let estimated_tokens = estimate_tokens(&text);
+            let threshold = match category {
+                BudgetCategory::Memory => thresholds.memory,
+                BudgetCategory::Rule => thresholds.rule,
+                BudgetCategory::Agent => thresholds.agent,
+                BudgetCategory::Command => thresholds.command,
+                BudgetCategory::Skill => thresholds.skill,
+            };
+            Some(FileBudget {
+                path: path.to_string_lossy().into_owned(),
+                category,
+                level,
+                chars: text.chars().count(),
+                estimated_tokens,
+                threshold,
+                over_threshold: estimated_tokens > threshold,
+            })
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.estimated_tokens));
+
+    let startup_tokens = files
+        .iter()
+        .filter(|f| matches!(f.category, BudgetCategory::Memory | BudgetCategory::Rule))
+        .map(|f| f.estimated_tokens)
+        .sum();
+    BudgetReport {
+        startup_tokens,
+        total_tokens: files.iter().map(|f| f.estimated_tokens).sum(),
+        total_threshold: thresholds.total,
+        over_total: startup_tokens > thresholds.total,
+        flagged: files.iter().filter(|f| f.over_threshold).count(),
+        files,
+    }
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn token_budget_report(project_path: Option<String>, thresholds: Option<BudgetThresholds>) -> BudgetReport {
+    budget_report(project_path.as_deref().map(Path::new), &thresholds.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::ImportEdge;
+    use tempfile::TempDir;
+
+    fn entry(source: MemorySource, path: &Path, directory: &Path, imports: &[(&Path, ImportStatus)]) -> MemoryEntry {
+        MemoryEntry {
+            source,
+            path: path.to_string_lossy().into_owned(),
+            directory: directory.to_string_lossy().into_owned(),
+            on_demand: false,
+            size: 0,
+            expanded_size: 0,
+            estimated_tokens: 0,
+            imports: imports
+                .iter()
+                .map(|(target, status)| ImportEdge {
+                    from: path.to_string_lossy().into_owned(),
+                    raw: String::new(),
+                    line: 1,
+                    target: target.to_string_lossy().into_owned(),
+                    depth: 1,
+                    status: *status,
+                })
+                .collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn estimates_follow_the_heuristic() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("hello"), 1);
+        assert_eq!(estimate_tokens("internationalization"), 4);
+        // A single space folds into the next word; longer runs cost a token per four
+        assert_eq!(estimate_tokens("one two three"), 3);
+        assert_eq!(estimate_tokens("a     b"), 3);
+        assert_eq!(estimate_tokens("2025"), 2);
+        assert_eq!(estimate_tokens("a\n\n\nb"), 3);
+        assert_eq!(estimate_tokens("- [x] done."), 6);
+        assert_eq!(estimate_tokens("日本語"), 3);
+        let prose = "Use pnpm, not npm. Run the tests before committing and keep commits small.";
+        let tokens = estimate_tokens(prose);
+        assert!((prose.len() / 5..=prose.len() / 3).contains(&tokens), "{} tokens", tokens);
+    }
+
+    #[test]
+    fn each_memory_file_is_counted_once() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let project = temp.path().join("work/app");
+        fs::create_dir_all(home.join(".claude")).unwrap();
+        fs::create_dir_all(project.join(".claude/rules")).unwrap();
+        for file in [home.join(".claude/CLAUDE.md"), home.join("shared.md"), project.join("CLAUDE.md"), project.join(".claude/rules/style.md"), project.join("missing-target.md")] {
+            fs::write(file, "text").unwrap();
+        }
+        let shared = home.join("shared.md");
+        let shared_again = project.join("../../home/shared.md");
+
+        let entries = [
+            entry(MemorySource::User, &home.join(".claude/CLAUDE.md"), &home, &[(&shared, ImportStatus::Resolved)]),
+            entry(
+                MemorySource::Ancestor,
+                &project.join("CLAUDE.md"),
+                &project,
+                &[(&shared_again, ImportStatus::Resolved), (&project.join("missing-target.md"), ImportStatus::Missing)],
+            ),
+            entry(MemorySource::Rule, &project.join(".claude/rules/style.md"), &project, &[(&project.join("CLAUDE.md"), ImportStatus::Resolved)]),
+        ];
+        let files = loaded_files(&entries, Some(&project));
+        let found: Vec<(BudgetCategory, BudgetLevel, String)> = files
+            .into_iter()
+            .map(|(category, level, path)| (category, level, path.strip_prefix(temp.path()).unwrap().to_string_lossy().into_owned()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (BudgetCategory::Memory, BudgetLevel::User, "home/.claude/CLAUDE.md".to_string()),
+                (BudgetCategory::Memory, BudgetLevel::User, "home/shared.md".to_string()),
+                (BudgetCategory::Memory, BudgetLevel::Project, "work/app/CLAUDE.md".to_string()),
+                (BudgetCategory::Rule, BudgetLevel::Project, "work/app/.claude/rules/style.md".to_string()),
+            ]
+        );
+    }
+}
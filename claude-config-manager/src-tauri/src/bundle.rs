@@ -0,0 +1,748 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use similar::TextDiff;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::storage::{atomic_write, content_version, unix_millis, write_with_backup};
+
+const BUNDLE_FORMAT: u32 = 1;
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Refuse archives that unpack to more than this, so a hostile bundle can't
+/// exhaust memory.
+const MAX_BUNDLE_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleScope {
+    User,       // ~/.claude and the mcpServers of ~/.claude.json
+    Project,    // Shared project files; CLAUDE.local.md and settings.local.json are personal and never bundled
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleRole {
+    Memory,
+    Settings,
+    Mcp,
+    Agents,
+    Commands,
+    Skills,
+    Rules,
+}
+
+impl BundleRole {
+    fn dir(self) -> &'static str {
+        match self {
+            BundleRole::Memory => "memory",
+            BundleRole::Settings => "settings",
+            BundleRole::Mcp => "mcp",
+            BundleRole::Agents => "agents",
+            BundleRole::Commands => "commands",
+            BundleRole::Skills => "skills",
+            BundleRole::Rules => "rules",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleEntry {
+    pub path: String,                   // Path inside the archive: "<role>/<relative>"
+    pub role: BundleRole,
+    pub relative: String,               // Location within the role, e.g. "frontend/component.md"
+    pub size: u64,
+    pub version: String,                // content_version of the archived bytes
+    #[serde(default)]
+    pub redacted: Vec<String>,          // Values replaced with ${NAME} placeholders, e.g. "env.API_KEY"
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleManifest {
+    pub format: u32,
+    pub created_at: u64,                // Unix seconds
+    pub scope: BundleScope,
+    pub source: String,                 // Project directory name, or "user"
+    pub entries: Vec<BundleEntry>,
+}
+
+#[derive(Serialize)]
+pub struct BundleExport {
+    pub path: String,
+    pub manifest: BundleManifest,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    New,
+    Identical,
+    Conflict,   // Destination exists with different contents; only written if listed in `overwrite`
+    Unmapped,   // No equivalent location in the target scope
+}
+
+#[derive(Serialize)]
+pub struct ImportItem {
+    pub id: String,                     // Archive path, or "mcp/mcp.json#<server>" for MCP servers
+    pub role: BundleRole,
+    pub destination: Option<String>,
+    pub status: ImportStatus,
+    pub diff: Option<String>,           // Unified diff from the existing file to the bundled one, for conflicts
+    pub redacted: Vec<String>,          // Placeholders the user needs to fill in after importing
+    pub applied: bool,
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub manifest: BundleManifest,
+    pub items: Vec<ImportItem>,
+}
+
+/// Where each role lives in a scope.
+struct ScopeLayout {
+    memory: Vec<(&'static str, PathBuf)>,   // (relative name, path)
+    settings: PathBuf,
+    mcp: PathBuf,
+    dirs: Vec<(BundleRole, PathBuf)>,
+}
+
+fn scope_layout(scope: BundleScope, project_path: Option<&str>) -> Result<ScopeLayout, String> {
+    match scope {
+        BundleScope::User => {
+            let user = crate::get_config_paths().user;
+            Ok(ScopeLayout {
+                memory: vec![("CLAUDE.md", PathBuf::from(user.claude_md.path))],
+                settings: PathBuf::from(user.settings.path),
+                mcp: PathBuf::from(user.mcp.path),
+                dirs: vec![
+                    (BundleRole::Agents, PathBuf::from(user.agents.path)),
+                    (BundleRole::Commands, PathBuf::from(user.commands.path)),
+                    (BundleRole::Skills, PathBuf::from(user.skills.path)),
+                    (BundleRole::Rules, crate::home_dir().join(".claude").join("rules")),
+                ],
+            })
+        }
+        BundleScope::Project => {
+            let project = project_path
+                .filter(|p| !p.trim().is_empty())
+                .ok_or("A project path is required for the project scope")?;
+            let files = crate::project_config_files(Path::new(project));
+            Ok(ScopeLayout {
+                memory: vec![
+                    ("CLAUDE.md", PathBuf::from(files.claude_md_root.path)),
+                    (".claude/CLAUDE.md", PathBuf::from(files.claude_md_dotclaude.path)),
+                ],
+                settings: PathBuf::from(files.settings.path),
+                mcp: PathBuf::from(files.mcp.path),
+                dirs: vec![
+                    (BundleRole::Agents, PathBuf::from(files.agents.path)),
+                    (BundleRole::Commands, PathBuf::from(files.commands.path)),
+                    (BundleRole::Skills, PathBuf::from(files.skills.path)),
+                    (BundleRole::Rules, PathBuf::from(files.rules.path)),
+                ],
+            })
+        }
+    }
+}
+
+/// Files under `dir`, skipping hidden entries (temp files, .DS_Store, ...).
+fn walk_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.file_name().unwrap_or_default().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            walk_files(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+fn is_placeholder(value: &str) -> bool {
+    value.starts_with("${") && value.ends_with('}')
+}
+
+/// Replace every value of the `env` (and, for MCP servers, `headers`) object
+/// with a `${NAME}` placeholder, recording what was replaced.
+fn redact_object(object: &mut Value, key: &str, prefix: &str, redacted: &mut Vec<String>) {
+    let Some(map) = object.get_mut(key).and_then(Value::as_object_mut) else { return };
+    for (name, value) in map.iter_mut() {
+        if value.as_str().is_some_and(is_placeholder) {
+            continue;
+        }
+        let placeholder = name.to_uppercase().replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        *value = Value::String(format!("${{{}}}", placeholder));
+        redacted.push(format!("{}{}.{}", prefix, key, name));
+    }
+}
+
+fn redact_mcp_servers(servers: &mut Value, redacted: &mut Vec<String>) {
+    let Some(map) = servers.as_object_mut() else { return };
+    for (name, server) in map.iter_mut() {
+        let prefix = format!("mcpServers.{}.", name);
+        redact_object(server, "env", &prefix, redacted);
+        redact_object(server, "headers", &prefix, redacted);
+    }
+}
+
+fn pretty_json(value: &Value) -> Result<Vec<u8>, String> {
+    let mut text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    text.push('\n');
+    Ok(text.into_bytes())
+}
+
+fn push_entry(files: &mut Vec<(BundleEntry, Vec<u8>)>, role: BundleRole, relative: &str, bytes: Vec<u8>, redacted: Vec<String>) {
+    files.push((
+        BundleEntry {
+            path: format!("{}/{}", role.dir(), relative),
+            role,
+            relative: relative.to_string(),
+            size: bytes.len() as u64,
+            version: content_version(&bytes),
+            redacted,
+        },
+        bytes,
+    ));
+}
+
+/// Read every bundled file of a scope, with secrets already redacted.
+/// `include` limits the export to files under those paths; empty means all.
+fn collect_files(layout: &ScopeLayout, include: &[PathBuf]) -> Result<Vec<(BundleEntry, Vec<u8>)>, String> {
+    let included = |path: &Path| include.is_empty() || include.iter().any(|p| path.starts_with(p));
+    let read = |path: &Path| fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e));
+    let mut files = Vec::new();
+
+    for (relative, path) in &layout.memory {
+        if path.is_file() && included(path) {
+            push_entry(&mut files, BundleRole::Memory, relative, read(path)?, Vec::new());
+        }
+    }
+
+    if layout.settings.is_file() && included(&layout.settings) {
+        let bytes = read(&layout.settings)?;
+        let mut redacted = Vec::new();
+        // Unparseable settings are bundled as-is rather than dropped
+        let bytes = match serde_json::from_slice::<Value>(&bytes) {
+            Ok(mut settings) => {
+                redact_object(&mut settings, "env", "", &mut redacted);
+                if redacted.is_empty() { bytes } else { pretty_json(&settings)? }
+            }
+            Err(_) => bytes,
+        };
+        push_entry(&mut files, BundleRole::Settings, "settings.json", bytes, redacted);
+    }
+
+    if layout.mcp.is_file() && included(&layout.mcp) {
+        // Only the server definitions; ~/.claude.json also holds Claude Code's private state
+        if let Some(mut servers) = crate::mcp::read_json_object(&layout.mcp)?.get("mcpServers").cloned() {
+            let mut redacted = Vec::new();
+            redact_mcp_servers(&mut servers, &mut redacted);
+            let mut root = Map::new();
+            root.insert("mcpServers".into(), servers);
+            push_entry(&mut files, BundleRole::Mcp, "mcp.json", pretty_json(&Value::Object(root))?, redacted);
+        }
+    }
+
+    for (role, dir) in &layout.dirs {
+        let mut paths = Vec::new();
+        walk_files(dir, &mut paths);
+        for path in paths.into_iter().filter(|p| included(p)) {
+            let relative = path.strip_prefix(dir).unwrap_or(&path);
+            let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            push_entry(&mut files, *role, &relative, read(&path)?, Vec::new());
+        }
+    }
+    Ok(files)
+}
+
+fn append_file(builder: &mut tar::Builder<GzEncoder<Vec<u8>>>, path: &str, bytes: &[u8], mtime: u64) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    builder.append_data(&mut header, path, bytes).map_err(|e| format!("Failed to add {} to the bundle: {}", path, e))
+}
+
+/// The manifest and gzipped archive for a scope's files.
+fn build_archive(layout: &ScopeLayout, include: &[PathBuf], scope: BundleScope, source: &str) -> Result<(BundleManifest, Vec<u8>), String> {
+    let files = collect_files(layout, include)?;
+    if files.is_empty() {
+        return Err("There is no configuration to export".into());
+    }
+
+    let created_at = (unix_millis() / 1000) as u64;
+    let manifest = BundleManifest {
+        format: BUNDLE_FORMAT,
+        created_at,
+        scope,
+        source: source.to_string(),
+        entries: files.iter().map(|(entry, _)| entry.clone()).collect(),
+    };
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    append_file(&mut builder, MANIFEST_NAME, &pretty_json(&serde_json::to_value(&manifest).map_err(|e| e.to_string())?)?, created_at)?;
+    for (entry, bytes) in &files {
+        append_file(&mut builder, &entry.path, bytes, created_at)?;
+    }
+    let archive = builder
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to write the bundle: {}", e))?;
+    Ok((manifest, archive))
+}
+
+pub fn export(scope: BundleScope, project_path: Option<&str>, include: &[PathBuf], dest: &Path) -> Result<BundleExport, String> {
+    let layout = scope_layout(scope, project_path)?;
+    let source = match (scope, project_path) {
+        (BundleScope::Project, Some(project)) => Path::new(project).file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        _ => "user".to_string(),
+    };
+    let (manifest, archive) = build_archive(&layout, include, scope, &source)?;
+    let dest = archive_dest(dest, &source)?;
+    atomic_write(&dest, &archive)?;
+    Ok(BundleExport { path: dest.to_string_lossy().into_owned(), manifest })
+}
+
+/// Where to write an export. The webview picks `dest`, so it must name a
+/// bundle and may only replace an existing bundle, never another file.
+fn archive_dest(dest: &Path, source: &str) -> Result<PathBuf, String> {
+    let dest = if dest.is_dir() { dest.join(format!("{}-claude-config.tar.gz", source)) } else { dest.to_path_buf() };
+    let dest = crate::sandbox::resolve(&dest)?;
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    if !name.ends_with(".tar.gz") && !name.ends_with(".tgz") {
+        return Err(crate::sandbox::policy_error(&dest, "is not a .tar.gz or .tgz file"));
+    }
+    if dest.exists() && read_bundle(&dest).is_err() {
+        return Err(crate::sandbox::policy_error(&dest, "exists and is not a bundle"));
+    }
+    Ok(dest)
+}
+
+/// Archive paths must stay relative and inside the bundle.
+fn is_safe_relative(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Read and verify a bundle without touching the filesystem outside it.
+fn read_bundle(archive: &Path) -> Result<(BundleManifest, HashMap<String, Vec<u8>>), String> {
+    let file = fs::File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let invalid = |e: std::io::Error| format!("{} is not a valid bundle: {}", archive.display(), e);
+
+    let mut contents = HashMap::new();
+    let mut total = 0u64;
+    for entry in tar.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path().map_err(invalid)?.to_string_lossy().into_owned();
+        if !is_safe_relative(&path) {
+            return Err(format!("Bundle contains an unsafe path: {}", path));
+        }
+        total += entry.size();
+        if total > MAX_BUNDLE_BYTES {
+            return Err("Bundle is too large".into());
+        }
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(invalid)?;
+        contents.insert(path, bytes);
+    }
+
+    let manifest: BundleManifest = contents
+        .get(MANIFEST_NAME)
+        .ok_or("Bundle has no manifest.json")
+        .and_then(|bytes| serde_json::from_slice(bytes).map_err(|_| "Bundle manifest.json is invalid"))?;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err(format!("Unsupported bundle format {}", manifest.format));
+    }
+    for entry in &manifest.entries {
+        if entry.path != format!("{}/{}", entry.role.dir(), entry.relative) || !is_safe_relative(&entry.relative) {
+            return Err(format!("Bundle manifest has an invalid entry: {}", entry.path));
+        }
+        match contents.get(&entry.path) {
+            Some(bytes) if content_version(bytes) == entry.version => {}
+            Some(_) => return Err(format!("{} doesn't match the manifest; the bundle may be corrupt", entry.path)),
+            None => return Err(format!("Bundle is missing {}", entry.path)),
+        }
+    }
+    Ok((manifest, contents))
+}
+
+fn destination(layout: &ScopeLayout, entry: &BundleEntry) -> Option<PathBuf> {
+    match entry.role {
+        BundleRole::Memory => layout.memory.iter().find(|(name, _)| *name == entry.relative).map(|(_, p)| p.clone()),
+        BundleRole::Settings => Some(layout.settings.clone()),
+        BundleRole::Mcp => Some(layout.mcp.clone()),
+        role => layout.dirs.iter().find(|(r, _)| *r == role).map(|(_, dir)| dir.join(&entry.relative)),
+    }
+}
+
+fn text_diff(old: &[u8], new: &[u8], label: &str) -> Option<String> {
+    let (old, new) = (std::str::from_utf8(old).ok()?, std::str::from_utf8(new).ok()?);
+    Some(TextDiff::from_lines(old, new).unified_diff().header(&format!("current/{}", label), &format!("bundle/{}", label)).to_string())
+}
+
+/// MCP servers are compared and merged one at a time so importing never
+/// drops servers the target already has.
+fn mcp_items(entry: &BundleEntry, bytes: &[u8], dest: &Path, items: &mut Vec<ImportItem>) -> Result<(), String> {
+    let bundled: Value = serde_json::from_slice(bytes).map_err(|e| format!("Invalid {}: {}", entry.path, e))?;
+    let existing = crate::mcp::read_json_object(dest)?;
+    let Some(servers) = bundled.get("mcpServers").and_then(Value::as_object) else { return Ok(()) };
+
+    for (name, config) in servers {
+        let prefix = format!("mcpServers.{}.", name);
+        let current = existing.get("mcpServers").and_then(|s| s.get(name));
+        let status = match current {
+            None => ImportStatus::New,
+            Some(current) if current == config => ImportStatus::Identical,
+            Some(_) => ImportStatus::Conflict,
+        };
+        let diff = match current {
+            Some(current) if status == ImportStatus::Conflict => {
+                text_diff(&pretty_json(current)?, &pretty_json(config)?, &format!("mcpServers/{}", name))
+            }
+            _ => None,
+        };
+        items.push(ImportItem {
+            id: format!("{}#{}", entry.path, name),
+            role: BundleRole::Mcp,
+            destination: Some(dest.to_string_lossy().into_owned()),
+            status,
+            diff,
+            redacted: entry.redacted.iter().filter(|r| r.starts_with(&prefix)).cloned().collect(),
+            applied: false,
+        });
+    }
+    Ok(())
+}
+
+fn preview(manifest: &BundleManifest, contents: &HashMap<String, Vec<u8>>, layout: &ScopeLayout) -> Result<Vec<ImportItem>, String> {
+    let mut items = Vec::new();
+    for entry in &manifest.entries {
+        let bytes = &contents[&entry.path];
+        let dest = destination(layout, entry);
+        if let (BundleRole::Mcp, Some(dest)) = (entry.role, &dest) {
+            mcp_items(entry, bytes, dest, &mut items)?;
+            continue;
+        }
+        let current = dest.as_ref().and_then(|d| fs::read(d).ok());
+        let status = match (&dest, &current) {
+            (None, _) => ImportStatus::Unmapped,
+            (Some(_), None) => ImportStatus::New,
+            (Some(_), Some(current)) if current == bytes => ImportStatus::Identical,
+            (Some(_), Some(_)) => ImportStatus::Conflict,
+        };
+        items.push(ImportItem {
+            id: entry.path.clone(),
+            role: entry.role,
+            destination: dest.map(|d| d.to_string_lossy().into_owned()),
+            status,
+            diff: current.filter(|_| status == ImportStatus::Conflict).and_then(|c| text_diff(&c, bytes, &entry.path)),
+            redacted: entry.redacted.clone(),
+            applied: false,
+        });
+    }
+    Ok(items)
+}
+
+/// Preview or apply `archive` against `layout`. `check` vets every
+/// destination before anything is written.
+fn import_into(
+    archive: &Path,
+    layout: &ScopeLayout,
+    apply: bool,
+    overwrite: &[String],
+    check: impl Fn(&str) -> Result<PathBuf, String>,
+) -> Result<ImportReport, String> {
+    let (manifest, contents) = read_bundle(archive)?;
+    let mut items = preview(&manifest, &contents, layout)?;
+    if !apply {
+        return Ok(ImportReport { manifest, items });
+    }
+
+    let overwrite: HashSet<&str> = overwrite.iter().map(String::as_str).collect();
+    let wanted = |item: &ImportItem| match item.status {
+        ImportStatus::New => true,
+        ImportStatus::Conflict => overwrite.contains(item.id.as_str()),
+        ImportStatus::Identical | ImportStatus::Unmapped => false,
+    };
+    // Check every destination before writing any, so a refused path doesn't
+    // leave a half-applied import behind.
+    for item in items.iter().filter(|i| wanted(i)) {
+        if let Some(dest) = &item.destination {
+            check(dest)?;
+        }
+    }
+
+    let mut servers = Map::new();
+    for item in items.iter_mut() {
+        let Some(dest) = item.destination.as_ref().filter(|_| wanted(item)) else { continue };
+
+        if let Some((path, name)) = item.id.split_once('#') {
+            let config = serde_json::from_slice::<Value>(&contents[path]).ok().and_then(|v| v["mcpServers"].get(name).cloned());
+            if let Some(config) = config {
+                servers.insert(name.to_string(), config);
+                item.applied = true;
+            }
+            continue;
+        }
+        write_with_backup(Path::new(dest), &contents[&item.id])?;
+        item.applied = true;
+    }
+
+    if !servers.is_empty() {
+        crate::mcp::update_json_file(&layout.mcp, |root| {
+            let map = root.as_object_mut().ok_or("MCP config must contain a JSON object")?;
+            let existing = map.entry("mcpServers").or_insert_with(|| Value::Object(Map::new()));
+            let existing = existing.as_object_mut().ok_or("mcpServers must be an object")?;
+            for (name, config) in &servers {
+                existing.insert(name.clone(), config.clone());
+            }
+            Ok(())
+        })?;
+    }
+    Ok(ImportReport { manifest, items })
+}
+
+pub fn import(archive: &Path, target: BundleScope, project_path: Option<&str>, apply: bool, overwrite: &[String]) -> Result<ImportReport, String> {
+    let layout = scope_layout(target, project_path)?;
+    import_into(archive, &layout, apply, overwrite, crate::sandbox::check_path)
+}
+
+/// Package a scope's configuration into a .tar.gz with a manifest. Values of
+/// `env` blocks (and MCP `headers`) are replaced with `${NAME}` placeholders.
+/// `paths` limits the export to files under those paths.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn export_bundle(
+    scope: BundleScope,
+    paths: Option<Vec<String>>,
+    dest: String,
+    project_path: Option<String>,
+) -> Result<BundleExport, String> {
+    let include: Vec<PathBuf> = paths.unwrap_or_default().into_iter().map(PathBuf::from).collect();
+    tauri::async_runtime::spawn_blocking(move || export(scope, project_path.as_deref(), &include, Path::new(&dest)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Compare a bundle with the target scope. Nothing is written unless `apply`
+/// is set; even then conflicting files are only replaced when their item id
+/// is listed in `overwrite`.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn import_bundle(
+    archive: String,
+    target_scope: BundleScope,
+    project_path: Option<String>,
+    apply: Option<bool>,
+    overwrite: Option<Vec<String>>,
+) -> Result<ImportReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        import(
+            Path::new(&archive),
+            target_scope,
+            project_path.as_deref(),
+            apply.unwrap_or(false),
+            &overwrite.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn layout(root: &Path) -> ScopeLayout {
+        ScopeLayout {
+            memory: vec![("CLAUDE.md", root.join("CLAUDE.md")), (".claude/CLAUDE.md", root.join(".claude/CLAUDE.md"))],
+            settings: root.join(".claude/settings.json"),
+            mcp: root.join(".mcp.json"),
+            dirs: vec![(BundleRole::Agents, root.join(".claude/agents")), (BundleRole::Rules, root.join(".claude/rules"))],
+        }
+    }
+
+    fn put(path: PathBuf, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    /// A source project with one of everything, exported to `bundle.tar.gz`.
+    fn exported(temp: &TempDir) -> (PathBuf, BundleManifest) {
+        let source = temp.path().join("source");
+        put(source.join("CLAUDE.md"), "root memory\n");
+        put(source.join(".claude/CLAUDE.md"), "nested memory\n");
+        put(source.join(".claude/settings.json"), r#"{"model": "opus", "env": {"API_KEY": "sk-live-123", "REF": "${REF}"}}"#);
+        put(
+            source.join(".mcp.json"),
+            r#"{"mcpServers": {"api": {"url": "https://x", "headers": {"Authorization": "Bearer abc"}}, "local": {"command": "srv"}}}"#,
+        );
+        put(source.join(".claude/agents/review.md"), "reviewer\n");
+        put(source.join(".claude/agents/.DS_Store"), "hidden");
+        put(source.join(".claude/rules/style/rust.md"), "rules\n");
+
+        let (manifest, archive) = build_archive(&layout(&source), &[], BundleScope::Project, "source").unwrap();
+        let path = temp.path().join("bundle.tar.gz");
+        fs::write(&path, archive).unwrap();
+        (path, manifest)
+    }
+
+    fn allow(path: &str) -> Result<PathBuf, String> {
+        Ok(PathBuf::from(path))
+    }
+
+    /// A gzipped tar built from raw headers, so paths and sizes the tar
+    /// crate would refuse to write can be tested.
+    fn raw_archive(temp: &TempDir, entries: &[(&str, &[u8], u64)]) -> PathBuf {
+        let mut tar = Vec::new();
+        for (path, data, size) in entries {
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_size(*size);
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_cksum();
+            tar.extend_from_slice(header.as_bytes());
+            tar.extend_from_slice(data);
+            tar.resize(tar.len().next_multiple_of(512), 0);
+        }
+        tar.resize(tar.len() + 1024, 0);
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&tar).unwrap();
+        let path = temp.path().join("crafted.tar.gz");
+        fs::write(&path, gz.finish().unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn rejects_unsafe_paths() {
+        assert!(is_safe_relative("agents/review.md"));
+        for path in ["", "../x", "agents/../../x", "/etc/passwd", "./x"] {
+            assert!(!is_safe_relative(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn redacts_env_and_headers() {
+        let temp = TempDir::new().unwrap();
+        let (archive, manifest) = exported(&temp);
+        let (_, contents) = read_bundle(&archive).unwrap();
+
+        let settings: Value = serde_json::from_slice(&contents["settings/settings.json"]).unwrap();
+        assert_eq!(settings["env"], serde_json::json!({"API_KEY": "${API_KEY}", "REF": "${REF}"}));
+        let mcp: Value = serde_json::from_slice(&contents["mcp/mcp.json"]).unwrap();
+        assert_eq!(mcp["mcpServers"]["api"]["headers"]["Authorization"], "${AUTHORIZATION}");
+
+        let redacted: Vec<&String> = manifest.entries.iter().flat_map(|e| &e.redacted).collect();
+        assert_eq!(redacted, ["env.API_KEY", "mcpServers.api.headers.Authorization"]);
+        assert!(!contents.keys().any(|k| k.contains(".DS_Store")));
+    }
+
+    #[test]
+    fn round_trips_into_an_empty_project() {
+        let temp = TempDir::new().unwrap();
+        let (archive, manifest) = exported(&temp);
+        let target = temp.path().join("target");
+        let report = import_into(&archive, &layout(&target), true, &[], allow).unwrap();
+
+        assert_eq!(report.manifest.entries.len(), manifest.entries.len());
+        assert!(report.items.iter().all(|i| i.status == ImportStatus::New && i.applied));
+        assert_eq!(fs::read_to_string(target.join(".claude/rules/style/rust.md")).unwrap(), "rules\n");
+        assert_eq!(fs::read_to_string(target.join(".claude/CLAUDE.md")).unwrap(), "nested memory\n");
+        let mcp = crate::mcp::read_json_object(&target.join(".mcp.json")).unwrap();
+        assert_eq!(mcp["mcpServers"]["local"]["command"], "srv");
+
+        // Importing again finds nothing to do
+        let again = import_into(&archive, &layout(&target), false, &[], allow).unwrap();
+        assert!(again.items.iter().all(|i| i.status == ImportStatus::Identical));
+    }
+
+    #[test]
+    fn previews_against_existing_config() {
+        let temp = TempDir::new().unwrap();
+        crate::storage::set_test_data_dir(&temp.path().join("data"));
+        let (archive, _) = exported(&temp);
+        let target = temp.path().join("target");
+        put(target.join("CLAUDE.md"), "root memory\n");
+        put(target.join(".claude/agents/review.md"), "a different reviewer\n");
+        put(target.join(".mcp.json"), r#"{"mcpServers": {"local": {"command": "other"}, "mine": {"command": "keep"}}}"#);
+        // A user-style layout has no .claude/CLAUDE.md
+        let mut narrow = layout(&target);
+        narrow.memory.truncate(1);
+
+        let report = import_into(&archive, &narrow, false, &[], allow).unwrap();
+        let status = |id: &str| report.items.iter().find(|i| i.id == id).map(|i| i.status);
+        assert_eq!(status("memory/CLAUDE.md"), Some(ImportStatus::Identical));
+        assert_eq!(status("memory/.claude/CLAUDE.md"), Some(ImportStatus::Unmapped));
+        assert_eq!(status("agents/review.md"), Some(ImportStatus::Conflict));
+        assert_eq!(status("rules/style/rust.md"), Some(ImportStatus::New));
+        assert_eq!(status("mcp/mcp.json#api"), Some(ImportStatus::New));
+        assert_eq!(status("mcp/mcp.json#local"), Some(ImportStatus::Conflict));
+        let conflict = report.items.iter().find(|i| i.id == "agents/review.md").unwrap();
+        assert!(conflict.diff.as_deref().unwrap().contains("+reviewer"));
+
+        // Conflicts are only written when listed, and other servers survive
+        let overwrite = vec!["mcp/mcp.json#local".to_string()];
+        import_into(&archive, &narrow, true, &overwrite, allow).unwrap();
+        assert_eq!(fs::read_to_string(target.join(".claude/agents/review.md")).unwrap(), "a different reviewer\n");
+        let mcp = crate::mcp::read_json_object(&target.join(".mcp.json")).unwrap();
+        assert_eq!(mcp["mcpServers"]["local"]["command"], "srv");
+        assert_eq!(mcp["mcpServers"]["mine"]["command"], "keep");
+        assert!(!target.join(".claude/CLAUDE.md").exists());
+        assert_eq!(fs::read_dir(temp.path().join("data/backups")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn refused_destinations_leave_nothing_written() {
+        let temp = TempDir::new().unwrap();
+        let (archive, _) = exported(&temp);
+        let target = temp.path().join("target");
+        let deny_rules = |path: &str| if path.contains("rules") { Err("not allowed".to_string()) } else { Ok(PathBuf::from(path)) };
+        assert!(import_into(&archive, &layout(&target), true, &[], deny_rules).is_err());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn rejects_malicious_archives() {
+        let temp = TempDir::new().unwrap();
+        let manifest = br#"{"format": 1, "created_at": 0, "scope": "project", "source": "x", "entries": []}"#;
+        for path in ["../evil.md", "/tmp/evil.md", "agents/../../evil.md"] {
+            let archive = raw_archive(&temp, &[(MANIFEST_NAME, manifest, manifest.len() as u64), (path, b"x", 1)]);
+            assert_eq!(read_bundle(&archive).unwrap_err(), format!("Bundle contains an unsafe path: {}", path));
+        }
+
+        let archive = raw_archive(&temp, &[("agents/huge.md", b"", MAX_BUNDLE_BYTES + 1)]);
+        assert_eq!(read_bundle(&archive).unwrap_err(), "Bundle is too large");
+
+        // The manifest can't point entries outside their role either
+        let version = content_version(b"x");
+        let manifest = format!(
+            r#"{{"format": 1, "created_at": 0, "scope": "project", "source": "x", "entries": [{{"path": "agents/../x", "role": "agents", "relative": "../x", "size": 1, "version": "{}"}}]}}"#,
+            version
+        );
+        let archive = raw_archive(&temp, &[(MANIFEST_NAME, manifest.as_bytes(), manifest.len() as u64)]);
+        assert!(read_bundle(&archive).unwrap_err().starts_with("Bundle manifest has an invalid entry"));
+
+        // Contents that don't match the manifest
+        let manifest = format!(
+            r#"{{"format": 1, "created_at": 0, "scope": "project", "source": "x", "entries": [{{"path": "agents/a.md", "role": "agents", "relative": "a.md", "size": 1, "version": "{}"}}]}}"#,
+            version
+        );
+        let archive = raw_archive(&temp, &[(MANIFEST_NAME, manifest.as_bytes(), manifest.len() as u64), ("agents/a.md", b"y", 1)]);
+        assert!(read_bundle(&archive).unwrap_err().contains("doesn't match the manifest"));
+    }
+}
@@ -0,0 +1,221 @@
+//! `claude-config`: the GUI's discovery, validation and resolution logic for
+//! scripts and CI.
+
+use serde::Serialize;
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::doctor::{validate_config, DoctorReport, ValidationReport};
+
+/// `println!` that ignores a closed stdout (e.g. piped into `head`) instead
+/// of panicking.
+macro_rules! out {
+    ($($arg:tt)*) => {{
+        use std::io::Write;
+        let _ = writeln!(std::io::stdout(), $($arg)*);
+    }};
+}
+
+const USAGE: &str = "Usage: claude-config [--json] <command> [args]
+
+Commands:
+  paths                          Enterprise and user config locations
+  projects <dir> [--depth N]     Projects under a directory
+  discover <project> [--depth N] CLAUDE.md files in a project's subdirectories
+  validate [project]             Validate settings, MCP servers, agents, commands and skills
+                                 (the project's files, or user-level files without one);
+                                 exits with status 1 if there are errors
+  effective <project>            Merged settings and the file each value comes from
+  doctor [project]               Check config locations, files, MCP commands, hooks and
+                                 conflicting settings; exits with status 1 on errors
+
+Options:
+  --json                         Machine-readable output
+  -h, --help                     Show this help";
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "info",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+fn print_diagnostic(d: &Diagnostic) {
+    let position = match (d.line, d.column) {
+        (Some(line), Some(column)) => format!("{}:{} ", line, column),
+        _ => String::new(),
+    };
+    out!("  {}{}: {}", position, severity_label(d.severity), d.message);
+    if let Some(suggestion) = &d.suggestion {
+        out!("      {}", suggestion);
+    }
+}
+
+fn print_validation(report: &ValidationReport) {
+    for file in report.files.iter().filter(|f| !f.diagnostics.is_empty()) {
+        out!("{} ({})", file.path, file.kind);
+        file.diagnostics.iter().for_each(print_diagnostic);
+    }
+    out!("{} file(s) checked, {} error(s), {} warning(s)", report.files.len(), report.errors, report.warnings);
+}
+
+fn print_doctor(report: &DoctorReport) {
+    for f in &report.findings {
+        let location = match (&f.path, f.line) {
+            (Some(path), Some(line)) => format!("{}:{}: ", path, line),
+            (Some(path), None) => format!("{}: ", path),
+            _ => String::new(),
+        };
+        let category = serde_json::to_value(f.category).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        out!("{} [{}] {}{}", severity_label(f.severity), category, location, f.message);
+        if let Some(suggestion) = &f.suggestion {
+            out!("      {}", suggestion);
+        }
+    }
+    out!(
+        "{} file(s) validated, {} error(s), {} warning(s), {} note(s)",
+        report.files_checked, report.errors, report.warnings, report.infos
+    );
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    out!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+}
+
+fn exists_mark(info: &crate::ConfigPathInfo) -> &'static str {
+    if info.exists { "" } else { " (missing)" }
+}
+
+struct Args {
+    json: bool,
+    depth: Option<usize>,
+    positional: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args { json: false, depth: None, positional: Vec::new() };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => parsed.json = true,
+            "--depth" => {
+                let value = args.next().ok_or("--depth needs a value")?;
+                parsed.depth = Some(value.parse().map_err(|_| format!("Invalid depth \"{}\"", value))?);
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ => parsed.positional.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+/// The project argument, if given. A typo must not turn into an empty (and
+/// passing) check, so a missing directory is a usage error.
+fn project_arg<'a>(positional: &[&'a str]) -> Result<Option<&'a Path>, String> {
+    match positional.get(1).map(|p| Path::new(*p)) {
+        Some(project) if !project.is_dir() => Err(format!("{} is not a directory", project.display())),
+        project => Ok(project),
+    }
+}
+
+fn run(args: Args) -> Result<ExitCode, String> {
+    let positional: Vec<&str> = args.positional.iter().map(String::as_str).collect();
+    let json = args.json;
+    match positional.as_slice() {
+        ["paths"] => {
+            let paths = crate::get_config_paths();
+            if json {
+                print_json(&paths);
+            } else {
+                let (e, u) = (&paths.enterprise, &paths.user);
+                out!("Enterprise");
+                for (label, info) in [("CLAUDE.md", &e.claude_md), ("managed-settings", &e.managed_settings), ("managed-mcp", &e.managed_mcp)] {
+                    out!("  {:<20} {}{}", label, info.path, exists_mark(info));
+                }
+                out!("User");
+                for (label, info) in [
+                    ("CLAUDE.md", &u.claude_md),
+                    ("CLAUDE.local.md", &u.claude_local_md),
+                    ("settings", &u.settings),
+                    ("settings.local", &u.settings_local),
+                    ("mcp", &u.mcp),
+                    ("agents", &u.agents),
+                    ("commands", &u.commands),
+                    ("skills", &u.skills),
+                ] {
+                    out!("  {:<20} {}{}", label, info.path, exists_mark(info));
+                }
+            }
+        }
+        ["projects", dir] => {
+            let mut options = crate::discovery::ScanOptions::default();
+            if let Some(depth) = args.depth {
+                options.max_depth = depth;
+            }
+            let projects = crate::discovery::scan_projects(Path::new(dir), &options)?;
+            if json {
+                print_json(&projects);
+            } else {
+                for project in &projects {
+                    out!("{:<30} {}{}", project.name, project.path, if project.has_claude_md { "  [CLAUDE.md]" } else { "" });
+                }
+            }
+        }
+        ["discover", project] => {
+            project_arg(&positional)?;
+            let found = crate::discover_subdirectory_claude_md(project.to_string(), args.depth.map(|d| d as u32));
+            if json {
+                print_json(&found);
+            } else {
+                for file in &found {
+                    out!("{:<30} {}", file.relative_path, file.full_path);
+                }
+            }
+        }
+        ["validate"] | ["validate", _] => {
+            let report = validate_config(project_arg(&positional)?);
+            if json { print_json(&report) } else { print_validation(&report) }
+            return Ok(if report.errors > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS });
+        }
+        ["effective", project] => {
+            project_arg(&positional)?;
+            let effective = crate::settings::resolve_effective_settings(Some(project.to_string()));
+            if json {
+                print_json(&effective);
+            } else {
+                print_json(&effective.settings);
+                out!("\nSources:");
+                for (pointer, source) in &effective.provenance {
+                    out!("  {:<40} {}", pointer, source.path);
+                }
+                for layer in effective.layers.iter().filter(|l| l.error.is_some()) {
+                    out!("Skipped {}: {}", layer.path, layer.error.as_deref().unwrap_or_default());
+                }
+            }
+        }
+        ["doctor"] | ["doctor", _] => {
+            let report = crate::doctor::doctor(project_arg(&positional)?);
+            if json { print_json(&report) } else { print_doctor(&report) }
+            return Ok(if report.errors > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS });
+        }
+        ["help"] | [] => out!("{}", USAGE),
+        _ => return Err(format!("Unknown command or wrong arguments: {}", positional.join(" "))),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        out!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match parse_args(args.into_iter()).and_then(run) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("claude-config: {}\n\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
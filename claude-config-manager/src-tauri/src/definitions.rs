@@ -0,0 +1,649 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::{closest_match, has_errors, Diagnostic, Severity};
+use crate::permissions::{parse_permission_rule, KNOWN_TOOLS};
+use crate::schema::PERMISSION_MODES;
+
+// ---------------------------------------------------------------------------
+// Typed frontmatter for agents, slash commands and skills. Unknown keys are
+// kept in `extra`, as in schema.rs.
+// ---------------------------------------------------------------------------
+
+/// Tool and skill lists are written either as a comma-separated string or as
+/// a YAML list; both become a list here.
+fn string_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ListOrString {
+        List(Vec<String>),
+        Text(String),
+    }
+    Ok(match Option::<ListOrString>::deserialize(deserializer)? {
+        None => None,
+        Some(ListOrString::List(items)) => Some(items),
+        Some(ListOrString::Text(text)) => Some(split_list(&text)),
+    })
+}
+
+/// Split on commas that aren't inside parentheses, so `Bash(git add:*, ls)`
+/// stays one entry.
+fn split_list(text: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    items.push(current);
+    items.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// `argument-hint: [file]` is how most commands write their hint, and YAML
+/// reads that as a list; turn it back into the text that was meant.
+fn argument_hint<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum HintOrList {
+        Text(String),
+        List(Vec<String>),
+    }
+    Ok(match Option::<HintOrList>::deserialize(deserializer)? {
+        None => None,
+        Some(HintOrList::Text(text)) => Some(text),
+        Some(HintOrList::List(items)) => Some(items.iter().map(|i| format!("[{}]", i)).collect::<Vec<_>>().join(" ")),
+    })
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentDefinition {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, deserialize_with = "string_list", skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<String>>,         // None = inherits every tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<String>,
+    #[serde(default, deserialize_with = "string_list", skip_serializing_if = "Option::is_none")]
+    pub skills: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct CommandDefinition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,        // Defaults to the first line of the body
+    #[serde(default, deserialize_with = "string_list", skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "argument_hint", skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_model_invocation: Option<bool>,   // Hides the command from the SlashCommand tool
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct SkillDefinition {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, deserialize_with = "string_list", skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionKind {
+    Agent,
+    Command,
+    Skill,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "kind", content = "fields", rename_all = "snake_case")]
+pub enum Definition {
+    Agent(AgentDefinition),
+    Command(CommandDefinition),
+    Skill(SkillDefinition),
+}
+
+const AGENT_FIELDS: &[&str] = &["name", "description", "tools", "model", "permissionMode", "skills"];
+const COMMAND_FIELDS: &[&str] = &["description", "allowed-tools", "argument-hint", "model", "disable-model-invocation"];
+const SKILL_FIELDS: &[&str] = &["name", "description", "allowed-tools", "model", "license", "metadata"];
+
+const MODEL_ALIASES: &[&str] = &["sonnet", "opus", "haiku", "inherit"];
+
+const MAX_SKILL_NAME: usize = 64;
+const MAX_SKILL_DESCRIPTION: usize = 1024;
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// A markdown file split into its frontmatter block and body. `yaml_line` is
+/// the 1-based line of the first YAML line, for mapping parser positions.
+pub struct FrontmatterSplit<'a> {
+    pub yaml: Option<&'a str>,
+    pub yaml_line: usize,
+    pub body: &'a str,
+    pub body_line: usize,
+    pub unclosed: bool,
+}
+
+/// Split frontmatter from a markdown file. The block must open on the first
+/// line and closes at the next line that is exactly `---`, so horizontal
+/// rules and `---` inside values don't end it early.
+pub fn split_frontmatter(text: &str) -> FrontmatterSplit<'_> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let no_frontmatter = FrontmatterSplit { yaml: None, yaml_line: 0, body: text, body_line: 1, unclosed: false };
+
+    let mut lines = text.split_inclusive('\n');
+    match lines.next() {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return no_frontmatter,
+    }
+
+    let yaml_start = text.find('\n').map(|i| i + 1).unwrap_or(text.len());
+    let mut offset = yaml_start;
+    for (index, line) in lines.enumerate() {
+        if line.trim_end() == "---" {
+            return FrontmatterSplit {
+                yaml: Some(&text[yaml_start..offset]),
+                yaml_line: 2,
+                body: &text[offset + line.len()..],
+                body_line: index + 3,
+                unclosed: false,
+            };
+        }
+        offset += line.len();
+    }
+    FrontmatterSplit { unclosed: true, ..no_frontmatter }
+}
+
+/// The kind of definition a file is, from where it lives: SKILL.md anywhere,
+/// otherwise the nearest `agents` or `commands` ancestor directory.
+pub fn infer_kind(path: &Path) -> Option<DefinitionKind> {
+    if path.file_name().and_then(|n| n.to_str()) == Some("SKILL.md") {
+        return Some(DefinitionKind::Skill);
+    }
+    path.ancestors().skip(1).find_map(|dir| match dir.file_name().and_then(|n| n.to_str()) {
+        Some("agents") => Some(DefinitionKind::Agent),
+        Some("commands") => Some(DefinitionKind::Command),
+        Some("skills") => Some(DefinitionKind::Skill),
+        _ => None,
+    })
+}
+
+/// 1-based line of a top-level key in the YAML block, if present.
+fn key_line(yaml: &str, yaml_line: usize, key: &str) -> Option<usize> {
+    yaml.lines()
+        .position(|line| {
+            line.strip_prefix(key)
+                .map(|rest| rest.trim_start().starts_with(':'))
+                .unwrap_or(false)
+        })
+        .map(|i| yaml_line + i)
+}
+
+#[derive(Serialize)]
+pub struct ParsedDefinition {
+    pub path: String,
+    pub kind: DefinitionKind,
+    pub frontmatter: Option<Value>,     // Raw frontmatter as JSON; None if the file has none
+    pub definition: Option<Definition>, // Typed view; None if the frontmatter didn't parse
+    pub body: String,
+    pub body_line: usize,               // 1-based line where the body starts
+    pub valid: bool,                    // No error-severity diagnostics
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Parse and validate one definition. `path` is only used for messages that
+/// depend on the file or directory name.
+pub fn parse_definition_text(path: &Path, kind: DefinitionKind, text: &str) -> ParsedDefinition {
+    let split = split_frontmatter(text);
+    let mut diagnostics = Vec::new();
+    let mut frontmatter = None;
+    let mut definition = None;
+
+    if split.unclosed {
+        diagnostics.push(
+            Diagnostic::new(Severity::Error, "", "Frontmatter is not closed")
+                .with_suggestion("Add a line containing only --- after the last field")
+                .at(1, 1),
+        );
+    }
+
+    match split.yaml {
+        None if kind == DefinitionKind::Command => {
+            if !split.unclosed {
+                diagnostics.push(Diagnostic::new(
+                    Severity::Info,
+                    "",
+                    "No frontmatter; Claude Code uses the first line as the description",
+                ));
+            }
+            definition = Some(Definition::Command(CommandDefinition::default()));
+        }
+        None => {
+            if !split.unclosed {
+                diagnostics.push(
+                    Diagnostic::new(Severity::Error, "", "Missing frontmatter")
+                        .with_suggestion("Start the file with a --- block containing name and description")
+                        .at(1, 1),
+                );
+            }
+        }
+        Some(yaml) => match serde_yaml::from_str::<Value>(yaml) {
+            Err(e) => {
+                // The message repeats positions relative to the YAML block; ours are absolute
+                let message = regex::Regex::new(r" at line \d+ column \d+").unwrap().replace_all(&e.to_string(), "").into_owned();
+                let diagnostic = Diagnostic::new(Severity::Error, "", format!("Invalid YAML: {}", message))
+                    .with_suggestion("Quote values that contain : # [ ] { } or start with a special character");
+                diagnostics.push(match e.location() {
+                    Some(at) => diagnostic.at(split.yaml_line + at.line() - 1, at.column()),
+                    None => diagnostic,
+                });
+            }
+            Ok(Value::Null) => frontmatter = Some(Value::Object(Map::new())),
+            Ok(value @ Value::Object(_)) => frontmatter = Some(value),
+            Ok(_) => diagnostics.push(
+                Diagnostic::new(Severity::Error, "", "Frontmatter must be a set of key: value fields").at(split.yaml_line, 1),
+            ),
+        },
+    }
+
+    if let Some(value) = &frontmatter {
+        let typed = match kind {
+            DefinitionKind::Agent => serde_json::from_value(value.clone()).map(Definition::Agent),
+            DefinitionKind::Command => serde_json::from_value(value.clone()).map(Definition::Command),
+            DefinitionKind::Skill => serde_json::from_value(value.clone()).map(Definition::Skill),
+        };
+        match typed {
+            Ok(typed) => {
+                validate(path, &typed, &mut diagnostics);
+                definition = Some(typed);
+            }
+            Err(e) => diagnostics.push(Diagnostic::new(Severity::Error, "", e.to_string())),
+        }
+
+        let yaml = split.yaml.unwrap_or_default();
+        for d in diagnostics.iter_mut().filter(|d| d.line.is_none() && !d.pointer.is_empty()) {
+            let key = d.pointer.split('.').next().unwrap_or_default();
+            if let Some(line) = key_line(yaml, split.yaml_line, key) {
+                d.line = Some(line);
+                d.column = Some(1);
+            }
+        }
+    }
+
+    ParsedDefinition {
+        path: path.to_string_lossy().into_owned(),
+        kind,
+        frontmatter,
+        definition,
+        body: split.body.to_string(),
+        body_line: split.body_line,
+        valid: !has_errors(&diagnostics),
+        diagnostics,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+fn required(value: &str, field: &str, out: &mut Vec<Diagnostic>) -> bool {
+    if value.trim().is_empty() {
+        out.push(Diagnostic::new(Severity::Error, field, format!("Missing required field: {}", field)));
+        return false;
+    }
+    true
+}
+
+fn check_unknown_fields(extra: &Map<String, Value>, known: &[&str], out: &mut Vec<Diagnostic>) {
+    for key in extra.keys() {
+        let diagnostic = Diagnostic::new(Severity::Warning, key, format!("Unknown field \"{}\"; Claude Code will ignore it", key));
+        out.push(match closest_match(key, known) {
+            Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+            None => diagnostic,
+        });
+    }
+}
+
+fn check_model(model: Option<&str>, out: &mut Vec<Diagnostic>) {
+    let Some(model) = model else { return };
+    if MODEL_ALIASES.contains(&model.to_lowercase().as_str()) || model.starts_with("claude-") {
+        return;
+    }
+    let diagnostic = Diagnostic::new(
+        Severity::Error,
+        "model",
+        format!("Unknown model \"{}\"; expected one of {} or a full model ID", model, MODEL_ALIASES.join(", ")),
+    );
+    out.push(match closest_match(model, MODEL_ALIASES) {
+        Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+        None => diagnostic,
+    });
+}
+
+/// Tools in an agent's `tools` or a command's `allowed-tools`. Entries use
+/// permission rule syntax, e.g. `Bash(git status:*)`.
+fn check_tools(tools: Option<&Vec<String>>, field: &str, out: &mut Vec<Diagnostic>) {
+    for (i, tool) in tools.into_iter().flatten().enumerate() {
+        let pointer = format!("{}.{}", field, i);
+        match parse_permission_rule(tool) {
+            Err(message) => out.push(Diagnostic::new(Severity::Error, pointer, message)),
+            Ok(rule) if rule.tool.starts_with("mcp__") || rule.tool == "*" || KNOWN_TOOLS.contains(&rule.tool.as_str()) => {}
+            Ok(rule) => {
+                let diagnostic = Diagnostic::new(Severity::Warning, pointer, format!("Unknown tool \"{}\"", rule.tool));
+                out.push(match closest_match(&rule.tool, KNOWN_TOOLS) {
+                    Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+                    None => diagnostic,
+                });
+            }
+        }
+    }
+}
+
+fn is_slug(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn validate(path: &Path, definition: &Definition, out: &mut Vec<Diagnostic>) {
+    match definition {
+        Definition::Agent(agent) => {
+            if required(&agent.name, "name", out) && !is_slug(&agent.name) {
+                out.push(Diagnostic::new(
+                    Severity::Error,
+                    "name",
+                    "Name must be lowercase letters, numbers, and hyphens only",
+                ));
+            }
+            required(&agent.description, "description", out);
+            check_tools(agent.tools.as_ref(), "tools", out);
+            check_model(agent.model.as_deref(), out);
+            if let Some(mode) = agent.permission_mode.as_deref() {
+                if mode != "ignore" && !PERMISSION_MODES.contains(&mode) {
+                    let diagnostic = Diagnostic::new(
+                        Severity::Error,
+                        "permissionMode",
+                        format!("Invalid permissionMode \"{}\"; expected one of: {}", mode, PERMISSION_MODES.join(", ")),
+                    );
+                    out.push(match closest_match(mode, PERMISSION_MODES) {
+                        Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+                        None => diagnostic,
+                    });
+                }
+            }
+            check_unknown_fields(&agent.extra, AGENT_FIELDS, out);
+        }
+        Definition::Command(command) => {
+            if command.description.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+                out.push(Diagnostic::new(
+                    Severity::Warning,
+                    "description",
+                    "No description; Claude Code uses the first line of the body",
+                ));
+            }
+            check_tools(command.allowed_tools.as_ref(), "allowed-tools", out);
+            check_model(command.model.as_deref(), out);
+            check_unknown_fields(&command.extra, COMMAND_FIELDS, out);
+        }
+        Definition::Skill(skill) => {
+            if required(&skill.name, "name", out) {
+                if skill.name.chars().count() > MAX_SKILL_NAME {
+                    out.push(Diagnostic::new(Severity::Error, "name", format!("Name must be {} characters or less", MAX_SKILL_NAME)));
+                }
+                if !is_slug(&skill.name) {
+                    out.push(Diagnostic::new(Severity::Error, "name", "Name must be lowercase letters, numbers, and hyphens only"));
+                }
+                if skill.name.contains("anthropic") || skill.name.contains("claude") {
+                    out.push(Diagnostic::new(Severity::Error, "name", "Name cannot contain \"anthropic\" or \"claude\""));
+                }
+                let dir_name = path.parent().and_then(|d| d.file_name()).and_then(|n| n.to_str());
+                if let Some(dir_name) = dir_name.filter(|d| *d != skill.name) {
+                    out.push(
+                        Diagnostic::new(
+                            Severity::Warning,
+                            "name",
+                            format!("Name \"{}\" doesn't match the skill directory \"{}\"", skill.name, dir_name),
+                        )
+                        .with_suggestion(format!("Rename the directory to \"{}\"", skill.name)),
+                    );
+                }
+            }
+            if required(&skill.description, "description", out) {
+                if skill.description.chars().count() > MAX_SKILL_DESCRIPTION {
+                    out.push(Diagnostic::new(
+                        Severity::Error,
+                        "description",
+                        format!("Description must be {} characters or less", MAX_SKILL_DESCRIPTION),
+                    ));
+                }
+                if regex::Regex::new(r"<[^>]+>").unwrap().is_match(&skill.description) {
+                    out.push(Diagnostic::new(Severity::Error, "description", "Description cannot contain XML tags"));
+                }
+            }
+            check_tools(skill.allowed_tools.as_ref(), "allowed-tools", out);
+            check_model(skill.model.as_deref(), out);
+            check_unknown_fields(&skill.extra, SKILL_FIELDS, out);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+pub fn parse_definition_file(path: &Path, kind: Option<DefinitionKind>) -> Result<ParsedDefinition, String> {
+    let kind = kind
+        .or_else(|| infer_kind(path))
+        .ok_or_else(|| format!("Can't tell whether {} is an agent, command or skill", path.display()))?;
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(parse_definition_text(path, kind, &text))
+}
+
+/// Parse one agent, command or SKILL.md file. `kind` is inferred from the
+/// path when omitted.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn parse_definition(path: String, kind: Option<DefinitionKind>) -> Result<ParsedDefinition, String> {
+    parse_definition_file(&crate::sandbox::check_path(&path)?, kind)
+}
+
+#[derive(Serialize)]
+pub struct DefinitionValidation {
+    pub path: String,
+    pub kind: DefinitionKind,
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Every definition file under `dir`: markdown files in agents/ and
+/// commands/, and SKILL.md in skills/.
+pub fn definition_files(dir: &Path) -> Vec<(PathBuf, DefinitionKind)> {
+    fn walk(dir: &Path, out: &mut Vec<(PathBuf, DefinitionKind)>) {
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        let mut entries: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                walk(&path, out);
+                continue;
+            }
+            if path.extension().and_then(|x| x.to_str()) != Some("md") {
+                continue;
+            }
+            match infer_kind(&path) {
+                Some(DefinitionKind::Skill) if path.file_name().and_then(|n| n.to_str()) != Some("SKILL.md") => {}
+                Some(kind) => out.push((path, kind)),
+                None => {}
+            }
+        }
+    }
+    let mut files = Vec::new();
+    walk(dir, &mut files);
+    files
+}
+
+/// Validate every definition under `dir`, which can be an agents, commands or
+/// skills directory or any parent of them (such as `.claude`).
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn validate_definitions(dir: String) -> Result<Vec<DefinitionValidation>, String> {
+    let dir = crate::sandbox::check_path(&dir)?;
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    Ok(definition_files(&dir)
+        .into_iter()
+        .map(|(path, kind)| match fs::read_to_string(&path) {
+            Ok(text) => {
+                let parsed = parse_definition_text(&path, kind, &text);
+                DefinitionValidation {
+                    path: parsed.path,
+                    kind,
+                    valid: parsed.valid,
+                    diagnostics: parsed.diagnostics,
+                }
+            }
+            Err(e) => DefinitionValidation {
+                path: path.to_string_lossy().into_owned(),
+                kind,
+                valid: false,
+                diagnostics: vec![Diagnostic::new(Severity::Error, "", format!("Failed to read file: {}", e))],
+            },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(path: &str, kind: DefinitionKind, text: &str) -> Vec<(Severity, String, String)> {
+        parse_definition_text(Path::new(path), kind, text)
+            .diagnostics
+            .into_iter()
+            .map(|d| (d.severity, d.pointer, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn frontmatter_closes_at_the_first_bare_rule() {
+        let split = split_frontmatter("---\nname: a\ndescription: x --- y\n---\nIntro\n\n---\n\nMore\n");
+        assert_eq!(split.yaml, Some("name: a\ndescription: x --- y\n"));
+        assert_eq!(split.yaml_line, 2);
+        assert_eq!(split.body, "Intro\n\n---\n\nMore\n");
+        assert_eq!(split.body_line, 5);
+        assert!(!split.unclosed);
+    }
+
+    #[test]
+    fn frontmatter_handles_crlf_bom_and_missing_blocks() {
+        let split = split_frontmatter("\u{feff}---\r\nname: a\r\n---\r\nBody\r\n");
+        assert_eq!(split.yaml, Some("name: a\r\n"));
+        assert_eq!(split.body, "Body\r\n");
+        assert_eq!(split.body_line, 4);
+
+        let none = split_frontmatter("Just a prompt\n---\n");
+        assert_eq!(none.yaml, None);
+        assert_eq!(none.body, "Just a prompt\n---\n");
+
+        let unclosed = split_frontmatter("---\nname: a\n");
+        assert!(unclosed.unclosed);
+        assert_eq!(unclosed.yaml, None);
+
+        let empty = split_frontmatter("---\n---\n");
+        assert_eq!(empty.yaml, Some(""));
+        assert_eq!(empty.body, "");
+    }
+
+    #[test]
+    fn validates_agents() {
+        let agent = "---\nname: Code Reviewer\ndescription: Reviews code\ntools: Read, Bash(git diff:*), Grpe\nmodel: sonet\npermissionMode: acceptEdit\ncolour: blue\n---\nYou review code.\n";
+        let found = messages("/p/.claude/agents/reviewer.md", DefinitionKind::Agent, agent);
+        let at = |pointer: &str| found.iter().find(|(_, p, _)| p == pointer).unwrap_or_else(|| panic!("no diagnostic at {}", pointer));
+        assert_eq!(at("name").0, Severity::Error);
+        assert_eq!(at("tools.2").2, "Unknown tool \"Grpe\"");
+        assert_eq!(at("model").0, Severity::Error);
+        assert_eq!(at("permissionMode").0, Severity::Error);
+        assert_eq!(at("colour").0, Severity::Warning);
+        assert_eq!(found.len(), 5);
+
+        let parsed = parse_definition_text(Path::new("a.md"), DefinitionKind::Agent, agent);
+        assert_eq!(parsed.diagnostics.iter().find(|d| d.pointer == "model").unwrap().line, Some(5));
+        assert!(parse_definition_text(Path::new("a.md"), DefinitionKind::Agent, "---\nname: ok\ndescription: d\n---\n").valid);
+        assert_eq!(messages("a.md", DefinitionKind::Agent, "No frontmatter")[0].2, "Missing frontmatter");
+    }
+
+    #[test]
+    fn validates_commands() {
+        let plain = messages("/p/.claude/commands/fix.md", DefinitionKind::Command, "Fix the bug\n");
+        assert_eq!(plain, [(Severity::Info, String::new(), "No frontmatter; Claude Code uses the first line as the description".into())]);
+
+        let command = "---\nallowed-tools: Bash(git add:*, git status)\nargument-hint: [file]\ndisable-model-invocation: true\n---\nReview $ARGUMENTS\n";
+        let parsed = parse_definition_text(Path::new("review.md"), DefinitionKind::Command, command);
+        let Some(Definition::Command(typed)) = &parsed.definition else { panic!("not a command") };
+        assert_eq!(typed.allowed_tools.as_deref(), Some(&["Bash(git add:*, git status)".to_string()][..]));
+        assert_eq!(typed.argument_hint.as_deref(), Some("[file]"));
+        assert_eq!(typed.disable_model_invocation, Some(true));
+        assert_eq!(parsed.diagnostics.len(), 1);
+        assert_eq!(parsed.diagnostics[0].pointer, "description");
+
+        let bad_yaml = parse_definition_text(Path::new("x.md"), DefinitionKind::Command, "---\ndescription: [unclosed\n---\nx");
+        assert!(!bad_yaml.valid);
+        assert_eq!(bad_yaml.diagnostics[0].line, Some(3));
+        let unclosed = messages("x.md", DefinitionKind::Command, "---\ndescription: x\n");
+        assert_eq!(unclosed[0].2, "Frontmatter is not closed");
+    }
+
+    #[test]
+    fn validates_skills() {
+        let skill = format!("---\nname: claude-pdf\ndescription: Handles <b>PDFs</b> {}\n---\nbody", "x".repeat(MAX_SKILL_DESCRIPTION));
+        let found = messages("/p/.claude/skills/pdf-tools/SKILL.md", DefinitionKind::Skill, &skill);
+        let texts: Vec<&str> = found.iter().map(|(_, _, m)| m.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "Name cannot contain \"anthropic\" or \"claude\"",
+                "Name \"claude-pdf\" doesn't match the skill directory \"pdf-tools\"",
+                "Description must be 1024 characters or less",
+                "Description cannot contain XML tags",
+            ]
+        );
+
+        let ok = "---\nname: pdf-tools\ndescription: Fill PDF forms\nallowed-tools: [Read, Write]\nmetadata:\n  version: 1\n---\n";
+        let parsed = parse_definition_text(Path::new("/p/.claude/skills/pdf-tools/SKILL.md"), DefinitionKind::Skill, ok);
+        assert!(parsed.diagnostics.is_empty(), "{:?}", parsed.diagnostics);
+        assert_eq!(messages("/s/SKILL.md", DefinitionKind::Skill, "---\nname: s\n---\n")[0].2, "Missing required field: description");
+    }
+}
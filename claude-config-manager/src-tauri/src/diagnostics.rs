@@ -0,0 +1,279 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A problem found in a config file. `pointer` is a JSON pointer for JSON
+/// files and a dotted field path for frontmatter; line and column are 1-based.
+#[derive(Serialize, Clone, Debug)]
+pub struct Diagnostic {
+    pub pointer: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, pointer: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            pointer: pointer.into(),
+            line: None,
+            column: None,
+            severity,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut cur = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// The candidate most likely meant by a misspelled `input`, if any is close.
+pub fn closest_match<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let lower = input.to_lowercase();
+    if let Some(exact) = candidates.iter().find(|c| c.to_lowercase() == lower) {
+        return Some(exact);
+    }
+    let limit = (input.chars().count() / 3).max(2);
+    candidates
+        .iter()
+        .map(|c| (edit_distance(&lower, &c.to_lowercase()), *c))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// Maps JSON pointers to 1-based (line, column) positions in the source text.
+/// Object members point at their key, array items at the value.
+pub struct JsonLocator {
+    positions: HashMap<String, usize>,
+    line_starts: Vec<usize>,
+    text: String,
+}
+
+impl JsonLocator {
+    /// Build a locator for well-formed JSON; malformed input yields a
+    /// locator that only knows the positions seen before the error.
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        let mut locator = JsonLocator {
+            positions: HashMap::new(),
+            line_starts,
+            text: text.to_string(),
+        };
+        let mut pos = 0;
+        locator.parse_value(text.as_bytes(), &mut pos, String::new());
+        locator
+    }
+
+    pub fn locate(&self, pointer: &str) -> Option<(usize, usize)> {
+        let offset = *self.positions.get(pointer)?;
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let column = self.text[self.line_starts[line]..offset].chars().count() + 1;
+        Some((line + 1, column))
+    }
+
+    /// Fill in line/column on diagnostics that have a pointer but no position.
+    pub fn annotate(&self, diagnostics: &mut [Diagnostic]) {
+        for d in diagnostics.iter_mut().filter(|d| d.line.is_none()) {
+            if let Some((line, column)) = self.locate(&d.pointer) {
+                d.line = Some(line);
+                d.column = Some(column);
+            }
+        }
+    }
+
+    fn skip_ws(bytes: &[u8], pos: &mut usize) {
+        while *pos < bytes.len() && bytes[*pos].is_ascii_whitespace() {
+            *pos += 1;
+        }
+    }
+
+    fn parse_string(bytes: &[u8], pos: &mut usize) -> Option<String> {
+        let start = *pos;
+        *pos += 1;
+        while *pos < bytes.len() {
+            match bytes[*pos] {
+                b'\\' => *pos += 2,
+                b'"' => {
+                    *pos += 1;
+                    let raw = std::str::from_utf8(&bytes[start..*pos]).ok()?;
+                    return serde_json::from_str(raw).ok();
+                }
+                _ => *pos += 1,
+            }
+        }
+        None
+    }
+
+    fn parse_value(&mut self, bytes: &[u8], pos: &mut usize, pointer: String) -> bool {
+        Self::skip_ws(bytes, pos);
+        if *pos >= bytes.len() {
+            return false;
+        }
+        self.positions.entry(pointer.clone()).or_insert(*pos);
+
+        match bytes[*pos] {
+            b'{' => {
+                *pos += 1;
+                loop {
+                    Self::skip_ws(bytes, pos);
+                    match bytes.get(*pos) {
+                        Some(b'}') => {
+                            *pos += 1;
+                            return true;
+                        }
+                        Some(b'"') => {}
+                        _ => return false,
+                    }
+                    let key_start = *pos;
+                    let Some(key) = Self::parse_string(bytes, pos) else { return false };
+                    let child = format!("{}/{}", pointer, crate::settings::pointer_segment(&key));
+                    self.positions.insert(child.clone(), key_start);
+
+                    Self::skip_ws(bytes, pos);
+                    if bytes.get(*pos) != Some(&b':') {
+                        return false;
+                    }
+                    *pos += 1;
+                    if !self.parse_value(bytes, pos, child) {
+                        return false;
+                    }
+                    Self::skip_ws(bytes, pos);
+                    match bytes.get(*pos) {
+                        Some(b',') => *pos += 1,
+                        Some(b'}') => {
+                            *pos += 1;
+                            return true;
+                        }
+                        _ => return false,
+                    }
+                }
+            }
+            b'[' => {
+                *pos += 1;
+                let mut index = 0;
+                loop {
+                    Self::skip_ws(bytes, pos);
+                    if bytes.get(*pos) == Some(&b']') {
+                        *pos += 1;
+                        return true;
+                    }
+                    if !self.parse_value(bytes, pos, format!("{}/{}", pointer, index)) {
+                        return false;
+                    }
+                    index += 1;
+                    Self::skip_ws(bytes, pos);
+                    match bytes.get(*pos) {
+                        Some(b',') => *pos += 1,
+                        Some(b']') => {
+                            *pos += 1;
+                            return true;
+                        }
+                        _ => return false,
+                    }
+                }
+            }
+            b'"' => Self::parse_string(bytes, pos).is_some(),
+            _ => {
+                while *pos < bytes.len() && !matches!(bytes[*pos], b',' | b'}' | b']') && !bytes[*pos].is_ascii_whitespace() {
+                    *pos += 1;
+                }
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_keys_and_array_items() {
+        let text = "{\n  \"model\": \"opus\",\n  \"permissions\": {\n    \"allow\": [\"Read\",\n      \"Bash(ls)\"]\n  }\n}";
+        let locator = JsonLocator::new(text);
+        assert_eq!(locator.locate(""), Some((1, 1)));
+        assert_eq!(locator.locate("/model"), Some((2, 3)));
+        assert_eq!(locator.locate("/permissions/allow"), Some((4, 5)));
+        assert_eq!(locator.locate("/permissions/allow/0"), Some((4, 15)));
+        assert_eq!(locator.locate("/permissions/allow/1"), Some((5, 7)));
+        assert_eq!(locator.locate("/permissions/deny"), None);
+    }
+
+    #[test]
+    fn columns_count_characters_and_keys_are_escaped() {
+        let text = "{\"naïve\": 1, \"a/b\": {\"~x\": [true]}}\r\n";
+        let locator = JsonLocator::new(text);
+        assert_eq!(locator.locate("/naïve"), Some((1, 2)));
+        assert_eq!(locator.locate("/a~1b"), Some((1, 14)));
+        assert_eq!(locator.locate("/a~1b/~0x/0"), Some((1, 29)));
+
+        let crlf = JsonLocator::new("{\r\n  \"env\": {\r\n    \"A\": \"1\"\r\n  }\r\n}");
+        assert_eq!(crlf.locate("/env/A"), Some((3, 5)));
+    }
+
+    #[test]
+    fn malformed_json_keeps_earlier_positions() {
+        let locator = JsonLocator::new("{\n  \"model\": \"opus\",\n  \"env\": {\"A\": }\n}");
+        assert_eq!(locator.locate("/model"), Some((2, 3)));
+        assert_eq!(locator.locate("/env/A"), Some((3, 11)));
+
+        let mut diagnostics = vec![
+            Diagnostic::new(Severity::Error, "/model", "bad"),
+            Diagnostic::new(Severity::Error, "/missing", "bad"),
+            Diagnostic::new(Severity::Error, "/model", "placed").at(9, 9),
+        ];
+        locator.annotate(&mut diagnostics);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (Some(2), Some(3)));
+        assert_eq!(diagnostics[1].line, None);
+        assert_eq!(diagnostics[2].line, Some(9));
+    }
+
+    #[test]
+    fn closest_match_prefers_case_insensitive_then_nearby_names() {
+        assert_eq!(closest_match("MODEL", &["model", "env"]), Some("model"));
+        assert_eq!(closest_match("permisions", &["permissions", "env"]), Some("permissions"));
+        assert_eq!(closest_match("xyz", &["permissions", "env"]), None);
+    }
+}
@@ -0,0 +1,310 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::{project_info, ProjectInfo};
+
+/// How `list_projects` walks its scan roots. Every field is optional from the
+/// frontend; omitted fields fall back to `ScanOptions::default()`.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub max_depth: usize,               // 1 = immediate children of each root
+    pub extra_roots: Vec<String>,       // Scanned in addition to base_dir
+    pub include: Vec<String>,           // Globs (relative to the root); empty = everything
+    pub exclude: Vec<String>,           // Globs (relative to the root) that are never entered
+    pub respect_gitignore: bool,
+    pub stop_at_project_root: bool,     // Don't look for projects inside projects
+    pub markers: Vec<String>,           // Files/dirs that mark a project root
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            max_depth: 3,
+            extra_roots: Vec::new(),
+            include: Vec::new(),
+            exclude: ["node_modules", "target", "dist", "build", "__pycache__", "vendor"]
+                .iter()
+                .map(|d| format!("**/{}", d))
+                .collect(),
+            respect_gitignore: true,
+            stop_at_project_root: true,
+            markers: [".git", "package.json", "tsconfig.json", "pyproject.toml", "go.mod", "Cargo.toml"]
+                .iter()
+                .map(|m| m.to_string())
+                .collect(),
+        }
+    }
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// A directory is a project if it has a CLAUDE.md or any configured marker.
+pub fn is_project_dir(path: &Path, markers: &[String]) -> bool {
+    path.join("CLAUDE.md").exists()
+        || path.join(".claude").join("CLAUDE.md").exists()
+        || markers.iter().any(|m| path.join(m).exists())
+}
+
+fn relative_id(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn scan_root(
+    root: &Path,
+    options: &ScanOptions,
+    include: &GlobSet,
+    exclude: &Arc<GlobSet>,
+    seen: &mut HashSet<PathBuf>,
+    projects: &mut Vec<ProjectInfo>,
+) {
+    // Memoised project checks for parents, used to prune descent below a
+    // project root. Children are filtered as soon as a directory is read, so
+    // this can't rely on the main loop having seen the parent yet.
+    let project_dirs: Mutex<HashMap<PathBuf, bool>> = Mutex::new(HashMap::new());
+
+    let filter_root = root.to_path_buf();
+    let filter_exclude = exclude.clone();
+    let filter_markers = options.markers.clone();
+    let stop_at_project_root = options.stop_at_project_root;
+
+    let walker = WalkBuilder::new(root)
+        .max_depth(Some(options.max_depth))
+        .standard_filters(options.respect_gitignore)
+        .hidden(true)
+        .follow_links(false)
+        .filter_entry(move |entry| {
+            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                return false;
+            }
+            let path = entry.path();
+            if path == filter_root {
+                return true;
+            }
+            let relative = path.strip_prefix(&filter_root).unwrap_or(path);
+            if filter_exclude.is_match(relative) {
+                return false;
+            }
+            if stop_at_project_root {
+                if let Some(parent) = path.parent().filter(|p| *p != filter_root) {
+                    let mut cache = project_dirs.lock().unwrap();
+                    let parent_is_project = *cache
+                        .entry(parent.to_path_buf())
+                        .or_insert_with(|| is_project_dir(parent, &filter_markers));
+                    if parent_is_project {
+                        return false;
+                    }
+                }
+            }
+            true
+        })
+        .build();
+
+    for entry in walker.flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        let path = entry.path();
+        if !is_project_dir(path, &options.markers) {
+            continue;
+        }
+
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        if !include.is_empty() && !include.is_match(relative) {
+            continue;
+        }
+        if seen.insert(path.to_path_buf()) {
+            projects.push(project_info(path, relative_id(root, path)));
+        }
+    }
+}
+
+/// Find projects under `base_dir` and any extra roots.
+pub fn scan_projects(base_dir: &Path, options: &ScanOptions) -> Result<Vec<ProjectInfo>, String> {
+    let include = build_globset(&options.include)?;
+    let exclude = Arc::new(build_globset(&options.exclude)?);
+
+    let mut roots = vec![base_dir.to_path_buf()];
+    roots.extend(options.extra_roots.iter().map(PathBuf::from));
+
+    let mut seen = HashSet::new();
+    let mut projects = Vec::new();
+    for root in roots.iter().filter(|r| r.is_dir()) {
+        scan_root(root, options, &include, &exclude, &mut seen, &mut projects);
+    }
+
+    projects.sort_by_key(|p| p.path.to_lowercase());
+    Ok(projects)
+}
+
+#[derive(Serialize)]
+pub struct KnownProject {
+    #[serde(flatten)]
+    pub project: ProjectInfo,
+    pub exists: bool,                   // False if the directory has since been removed
+    pub in_claude_json: bool,           // Listed in the `projects` map of ~/.claude.json
+    pub history_dir: Option<String>,    // ~/.claude/projects/<encoded-path>/
+    pub last_used: Option<u64>,         // Unix seconds, from the newest session file
+}
+
+/// Claude Code's folder name for a project: every character that isn't
+/// ASCII alphanumeric becomes '-'.
+pub fn encode_project_path(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Session transcripts record the working directory on each message, which is
+/// the only lossless way back from an encoded folder name.
+fn cwd_from_sessions(history_dir: &Path) -> (Option<String>, Option<u64>) {
+    let mut sessions: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(history_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("jsonl"))
+                .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+                .collect()
+        })
+        .unwrap_or_default();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.0));
+
+    let last_used = sessions
+        .first()
+        .and_then(|(t, _)| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
+    for (_, session) in &sessions {
+        let Ok(file) = std::fs::File::open(session) else { continue };
+        let reader = std::io::BufReader::new(file);
+        for line in std::io::BufRead::lines(reader).take(50).map_while(Result::ok) {
+            if !line.contains("\"cwd\"") {
+                continue;
+            }
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) {
+                if let Some(cwd) = value.get("cwd").and_then(|c| c.as_str()) {
+                    return (Some(cwd.to_string()), last_used);
+                }
+            }
+        }
+    }
+    (None, last_used)
+}
+
+/// Recover a path from its encoded folder name by walking the filesystem and
+/// matching each directory's encoded name against the remaining input.
+fn decode_against_filesystem(dir: &Path, remaining: &str) -> Option<PathBuf> {
+    if remaining.is_empty() {
+        return Some(dir.to_path_buf());
+    }
+    let rest = remaining.strip_prefix('-')?;
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let encoded = encode_project_path(&name);
+        if let Some(after) = rest.strip_prefix(&encoded) {
+            if after.is_empty() || after.starts_with('-') {
+                if let Some(found) = decode_against_filesystem(&entry.path(), after) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn decode_project_dir_name(encoded: &str) -> PathBuf {
+    // Windows paths encode as e.g. "C--Users-me-app"
+    let bytes = encoded.as_bytes();
+    let (root, remaining) = if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b'-' && cfg!(windows) {
+        (PathBuf::from(format!("{}:\\", &encoded[..1])), &encoded[2..])
+    } else {
+        (PathBuf::from("/"), encoded)
+    };
+
+    decode_against_filesystem(&root, remaining).unwrap_or_else(|| {
+        // The directory is gone; the best guess is that every '-' was a separator
+        root.join(remaining.trim_start_matches('-').replace('-', std::path::MAIN_SEPARATOR_STR))
+    })
+}
+
+/// Projects Claude Code has been used in, from the `projects` map in
+/// ~/.claude.json and the per-project history folders in ~/.claude/projects.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_known_projects() -> Vec<KnownProject> {
+    let paths = crate::get_config_paths();
+    let mut known: Vec<KnownProject> = Vec::new();
+
+    let mut upsert = |path: PathBuf, from_claude_json: bool, history: Option<(String, Option<u64>)>| {
+        let key = path.to_string_lossy().into_owned();
+        if let Some(existing) = known.iter_mut().find(|k| k.project.path == key) {
+            existing.in_claude_json |= from_claude_json;
+            if let Some((dir, last_used)) = history {
+                existing.history_dir = Some(dir);
+                existing.last_used = last_used;
+            }
+            return;
+        }
+        let (history_dir, last_used) = match history {
+            Some((dir, last_used)) => (Some(dir), last_used),
+            None => (None, None),
+        };
+        known.push(KnownProject {
+            exists: path.is_dir(),
+            project: project_info(&path, key),
+            in_claude_json: from_claude_json,
+            history_dir,
+            last_used,
+        });
+    };
+
+    if let Ok(text) = std::fs::read_to_string(&paths.user.mcp.path) {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) {
+            if let Some(projects) = value.get("projects").and_then(|p| p.as_object()) {
+                for project_path in projects.keys() {
+                    upsert(PathBuf::from(project_path), true, None);
+                }
+            }
+        }
+    }
+
+    let history_root = crate::home_dir().join(".claude").join("projects");
+    if let Ok(entries) = std::fs::read_dir(&history_root) {
+        for entry in entries.flatten() {
+            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let encoded = entry.file_name().to_string_lossy().into_owned();
+            let (cwd, last_used) = cwd_from_sessions(&entry.path());
+            // A session's cwd may be a subdirectory; only trust it if it
+            // encodes back to this folder's name.
+            let path = cwd
+                .filter(|c| encode_project_path(c) == encoded)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| decode_project_dir_name(&encoded));
+            upsert(path, false, Some((entry.path().to_string_lossy().into_owned(), last_used)));
+        }
+    }
+
+    known.sort_by(|a, b| b.last_used.cmp(&a.last_used).then_with(|| a.project.path.cmp(&b.project.path)));
+    crate::sandbox::register_projects(known.iter().filter(|k| k.exists).map(|k| Path::new(&k.project.path)));
+    known
+}
@@ -0,0 +1,675 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::definitions::{definition_files, parse_definition_text, DefinitionKind};
+use crate::diagnostics::{has_errors, Diagnostic, JsonLocator, Severity};
+use crate::mcp::McpServerConfig;
+use crate::permissions::PermissionDecision;
+use crate::settings::{leaves, load_settings_layers, pointer_segment, SettingsScope};
+
+/// Programs that take a script as their first argument, so a hook like
+/// `python3 hooks/check.py` needs the script to exist but not be executable.
+const INTERPRETERS: &[&str] = &["sh", "bash", "zsh", "fish", "python", "python3", "node", "deno", "bun", "ruby", "perl", "uv", "uvx", "npx"];
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorCategory {
+    Paths,          // Existence, type and permissions of config locations
+    Validation,     // Settings, MCP and frontmatter schema problems
+    Mcp,            // MCP server commands that can't be found
+    Hooks,          // Hook commands that can't be run
+    Settings,       // The same setting with different values in several layers
+    Permissions,    // Contradicting allow/ask/deny rules across layers
+    Secrets,
+    Inventory,      // Shadowed, duplicate or unreachable agents, commands and skills
+    Budget,         // Files over their token budget
+}
+
+#[derive(Serialize)]
+pub struct DoctorFinding {
+    pub category: DoctorCategory,
+    pub severity: Severity,
+    pub path: Option<String>,
+    pub line: Option<usize>,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DoctorReport {
+    pub project: Option<String>,
+    pub findings: Vec<DoctorFinding>,   // Most severe first
+    pub files_checked: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+}
+
+#[derive(Serialize)]
+pub struct FileValidation {
+    pub path: String,
+    pub kind: &'static str,             // "settings", "mcp", "agent", "command" or "skill"
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize)]
+pub struct ValidationReport {
+    pub files: Vec<FileValidation>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+fn finding(category: DoctorCategory, severity: Severity, path: Option<&str>, message: impl Into<String>) -> DoctorFinding {
+    DoctorFinding {
+        category,
+        severity,
+        path: path.map(str::to_string),
+        line: None,
+        message: message.into(),
+        suggestion: None,
+    }
+}
+
+impl DoctorFinding {
+    fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+fn read_error(e: std::io::Error) -> Vec<Diagnostic> {
+    vec![Diagnostic::new(Severity::Error, "", format!("Failed to read file: {}", e))]
+}
+
+/// Diagnostics for the `mcpServers` object of an MCP config file.
+pub fn mcp_diagnostics(text: &str) -> Vec<Diagnostic> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(e) => {
+            return vec![Diagnostic::new(Severity::Error, "", format!("Invalid JSON: {}", e)).at(e.line().max(1), e.column().max(1))];
+        }
+    };
+    let mut diagnostics = Vec::new();
+    match value.get("mcpServers") {
+        None => {}
+        Some(Value::Object(servers)) => {
+            for (name, server) in servers {
+                let pointer = format!("/mcpServers/{}", pointer_segment(name));
+                let result = serde_json::from_value::<McpServerConfig>(server.clone())
+                    .map_err(|e| e.to_string())
+                    .and_then(|config| crate::mcp::validate_server_config(name, &config));
+                if let Err(message) = result {
+                    diagnostics.push(Diagnostic::new(Severity::Error, pointer, message));
+                }
+            }
+        }
+        Some(_) => diagnostics.push(Diagnostic::new(Severity::Error, "/mcpServers", "mcpServers must be an object")),
+    }
+    JsonLocator::new(text).annotate(&mut diagnostics);
+    diagnostics
+}
+
+/// Validate a project's shared and local config files, or the user-level and
+/// managed files when no project is given. Missing files are skipped.
+pub fn validate_config(project: Option<&Path>) -> ValidationReport {
+    let paths = crate::get_config_paths();
+    let (settings, mcp, definition_dirs) = match project {
+        Some(project) => {
+            let files = crate::project_config_files(project);
+            (
+                vec![files.settings.path, files.settings_local.path],
+                vec![files.mcp.path],
+                vec![files.agents.path, files.commands.path, files.skills.path],
+            )
+        }
+        None => (
+            vec![paths.user.settings.path, paths.user.settings_local.path, paths.enterprise.managed_settings.path],
+            vec![paths.user.mcp.path, paths.enterprise.managed_mcp.path],
+            vec![paths.user.agents.path, paths.user.commands.path, paths.user.skills.path],
+        ),
+    };
+
+    let mut files = Vec::new();
+    let mut push = |path: &Path, kind: &'static str, diagnostics: Vec<Diagnostic>| {
+        files.push(FileValidation {
+            path: path.to_string_lossy().into_owned(),
+            kind,
+            valid: !has_errors(&diagnostics),
+            diagnostics,
+        });
+    };
+    for path in settings.iter().map(PathBuf::from).filter(|p| p.is_file()) {
+        let diagnostics = fs::read_to_string(&path).map(|t| crate::schema::validate_settings_text(&t)).unwrap_or_else(read_error);
+        push(&path, "settings", diagnostics);
+    }
+    for path in mcp.iter().map(PathBuf::from).filter(|p| p.is_file()) {
+        let diagnostics = fs::read_to_string(&path).map(|t| mcp_diagnostics(&t)).unwrap_or_else(read_error);
+        push(&path, "mcp", diagnostics);
+    }
+    for dir in definition_dirs.iter().map(PathBuf::from).filter(|d| d.is_dir()) {
+        for (path, kind) in definition_files(&dir) {
+            let diagnostics = fs::read_to_string(&path)
+                .map(|t| parse_definition_text(&path, kind, &t).diagnostics)
+                .unwrap_or_else(read_error);
+            let kind = match kind {
+                DefinitionKind::Agent => "agent",
+                DefinitionKind::Command => "command",
+                DefinitionKind::Skill => "skill",
+            };
+            push(&path, kind, diagnostics);
+        }
+    }
+
+    let count = |severity: Severity| files.iter().flat_map(|f| &f.diagnostics).filter(|d| d.severity == severity).count();
+    ValidationReport {
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        files,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Resolve a command the way a shell would: paths are taken as-is, bare
+/// names are looked up on PATH (with PATHEXT on Windows).
+fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') || program.contains(std::path::MAIN_SEPARATOR) {
+        let path = PathBuf::from(program);
+        return path.exists().then_some(path);
+    }
+    let extensions: Vec<String> = if cfg!(windows) {
+        env::var("PATHEXT").unwrap_or(".EXE;.CMD;.BAT".into()).split(';').map(str::to_lowercase).collect()
+    } else {
+        vec![String::new()]
+    };
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        extensions.iter().map(|ext| dir.join(format!("{}{}", program, ext))).find(|p| is_executable(p))
+    })
+}
+
+/// Shell words of a simple command, honouring quotes. Good enough to find
+/// the program and its first argument.
+fn shell_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, ';' | '|' | '&') => break,
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+fn expand_hook_vars(word: &str, project: Option<&Path>) -> Option<String> {
+    let mut word = word.to_string();
+    if let Some(rest) = word.strip_prefix("~/") {
+        word = crate::home_dir().join(rest).to_string_lossy().into_owned();
+    }
+    if word.contains("CLAUDE_PROJECT_DIR") {
+        let project = project?.to_string_lossy().into_owned();
+        word = word.replace("${CLAUDE_PROJECT_DIR}", &project).replace("$CLAUDE_PROJECT_DIR", &project);
+    }
+    Some(word)
+}
+
+fn check_paths(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) {
+    let paths = crate::get_config_paths();
+    let (e, u) = (&paths.enterprise, &paths.user);
+    let mut locations: Vec<(&crate::ConfigPathInfo, bool, bool)> = vec![
+        // (path, should be a directory, app writes to it)
+        (&e.claude_md, false, false),
+        (&e.managed_settings, false, false),
+        (&e.managed_mcp, false, false),
+        (&u.claude_md, false, true),
+        (&u.claude_local_md, false, true),
+        (&u.settings, false, true),
+        (&u.settings_local, false, true),
+        (&u.mcp, false, true),
+        (&u.agents, true, true),
+        (&u.commands, true, true),
+        (&u.skills, true, true),
+    ];
+    let project_files = project.map(crate::project_config_files);
+    if let Some(p) = &project_files {
+        locations.extend([
+            (&p.claude_md_root, false, true),
+            (&p.claude_md_dotclaude, false, true),
+            (&p.claude_local_md, false, true),
+            (&p.settings, false, true),
+            (&p.settings_local, false, true),
+            (&p.mcp, false, true),
+            (&p.rules, true, true),
+            (&p.commands, true, true),
+            (&p.agents, true, true),
+            (&p.skills, true, true),
+        ]);
+    }
+
+    let claude_dir = crate::home_dir().join(".claude");
+    if !claude_dir.is_dir() {
+        findings.push(
+            finding(DoctorCategory::Paths, Severity::Info, Some(&claude_dir.to_string_lossy()), "No user config directory yet")
+                .suggest("It's created the first time Claude Code runs or you save a user-level file"),
+        );
+    }
+
+    for (info, want_dir, writable) in locations {
+        let path = Path::new(&info.path);
+        if !info.exists {
+            continue;
+        }
+        if info.is_dir != want_dir {
+            let expected = if want_dir { "a directory" } else { "a file" };
+            findings.push(
+                finding(DoctorCategory::Paths, Severity::Error, Some(&info.path), format!("Should be {}", expected))
+                    .suggest("Claude Code ignores it in this form; move it aside and recreate it"),
+            );
+            continue;
+        }
+        let readable = if want_dir { fs::read_dir(path).is_ok() } else { fs::File::open(path).is_ok() };
+        if !readable {
+            findings.push(
+                finding(DoctorCategory::Paths, Severity::Error, Some(&info.path), "Not readable by the current user")
+                    .suggest(format!("chmod u+r \"{}\"", info.path)),
+            );
+            continue;
+        }
+        if writable && fs::metadata(path).map(|m| m.permissions().readonly()).unwrap_or(false) {
+            findings.push(
+                finding(DoctorCategory::Paths, Severity::Warning, Some(&info.path), "Read-only; changes can't be saved")
+                    .suggest(format!("chmod u+w \"{}\"", info.path)),
+            );
+        }
+        if !want_dir && info.path.ends_with(".md") && fs::read_to_string(path).is_err() {
+            findings.push(
+                finding(DoctorCategory::Paths, Severity::Warning, Some(&info.path), "Not valid UTF-8 text")
+                    .suggest("Re-save the file as UTF-8"),
+            );
+        }
+    }
+}
+
+fn check_validation(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) -> usize {
+    let mut reports = vec![validate_config(None)];
+    reports.extend(project.map(|p| validate_config(Some(p))));
+    let mut files_checked = 0;
+    for report in reports {
+        files_checked += report.files.len();
+        for file in report.files {
+            for d in file.diagnostics {
+                findings.push(DoctorFinding {
+                    category: DoctorCategory::Validation,
+                    severity: d.severity,
+                    path: Some(file.path.clone()),
+                    line: d.line,
+                    message: if d.pointer.is_empty() { d.message } else { format!("{}: {}", d.pointer, d.message) },
+                    suggestion: d.suggestion,
+                });
+            }
+        }
+    }
+    files_checked
+}
+
+fn check_mcp(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) {
+    let project = project.map(|p| p.to_string_lossy().into_owned());
+    let listing = crate::mcp::list_mcp_servers(project);
+    for error in &listing.errors {
+        findings.push(
+            finding(DoctorCategory::Mcp, Severity::Error, Some(&error.source_path), error.message.clone())
+                .suggest("Fix or remove the entry; Claude Code can't start a server it can't read"),
+        );
+    }
+    for server in listing.servers.iter().filter(|s| s.transport == "stdio" && s.overridden_by.is_none()) {
+        let Some(command) = server.config.command.as_deref().filter(|c| !c.trim().is_empty()) else { continue };
+        // `${VAR}` commands depend on the environment Claude Code runs in
+        if command.contains("${") {
+            continue;
+        }
+        match find_program(command) {
+            None => findings.push(
+                finding(
+                    DoctorCategory::Mcp,
+                    Severity::Error,
+                    Some(&server.source_path),
+                    format!("MCP server \"{}\": command \"{}\" was not found", server.name, command),
+                )
+                .suggest("Install it, or use an absolute path; GUI-launched apps may see a shorter PATH than your shell"),
+            ),
+            Some(path) if !is_executable(&path) => findings.push(
+                finding(
+                    DoctorCategory::Mcp,
+                    Severity::Error,
+                    Some(&server.source_path),
+                    format!("MCP server \"{}\": {} is not executable", server.name, path.display()),
+                )
+                .suggest(format!("chmod +x \"{}\"", path.display())),
+            ),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Problems with the program (and, for interpreters, the script) a hook
+/// command runs, as (severity, message, suggestion).
+fn hook_command_problems(command: &str, project: Option<&Path>) -> Vec<(Severity, String, String)> {
+    let mut problems = Vec::new();
+    let words: Vec<String> = shell_words(command).into_iter().skip_while(|w| w.contains('=') && !w.starts_with('=')).collect();
+    let Some(program) = words.first().and_then(|w| expand_hook_vars(w, project)) else { return problems };
+
+    let base = Path::new(&program).file_name().unwrap_or_default().to_string_lossy().into_owned();
+    if INTERPRETERS.contains(&base.as_str()) {
+        if find_program(&program).is_none() {
+            problems.push((Severity::Error, format!("\"{}\" was not found", program), "Install it or use an absolute path".into()));
+        }
+        let script = words.get(1).filter(|w| !w.starts_with('-') && (w.contains('/') || w.contains('.'))).and_then(|w| expand_hook_vars(w, project));
+        if let Some(script) = script.filter(|s| Path::new(s).is_absolute() && !Path::new(s).exists()) {
+            problems.push((Severity::Error, format!("script {} does not exist", script), "Fix the path in the hook command".into()));
+        }
+        return problems;
+    }
+
+    let is_path = program.contains('/') || program.contains(std::path::MAIN_SEPARATOR);
+    match find_program(&program) {
+        None if is_path => problems.push((Severity::Error, format!("{} does not exist", program), "Fix the path in the hook command".into())),
+        None => problems.push((
+            Severity::Warning,
+            format!("\"{}\" was not found on PATH", program),
+            "Install it, or use an absolute path".into(),
+        )),
+        Some(path) if !is_executable(&path) => problems.push((
+            Severity::Error,
+            format!("{} is not executable", path.display()),
+            format!("chmod +x \"{}\"", path.display()),
+        )),
+        Some(_) => {}
+    }
+    problems
+}
+
+fn check_hooks(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) {
+    let list = crate::hooks::list_hooks(project.map(|p| p.to_string_lossy().into_owned()));
+    for hook in &list.hooks {
+        let Some(command) = hook.hook.command.as_deref() else { continue };
+        for (severity, message, suggestion) in hook_command_problems(command, project) {
+            findings.push(
+                finding(DoctorCategory::Hooks, severity, Some(&hook.path), format!("{} hook: {}", hook.event, message)).suggest(suggestion),
+            );
+        }
+    }
+}
+
+fn scope_label(scope: SettingsScope) -> &'static str {
+    match scope {
+        SettingsScope::User => "user settings",
+        SettingsScope::UserLocal => "user local settings",
+        SettingsScope::Project => "project settings",
+        SettingsScope::ProjectLocal => "project local settings",
+        SettingsScope::Managed => "managed settings",
+    }
+}
+
+fn check_settings_conflicts(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) {
+    let mut seen: BTreeMap<String, Vec<(SettingsScope, String, Value)>> = BTreeMap::new();
+    for layer in load_settings_layers(project) {
+        let Some(value) = &layer.value else { continue };
+        let mut values = BTreeMap::new();
+        leaves(value, "", &mut values);
+        for (pointer, value) in values {
+            seen.entry(pointer).or_default().push((layer.layer.scope, layer.layer.path.clone(), value));
+        }
+    }
+    for (pointer, values) in seen {
+        // Layers are loaded lowest precedence first, so the last one wins
+        let Some((winner_scope, winner_path, winner)) = values.last() else { continue };
+        for (scope, _, value) in values.iter().filter(|(_, _, v)| v != winner) {
+            findings.push(
+                finding(
+                    DoctorCategory::Settings,
+                    Severity::Warning,
+                    Some(winner_path),
+                    format!(
+                        "{} is {} in {} but {} in {}; {} wins",
+                        pointer,
+                        value,
+                        scope_label(*scope),
+                        winner,
+                        scope_label(*winner_scope),
+                        scope_label(*winner_scope)
+                    ),
+                )
+                .suggest(format!("Remove it from {} if the override is unintended", scope_label(*winner_scope))),
+            );
+        }
+    }
+}
+
+fn check_permissions(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) {
+    let rules = crate::permissions::collect_rules(project);
+    let list_label = |list: PermissionDecision| match list {
+        PermissionDecision::Allow => "allowed",
+        PermissionDecision::Ask => "set to ask",
+        PermissionDecision::Deny => "denied",
+    };
+    for (i, a) in rules.iter().enumerate() {
+        for b in rules.iter().skip(i + 1).filter(|b| b.rule == a.rule && b.list != a.list) {
+            // deny beats ask beats allow, whatever the layer
+            let strongest = [a.list, b.list].into_iter().max_by_key(|l| match l {
+                PermissionDecision::Allow => 0,
+                PermissionDecision::Ask => 1,
+                PermissionDecision::Deny => 2,
+            });
+            findings.push(
+                finding(
+                    DoctorCategory::Permissions,
+                    Severity::Warning,
+                    Some(&b.path),
+                    format!(
+                        "{} is {} in {} but {} in {}; it ends up {}",
+                        a.rule,
+                        list_label(a.list),
+                        scope_label(a.scope),
+                        list_label(b.list),
+                        scope_label(b.scope),
+                        list_label(strongest.unwrap_or(b.list))
+                    ),
+                )
+                .suggest("Keep the rule in one list only"),
+            );
+        }
+    }
+}
+
+fn check_extras(project: Option<&Path>, findings: &mut Vec<DoctorFinding>) {
+    for secret in crate::secrets::scan(project).findings {
+        let (severity, message) = if secret.tracked {
+            (Severity::Error, format!("Possible secret {} in a git-tracked file", secret.found.preview))
+        } else if secret.committable {
+            (Severity::Error, format!("Possible secret {} in a file git would commit", secret.found.preview))
+        } else {
+            (Severity::Info, format!("Possible secret {}", secret.found.preview))
+        };
+        let mut f = finding(DoctorCategory::Secrets, severity, Some(&secret.path), message)
+            .suggest("Move it to an environment variable and reference it as ${NAME}");
+        f.line = Some(secret.found.line);
+        findings.push(f);
+    }
+
+    for item in crate::inventory::build_inventory(project).items {
+        if item.status == crate::inventory::ItemStatus::Active {
+            continue;
+        }
+        let mut f = finding(DoctorCategory::Inventory, Severity::Warning, Some(&item.path), item.reason.unwrap_or_default());
+        if let Some(related) = item.related_path {
+            f = f.suggest(format!("Rename or remove one of the definitions; the other is {}", related));
+        }
+        findings.push(f);
+    }
+
+    let budget = crate::budget::budget_report(project, &Default::default());
+    for file in budget.files.iter().filter(|f| f.over_threshold) {
+        findings.push(
+            finding(
+                DoctorCategory::Budget,
+                Severity::Warning,
+                Some(&file.path),
+                format!("About {} tokens, over the {} token budget", file.estimated_tokens, file.threshold),
+            )
+            .suggest("Split it up or move rarely needed detail into files loaded on demand"),
+        );
+    }
+    if budget.over_total {
+        findings.push(finding(
+            DoctorCategory::Budget,
+            Severity::Warning,
+            None,
+            format!("About {} tokens load at startup, over the {} token budget", budget.startup_tokens, budget.total_threshold),
+        ));
+    }
+}
+
+pub fn doctor(project: Option<&Path>) -> DoctorReport {
+    let mut findings = Vec::new();
+    check_paths(project, &mut findings);
+    let files_checked = check_validation(project, &mut findings);
+    check_mcp(project, &mut findings);
+    check_hooks(project, &mut findings);
+    check_settings_conflicts(project, &mut findings);
+    check_permissions(project, &mut findings);
+    check_extras(project, &mut findings);
+
+    findings.sort_by_key(|f| (std::cmp::Reverse(f.severity), f.category));
+    let count = |severity: Severity| findings.iter().filter(|f| f.severity == severity).count();
+    DoctorReport {
+        project: project.map(|p| p.to_string_lossy().into_owned()),
+        files_checked,
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        infos: count(Severity::Info),
+        findings,
+    }
+}
+
+/// Check every config location, file and reference for the user (and a
+/// project, if given) and report problems with suggested fixes.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn run_doctor(project: Option<String>) -> Result<DoctorReport, String> {
+    tauri::async_runtime::spawn_blocking(move || doctor(project.as_deref().map(Path::new)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn shell_words_honour_quotes_and_stop_at_operators() {
+        assert_eq!(shell_words("python3 'my script.py' --flag"), ["python3", "my script.py", "--flag"]);
+        assert_eq!(shell_words(r#"node "$CLAUDE_PROJECT_DIR/hook.js""#), ["node", "$CLAUDE_PROJECT_DIR/hook.js"]);
+        assert_eq!(shell_words("echo ''"), ["echo", ""]);
+        assert_eq!(shell_words("lint; rm -rf /"), ["lint"]);
+        assert_eq!(shell_words("jq .tool_name | grep Bash"), ["jq", ".tool_name"]);
+        assert_eq!(shell_words("check && notify"), ["check"]);
+        assert_eq!(shell_words("echo 'a;b|c'"), ["echo", "a;b|c"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_program_takes_paths_as_is_and_searches_path() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp = TempDir::new().unwrap();
+        let script = temp.path().join("hook.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+
+        // A path is returned even when it isn't executable, so the caller can say so
+        assert_eq!(find_program(&script.to_string_lossy()), Some(script.clone()));
+        assert!(!is_executable(&script));
+        assert_eq!(find_program(&temp.path().join("missing.sh").to_string_lossy()), None);
+        assert!(find_program("sh").is_some_and(|p| is_executable(&p)));
+        assert_eq!(find_program("no-such-program-for-doctor-tests"), None);
+    }
+
+    #[test]
+    fn expand_hook_vars_needs_a_project_for_the_project_dir() {
+        let project = Path::new("/work/app");
+        assert_eq!(expand_hook_vars("$CLAUDE_PROJECT_DIR/a.sh", Some(project)).as_deref(), Some("/work/app/a.sh"));
+        assert_eq!(expand_hook_vars("${CLAUDE_PROJECT_DIR}/b.sh", Some(project)).as_deref(), Some("/work/app/b.sh"));
+        assert_eq!(expand_hook_vars("$CLAUDE_PROJECT_DIR/a.sh", None), None);
+        assert_eq!(expand_hook_vars("/usr/bin/env", None).as_deref(), Some("/usr/bin/env"));
+        let home = expand_hook_vars("~/bin/hook", None).unwrap();
+        assert_eq!(PathBuf::from(home), crate::home_dir().join("bin/hook"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hook_checks_cover_interpreters_and_scripts() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp = TempDir::new().unwrap();
+        let project = temp.path();
+        fs::write(project.join("present.py"), "").unwrap();
+        fs::write(project.join("plain.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(project.join("plain.sh"), fs::Permissions::from_mode(0o644)).unwrap();
+        let messages = |command: &str| -> Vec<(Severity, String)> {
+            hook_command_problems(command, Some(project)).into_iter().map(|(s, m, _)| (s, m)).collect()
+        };
+
+        assert!(messages("sh $CLAUDE_PROJECT_DIR/present.py").is_empty());
+        let missing = messages("sh \"$CLAUDE_PROJECT_DIR/gone.py\" --check");
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].0, Severity::Error);
+        assert!(missing[0].1.starts_with("script ") && missing[0].1.ends_with("gone.py does not exist"));
+        // Flags and relative scripts aren't checked
+        assert!(messages("sh -c 'exit 0'").is_empty());
+        assert!(messages("sh relative/script.sh").is_empty());
+        // Environment assignments before the program are skipped
+        assert!(messages("FOO=1 sh $CLAUDE_PROJECT_DIR/present.py").is_empty());
+
+        let not_executable = messages("$CLAUDE_PROJECT_DIR/plain.sh");
+        assert_eq!(not_executable.len(), 1);
+        assert!(not_executable[0].1.ends_with("plain.sh is not executable"));
+        let absent = messages("$CLAUDE_PROJECT_DIR/absent.sh");
+        assert_eq!(absent[0].0, Severity::Error);
+        assert_eq!(messages("no-such-program-for-doctor-tests")[0].0, Severity::Warning);
+        // Without a project, $CLAUDE_PROJECT_DIR can't be resolved so nothing is reported
+        assert!(hook_command_problems("$CLAUDE_PROJECT_DIR/absent.sh", None).is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use crate::diagnostics::{has_errors, Diagnostic, Severity};
+use crate::storage::{app_data_dir, atomic_write, content_version, create_backup, file_version, unix_millis};
+
+/// The enterprise files IT can author. Callers name the file rather than a
+/// path, so the elevated helper can only ever write these three locations.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EnterpriseFile {
+    ClaudeMd,
+    ManagedSettings,
+    ManagedMcp,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Elevation {
+    None,           // The directory is writable by this user (or the app runs as admin)
+    Pkexec,
+    Sudo,           // Non-interactive; needs cached credentials or NOPASSWD
+    Osascript,      // macOS administrator prompt
+    Unavailable,    // No way to elevate from here (Windows without admin rights)
+}
+
+#[derive(Serialize)]
+pub struct EnterprisePreview {
+    pub file: EnterpriseFile,
+    pub path: String,
+    pub exists: bool,
+    pub current_version: Option<String>,
+    pub changed: bool,
+    pub diff: String,                   // Unified diff from the installed file
+    pub diagnostics: Vec<Diagnostic>,
+    pub valid: bool,                    // No error diagnostics; required to install
+    pub elevation: Elevation,           // How installing would get write access
+}
+
+#[derive(Serialize)]
+pub struct EnterpriseInstall {
+    pub path: String,
+    pub version: String,
+    pub backup_id: Option<String>,      // Backup of the replaced file, in the app's backup store
+    pub elevation: Elevation,
+}
+
+fn target_path(file: EnterpriseFile) -> PathBuf {
+    let enterprise = crate::get_config_paths().enterprise;
+    PathBuf::from(match file {
+        EnterpriseFile::ClaudeMd => enterprise.claude_md.path,
+        EnterpriseFile::ManagedSettings => enterprise.managed_settings.path,
+        EnterpriseFile::ManagedMcp => enterprise.managed_mcp.path,
+    })
+}
+
+fn validate(file: EnterpriseFile, content: &str) -> Vec<Diagnostic> {
+    match file {
+        EnterpriseFile::ManagedSettings => crate::schema::validate_settings_text(content),
+        EnterpriseFile::ManagedMcp => crate::doctor::mcp_diagnostics(content),
+        EnterpriseFile::ClaudeMd if content.trim().is_empty() => {
+            vec![Diagnostic::new(Severity::Warning, "", "Empty file; every user on this machine loads it")]
+        }
+        EnterpriseFile::ClaudeMd => Vec::new(),
+    }
+}
+
+/// Whether this process can create files in `dir`, tested by doing it.
+fn dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".write-probe-{}", std::process::id()));
+    match fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+fn has_display() -> bool {
+    std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+fn elevation_for(target: &Path) -> Elevation {
+    if target.parent().is_some_and(dir_writable) {
+        Elevation::None
+    } else if cfg!(target_os = "macos") {
+        Elevation::Osascript
+    } else if cfg!(windows) {
+        Elevation::Unavailable
+    } else if has_display() {
+        Elevation::Pkexec
+    } else {
+        Elevation::Sudo
+    }
+}
+
+/// Quote for a POSIX shell.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn run_helper(command: &mut Command) -> std::io::Result<Output> {
+    command.stdin(std::process::Stdio::null()).output()
+}
+
+/// Copy `staged` to `target` as root, creating the enterprise directory if
+/// needed. The file ends up root-owned and world-readable, as Claude Code
+/// expects.
+fn install_elevated(staged: &Path, target: &Path, elevation: Elevation) -> Result<Elevation, String> {
+    let install_args = |cmd: &mut Command| {
+        cmd.args(["install", "-D", "-m", "0644"]).arg(staged).arg(target);
+    };
+    let output = match elevation {
+        Elevation::Pkexec => {
+            let mut cmd = Command::new("pkexec");
+            install_args(&mut cmd);
+            match run_helper(&mut cmd) {
+                // No polkit agent installed; fall back to sudo
+                Err(e) if e.kind() == ErrorKind::NotFound => return install_elevated(staged, target, Elevation::Sudo),
+                other => other,
+            }
+        }
+        Elevation::Sudo => {
+            let mut cmd = Command::new("sudo");
+            cmd.arg("-n");
+            install_args(&mut cmd);
+            run_helper(&mut cmd)
+        }
+        Elevation::Osascript => {
+            let dir = target.parent().unwrap_or(Path::new("/"));
+            let script = format!(
+                "mkdir -p {} && install -m 0644 {} {}",
+                shell_quote(&dir.to_string_lossy()),
+                shell_quote(&staged.to_string_lossy()),
+                shell_quote(&target.to_string_lossy())
+            );
+            let apple = format!(
+                "do shell script \"{}\" with administrator privileges",
+                script.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+            run_helper(Command::new("osascript").arg("-e").arg(apple))
+        }
+        Elevation::None => return atomic_write(target, &fs::read(staged).map_err(|e| e.to_string())?).map(|_| Elevation::None),
+        Elevation::Unavailable => {
+            return Err(format!("Writing {} needs administrator rights; restart the app as administrator", target.display()));
+        }
+    }
+    .map_err(|e| format!("Failed to start the elevation helper: {}", e))?;
+
+    if output.status.success() {
+        return Ok(elevation);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(match (elevation, output.status.code()) {
+        // pkexec: 126 when the prompt is dismissed, 127 when not authorized
+        (Elevation::Pkexec, Some(126 | 127)) => "Authorization was cancelled or denied".to_string(),
+        (Elevation::Sudo, _) if stderr.contains("password") => {
+            "sudo needs a password and there is no terminal to ask for it; run `sudo -v` first or install a polkit agent".to_string()
+        }
+        (Elevation::Osascript, _) if stderr.contains("-128") => "Authorization was cancelled".to_string(),
+        _ => format!("Installing {} failed: {}", target.display(), stderr),
+    })
+}
+
+/// Validate `content` for an enterprise file and diff it against what's
+/// installed, without writing anything.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn preview_enterprise_file(file: EnterpriseFile, content: String) -> EnterprisePreview {
+    let target = target_path(file);
+    let current = fs::read_to_string(&target).ok();
+    let old = current.as_deref().unwrap_or("");
+    let label = target.to_string_lossy();
+    let diagnostics = validate(file, &content);
+    EnterprisePreview {
+        file,
+        path: label.to_string(),
+        exists: current.is_some(),
+        current_version: file_version(&target),
+        changed: current.as_deref() != Some(content.as_str()),
+        diff: TextDiff::from_lines(old, content.as_str())
+            .unified_diff()
+            .header(if current.is_some() { &label } else { "/dev/null" }, &label)
+            .to_string(),
+        valid: !has_errors(&diagnostics),
+        diagnostics,
+        elevation: elevation_for(&target),
+    }
+}
+
+/// Install an enterprise file. The content is validated, written to a staged
+/// file in the app data directory, and copied into place through pkexec or
+/// sudo (osascript on macOS) when the enterprise directory isn't writable.
+/// When `expected_version` is given (from the preview) the install is
+/// refused if the installed file has changed since.
+fn install(file: EnterpriseFile, content: String, expected_version: Option<String>) -> Result<EnterpriseInstall, String> {
+    let target = target_path(file);
+    let diagnostics = validate(file, &content);
+    if has_errors(&diagnostics) {
+        let first = diagnostics.iter().find(|d| d.severity == Severity::Error).map(|d| d.message.as_str()).unwrap_or_default();
+        return Err(format!("{} has errors and was not installed: {}", target.display(), first));
+    }
+    if let Some(expected) = expected_version {
+        if file_version(&target).as_deref() != Some(expected.as_str()) {
+            return Err(format!("{} was modified by another program since it was previewed", target.display()));
+        }
+    }
+
+    // Backups live in the user's app data, so this doesn't need elevation
+    let backup_id = create_backup(&target)?;
+
+    let staging = app_data_dir().join("enterprise-staging");
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let staged = staging.join(format!("{}-{}", unix_millis(), name));
+    atomic_write(&staged, content.as_bytes())?;
+    let result = install_elevated(&staged, &target, elevation_for(&target));
+    let _ = fs::remove_file(&staged);
+    let elevation = result?;
+
+    if fs::read(&target).ok().as_deref() != Some(content.as_bytes()) {
+        return Err(format!("{} does not contain the new content after installing", target.display()));
+    }
+    Ok(EnterpriseInstall {
+        path: target.to_string_lossy().into_owned(),
+        version: content_version(content.as_bytes()),
+        backup_id,
+        elevation,
+    })
+}
+
+/// Install an enterprise file. pkexec and osascript wait for the user to
+/// answer a password prompt, so the install runs on the blocking pool.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn write_enterprise_file(
+    file: EnterpriseFile,
+    content: String,
+    expected_version: Option<String>,
+) -> Result<EnterpriseInstall, String> {
+    tauri::async_runtime::spawn_blocking(move || install(file, content, expected_version))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,691 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::diagnostics::{closest_match, Diagnostic, JsonLocator, Severity};
+use crate::permissions::KNOWN_TOOLS;
+use crate::schema::{HookCommand, HookMatcher, HOOK_EVENTS};
+use crate::settings::{load_settings_layers, pointer_segment, LoadedLayer, SettingsLayer, SettingsScope, SettingsSource};
+
+/// Claude Code's default when a hook doesn't set `timeout`.
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+
+/// Cap on captured stdout/stderr so a runaway hook can't exhaust memory.
+const MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// Events whose matcher is a tool name pattern.
+const TOOL_EVENTS: &[&str] = &["PreToolUse", "PostToolUse"];
+
+/// Events that ignore `matcher` entirely.
+const MATCHERLESS_EVENTS: &[&str] = &["UserPromptSubmit", "Stop", "SubagentStop", "SessionEnd"];
+
+/// Events whose matcher picks from a fixed set of values.
+const EVENT_MATCHER_VALUES: &[(&str, &[&str])] = &[
+    ("SessionStart", &["startup", "resume", "clear", "compact"]),
+    ("PreCompact", &["manual", "auto"]),
+];
+
+/// Whether a hook's matcher selects `target` (a tool name, or the source or
+/// trigger for session events). An empty or missing matcher and `*` match
+/// everything; anything else is a regex that must match the whole target.
+pub fn matcher_matches(matcher: Option<&str>, target: &str) -> Result<bool, String> {
+    let pattern = matcher.map(str::trim).unwrap_or_default();
+    if pattern.is_empty() || pattern == "*" {
+        return Ok(true);
+    }
+    let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())?;
+    Ok(regex.is_match(target))
+}
+
+fn matcher_diagnostics(event: &str, matcher: &str, pointer: &str, out: &mut Vec<Diagnostic>) {
+    let pattern = matcher.trim();
+    if pattern.is_empty() || pattern == "*" {
+        return;
+    }
+    if MATCHERLESS_EVENTS.contains(&event) {
+        out.push(
+            Diagnostic::new(Severity::Warning, pointer, format!("{} hooks ignore the matcher", event))
+                .with_suggestion("Remove \"matcher\"; the hook runs for every event"),
+        );
+        return;
+    }
+    if let Err(e) = Regex::new(pattern) {
+        out.push(Diagnostic::new(
+            Severity::Error,
+            pointer,
+            format!("Invalid matcher regex: {}", e.to_string().lines().last().unwrap_or_default().trim_start_matches("error: ")),
+        ));
+        return;
+    }
+
+    // Plain alternatives ("Edit|Write") are the common case; check the names
+    // so a typo doesn't leave the hook silently never firing.
+    let known: &[&str] = if TOOL_EVENTS.contains(&event) {
+        KNOWN_TOOLS
+    } else if let Some((_, values)) = EVENT_MATCHER_VALUES.iter().find(|(e, _)| *e == event) {
+        values
+    } else {
+        return;
+    };
+    for alternative in pattern.split('|').map(str::trim) {
+        let is_plain = !alternative.is_empty() && alternative.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_plain || alternative.starts_with("mcp__") || known.contains(&alternative) {
+            continue;
+        }
+        let diagnostic = Diagnostic::new(
+            Severity::Warning,
+            pointer,
+            format!("\"{}\" never matches a {} event", alternative, event),
+        );
+        out.push(match closest_match(alternative, known) {
+            Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+            None => diagnostic,
+        });
+    }
+}
+
+/// Semantic checks for the `hooks` section of a settings file. Structural
+/// problems are already reported by the settings schema.
+pub fn hook_diagnostics(settings: &Value) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(events) = settings.get("hooks").and_then(|h| h.as_object()) else { return diagnostics };
+
+    for (event, groups) in events {
+        if !HOOK_EVENTS.contains(&event.as_str()) {
+            continue;
+        }
+        let event_pointer = format!("/hooks/{}", pointer_segment(event));
+        for (i, group) in groups.as_array().into_iter().flatten().enumerate() {
+            let group_pointer = format!("{}/{}", event_pointer, i);
+            let Ok(group) = serde_json::from_value::<HookMatcher>(group.clone()) else { continue };
+
+            if let Some(matcher) = &group.matcher {
+                matcher_diagnostics(event, matcher, &format!("{}/matcher", group_pointer), &mut diagnostics);
+            }
+            if group.hooks.is_empty() {
+                diagnostics.push(Diagnostic::new(Severity::Warning, &group_pointer, "Matcher has no hooks"));
+            }
+            for (j, hook) in group.hooks.iter().enumerate() {
+                let hook_pointer = format!("{}/hooks/{}", group_pointer, j);
+                let missing = match hook.kind.as_str() {
+                    "command" if hook.command.as_deref().map(str::trim).unwrap_or_default().is_empty() => Some("command"),
+                    "prompt" if hook.prompt.as_deref().map(str::trim).unwrap_or_default().is_empty() => Some("prompt"),
+                    _ => None,
+                };
+                if let Some(field) = missing {
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Error,
+                        &hook_pointer,
+                        format!("A {} hook needs a \"{}\"", hook.kind, field),
+                    ));
+                }
+                if hook.timeout == Some(0) {
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        format!("{}/timeout", hook_pointer),
+                        "A timeout of 0 seconds kills the hook immediately",
+                    ));
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+#[derive(Serialize)]
+pub struct HookEntry {
+    pub event: String,
+    pub matcher: Option<String>,
+    #[serde(flatten)]
+    pub hook: HookCommand,
+    pub scope: SettingsScope,
+    pub path: String,
+    pub pointer: String,                // JSON pointer of the hook within its file
+    pub diagnostics: Vec<Diagnostic>,   // Problems with this hook or its matcher
+}
+
+/// A matcher group that couldn't be read, so none of its hooks are listed.
+#[derive(Serialize)]
+pub struct HookGroupError {
+    pub scope: SettingsScope,
+    pub path: String,
+    #[serde(flatten)]
+    pub diagnostic: Diagnostic,
+}
+
+#[derive(Serialize)]
+pub struct HookList {
+    pub hooks: Vec<HookEntry>,
+    pub errors: Vec<HookGroupError>,            // Matcher groups that couldn't be read
+    pub disabled_by: Option<SettingsSource>,    // Layer whose `disableAllHooks: true` wins
+    pub layers: Vec<SettingsLayer>,
+}
+
+/// Every hook from every settings layer, lowest precedence first. Claude Code
+/// runs all matching hooks regardless of which layer defines them.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_hooks(project: Option<String>) -> HookList {
+    hooks_in_layers(load_settings_layers(project.as_deref().map(Path::new)))
+}
+
+fn hooks_in_layers(loaded_layers: Vec<LoadedLayer>) -> HookList {
+    let mut hooks = Vec::new();
+    let mut errors = Vec::new();
+    let mut disabled_by = None;
+    let mut layers = Vec::new();
+
+    for loaded in loaded_layers {
+        let layer = loaded.layer;
+        let Some(value) = loaded.value else {
+            layers.push(layer);
+            continue;
+        };
+
+        if let Some(disabled) = value.get("disableAllHooks").and_then(|v| v.as_bool()) {
+            disabled_by = disabled.then(|| SettingsSource { scope: layer.scope, path: layer.path.clone() });
+        }
+
+        let locator = fs::read_to_string(&layer.path).ok().map(|text| JsonLocator::new(&text));
+        let mut diagnostics = hook_diagnostics(&value);
+        if let Some(locator) = &locator {
+            locator.annotate(&mut diagnostics);
+        }
+
+        let events = value.get("hooks").and_then(|h| h.as_object());
+        for (event, groups) in events.into_iter().flatten() {
+            for (i, group) in groups.as_array().into_iter().flatten().enumerate() {
+                let group_pointer = format!("/hooks/{}/{}", pointer_segment(event), i);
+                let group = match serde_json::from_value::<HookMatcher>(group.clone()) {
+                    Ok(group) => group,
+                    Err(e) => {
+                        let mut diagnostic = [Diagnostic::new(Severity::Error, &group_pointer, format!("Invalid matcher group: {}", e))];
+                        if let Some(locator) = &locator {
+                            locator.annotate(&mut diagnostic);
+                        }
+                        let [diagnostic] = diagnostic;
+                        errors.push(HookGroupError { scope: layer.scope, path: layer.path.clone(), diagnostic });
+                        continue;
+                    }
+                };
+                let matcher_pointer = format!("{}/matcher", group_pointer);
+
+                for (j, hook) in group.hooks.into_iter().enumerate() {
+                    let pointer = format!("{}/hooks/{}", group_pointer, j);
+                    let own = diagnostics
+                        .iter()
+                        .filter(|d| {
+                            d.pointer == matcher_pointer
+                                || d.pointer == pointer
+                                || d.pointer.starts_with(&format!("{}/", pointer))
+                        })
+                        .cloned()
+                        .collect();
+                    hooks.push(HookEntry {
+                        event: event.clone(),
+                        matcher: group.matcher.clone(),
+                        hook,
+                        scope: layer.scope,
+                        path: layer.path.clone(),
+                        pointer,
+                        diagnostics: own,
+                    });
+                }
+            }
+        }
+        layers.push(layer);
+    }
+
+    HookList { hooks, errors, disabled_by, layers }
+}
+
+// ---------------------------------------------------------------------------
+// Dry runs
+// ---------------------------------------------------------------------------
+
+/// A hook command to run against a synthetic event. Only `command` and
+/// `event` are required; the event payload gets plausible defaults.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct HookDryRun {
+    pub command: String,
+    pub event: String,
+    pub matcher: Option<String>,        // Reported against tool_name, not enforced
+    pub project_path: Option<String>,   // Working directory and $CLAUDE_PROJECT_DIR
+    pub tool_name: Option<String>,
+    pub tool_input: Option<Value>,
+    pub tool_response: Option<Value>,
+    pub prompt: Option<String>,
+    pub overrides: Option<Map<String, Value>>,  // Merged over the generated event JSON
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HookOutcome {
+    Proceed,    // No decision; Claude Code carries on normally
+    Approve,    // Tool call allowed without asking
+    Ask,        // User is asked to confirm the tool call
+    Block,      // Tool call, prompt or stop is blocked; `reason` goes to Claude
+    Stop,       // `continue: false`; Claude stops entirely
+    Error,      // Non-blocking error; stderr is shown to the user
+    TimedOut,
+}
+
+#[derive(Serialize)]
+pub struct HookDecision {
+    pub outcome: HookOutcome,
+    pub reason: Option<String>,
+    pub additional_context: Option<String>,    // Added to Claude's context
+    pub system_message: Option<String>,        // Shown to the user
+    pub suppress_output: bool,
+    pub json_output: Option<Value>,            // Parsed stdout, when it was JSON
+}
+
+#[derive(Serialize)]
+pub struct HookDryRunResult {
+    pub input: Value,                   // Event JSON written to stdin
+    pub exit_code: Option<i32>,         // None if killed (timeout or signal)
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub matcher_matches: Option<bool>,  // Whether `matcher` selects `tool_name`
+    pub decision: HookDecision,
+}
+
+/// The JSON Claude Code sends on stdin for `event`, filled with defaults
+/// where the request leaves something out.
+fn synthetic_event(request: &HookDryRun, cwd: &Path) -> Value {
+    let mut event = json!({
+        "session_id": format!("dry-run-{}", crate::storage::unix_millis()),
+        "transcript_path": "",
+        "cwd": cwd.to_string_lossy(),
+        "permission_mode": "default",
+        "hook_event_name": request.event,
+    });
+    let fields = event.as_object_mut().unwrap();
+
+    let tool_name = request.tool_name.clone().unwrap_or_else(|| "Bash".into());
+    let tool_input = request.tool_input.clone().unwrap_or_else(|| match tool_name.as_str() {
+        "Bash" => json!({ "command": "echo hello", "description": "Print hello" }),
+        "Read" | "Edit" | "Write" | "MultiEdit" => json!({ "file_path": cwd.join("example.txt").to_string_lossy() }),
+        _ => json!({}),
+    });
+
+    match request.event.as_str() {
+        "PreToolUse" => {
+            fields.insert("tool_name".into(), json!(tool_name));
+            fields.insert("tool_input".into(), tool_input);
+        }
+        "PostToolUse" => {
+            fields.insert("tool_name".into(), json!(tool_name));
+            fields.insert("tool_input".into(), tool_input);
+            let response = request.tool_response.clone().unwrap_or_else(|| json!({ "success": true }));
+            fields.insert("tool_response".into(), response);
+        }
+        "UserPromptSubmit" => {
+            fields.insert("prompt".into(), json!(request.prompt.clone().unwrap_or_else(|| "Hello".into())));
+        }
+        "Notification" => {
+            fields.insert("message".into(), json!("Claude needs your permission to use Bash"));
+        }
+        "Stop" | "SubagentStop" => {
+            fields.insert("stop_hook_active".into(), json!(false));
+        }
+        "PreCompact" => {
+            fields.insert("trigger".into(), json!("manual"));
+            fields.insert("custom_instructions".into(), json!(""));
+        }
+        "SessionStart" => {
+            fields.insert("source".into(), json!("startup"));
+        }
+        "SessionEnd" => {
+            fields.insert("reason".into(), json!("other"));
+        }
+        _ => {}
+    }
+
+    for (key, value) in request.overrides.iter().flatten() {
+        fields.insert(key.clone(), value.clone());
+    }
+    event
+}
+
+struct HookProcessOutput {
+    exit_code: Option<i32>,
+    timed_out: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn capture<R: Read + Send + 'static>(mut pipe: R, done: std::sync::mpsc::Sender<()>) -> Arc<Mutex<Vec<u8>>> {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let sink = buffer.clone();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let mut captured = sink.lock().unwrap();
+            if captured.len() < MAX_OUTPUT_BYTES {
+                captured.extend_from_slice(&buf[..n]);
+            }
+        }
+        let _ = done.send(());
+    });
+    buffer
+}
+
+/// Run `command` through the shell the way Claude Code does, feeding `input`
+/// on stdin and killing it after `timeout`.
+fn run_hook_command(command: &str, cwd: &Path, project_dir: Option<&Path>, input: &str, timeout: Duration) -> Result<HookProcessOutput, String> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut process = Command::new(shell);
+    process
+        .arg(flag)
+        .arg(command)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = project_dir {
+        process.env("CLAUDE_PROJECT_DIR", dir);
+    }
+    let mut child = process.spawn().map_err(|e| format!("Failed to start hook: {}", e))?;
+
+    // Written from a thread: a hook that never reads stdin must not block us
+    let mut stdin = child.stdin.take().ok_or("Failed to open hook stdin")?;
+    let input = input.to_string();
+    thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+
+    let (done_tx, done_rx) = channel();
+    let stdout = capture(child.stdout.take().ok_or("Failed to open hook stdout")?, done_tx.clone());
+    let stderr = capture(child.stderr.take().ok_or("Failed to open hook stderr")?, done_tx);
+
+    let deadline = Instant::now() + timeout;
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break (Some(status), false);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            break (None, true);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    // Background processes the hook started can hold the pipes open; don't
+    // wait on them for long.
+    for _ in 0..2 {
+        if done_rx.recv_timeout(Duration::from_millis(500)).is_err() {
+            break;
+        }
+    }
+
+    let text = |buffer: &Arc<Mutex<Vec<u8>>>| String::from_utf8_lossy(&buffer.lock().unwrap()).into_owned();
+    Ok(HookProcessOutput {
+        exit_code: status.and_then(|s| s.code()),
+        timed_out,
+        stdout: text(&stdout),
+        stderr: text(&stderr),
+    })
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    Some(text.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Interpret a hook's exit code and output the way Claude Code would for `event`.
+pub fn decode_decision(event: &str, exit_code: Option<i32>, timed_out: bool, stdout: &str, stderr: &str) -> HookDecision {
+    let mut decision = HookDecision {
+        outcome: HookOutcome::Proceed,
+        reason: None,
+        additional_context: None,
+        system_message: None,
+        suppress_output: false,
+        json_output: None,
+    };
+
+    if timed_out {
+        decision.outcome = HookOutcome::TimedOut;
+        decision.reason = Some("The hook was killed; Claude Code treats this as a non-blocking error".into());
+        return decision;
+    }
+
+    match exit_code {
+        Some(0) => {}
+        Some(2) => {
+            // Exit code 2 feeds stderr back to Claude, but only some events can block
+            let can_block = matches!(event, "PreToolUse" | "PostToolUse" | "UserPromptSubmit" | "Stop" | "SubagentStop");
+            decision.outcome = if can_block { HookOutcome::Block } else { HookOutcome::Error };
+            decision.reason = non_empty(stderr);
+            return decision;
+        }
+        _ => {
+            decision.outcome = HookOutcome::Error;
+            decision.reason = non_empty(stderr).or_else(|| Some(format!("Hook exited with {:?}", exit_code)));
+            return decision;
+        }
+    }
+
+    let output = serde_json::from_str::<Value>(stdout.trim()).ok().filter(|v| v.is_object());
+    let Some(output) = output else {
+        // Plain stdout becomes context for these events and is only logged otherwise
+        if matches!(event, "UserPromptSubmit" | "SessionStart") {
+            decision.additional_context = non_empty(stdout);
+        }
+        return decision;
+    };
+
+    let text = |v: &Value, key: &str| v.get(key).and_then(|s| s.as_str()).map(str::to_string);
+    let specific = output.get("hookSpecificOutput").cloned().unwrap_or(Value::Null);
+
+    decision.system_message = text(&output, "systemMessage");
+    decision.suppress_output = output.get("suppressOutput").and_then(|v| v.as_bool()).unwrap_or(false);
+    decision.additional_context = text(&specific, "additionalContext");
+
+    if let Some(permission) = text(&specific, "permissionDecision") {
+        decision.outcome = match permission.as_str() {
+            "allow" => HookOutcome::Approve,
+            "deny" => HookOutcome::Block,
+            "ask" => HookOutcome::Ask,
+            _ => HookOutcome::Proceed,
+        };
+        decision.reason = text(&specific, "permissionDecisionReason");
+    } else if let Some(legacy) = text(&output, "decision") {
+        decision.outcome = match legacy.as_str() {
+            "block" => HookOutcome::Block,
+            "approve" => HookOutcome::Approve,
+            _ => HookOutcome::Proceed,
+        };
+        decision.reason = text(&output, "reason");
+    }
+
+    // `continue: false` overrides any other decision
+    if output.get("continue").and_then(|v| v.as_bool()) == Some(false) {
+        decision.outcome = HookOutcome::Stop;
+        decision.reason = text(&output, "stopReason");
+    }
+
+    decision.json_output = Some(output);
+    decision
+}
+
+/// Run a hook command locally against a synthetic event and report what
+/// Claude Code would do with its result.
+fn dry_run(request: HookDryRun) -> Result<HookDryRunResult, String> {
+    if request.command.trim().is_empty() {
+        return Err("Hook command is empty".into());
+    }
+    if !HOOK_EVENTS.contains(&request.event.as_str()) {
+        return Err(format!("Unknown hook event \"{}\"", request.event));
+    }
+
+    let project_dir = request.project_path.as_deref().map(PathBuf::from);
+    let cwd = match &project_dir {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir().map_err(|e| e.to_string())?,
+    };
+    let input = synthetic_event(&request, &cwd);
+    let input_text = serde_json::to_string(&input).map_err(|e| e.to_string())?;
+
+    let matcher_matches = match (&request.matcher, input.get("tool_name").and_then(|t| t.as_str())) {
+        (Some(matcher), Some(tool)) => Some(matcher_matches(Some(matcher), tool)?),
+        _ => None,
+    };
+
+    let timeout = Duration::from_secs(request.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    let started = Instant::now();
+    let output = run_hook_command(&request.command, &cwd, project_dir.as_deref(), &input_text, timeout)?;
+    let decision = decode_decision(&request.event, output.exit_code, output.timed_out, &output.stdout, &output.stderr);
+
+    Ok(HookDryRunResult {
+        input,
+        exit_code: output.exit_code,
+        timed_out: output.timed_out,
+        stdout: output.stdout,
+        stderr: output.stderr,
+        duration_ms: started.elapsed().as_millis() as u64,
+        matcher_matches,
+        decision,
+    })
+}
+
+/// Dry-run a hook. A hook can sleep or wait on input for its whole timeout,
+/// so it runs on the blocking pool where it can't stall other commands.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn dry_run_hook(request: HookDryRun) -> Result<HookDryRunResult, String> {
+    tauri::async_runtime::spawn_blocking(move || dry_run(request))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn diagnostics(event: &str, matcher: &str) -> Vec<Diagnostic> {
+        let mut out = Vec::new();
+        matcher_diagnostics(event, matcher, "/hooks/x/0/matcher", &mut out);
+        out
+    }
+
+    #[test]
+    fn matchers_are_anchored_regexes() {
+        assert_eq!(matcher_matches(None, "Bash"), Ok(true));
+        assert_eq!(matcher_matches(Some(" "), "Bash"), Ok(true));
+        assert_eq!(matcher_matches(Some("*"), "Edit"), Ok(true));
+        assert_eq!(matcher_matches(Some("Edit|Write"), "Write"), Ok(true));
+        assert_eq!(matcher_matches(Some("Edit"), "MultiEdit"), Ok(false));
+        assert_eq!(matcher_matches(Some("mcp__github__.*"), "mcp__github__create_issue"), Ok(true));
+        assert!(matcher_matches(Some("Bash("), "Bash").is_err());
+    }
+
+    #[test]
+    fn matcher_diagnostics_catch_typos_and_ignored_matchers() {
+        assert!(diagnostics("PreToolUse", "Edit|Write").is_empty());
+        assert!(diagnostics("PreToolUse", "mcp__memory__store|Notebook.*").is_empty());
+
+        let typo = diagnostics("PostToolUse", "Edit|Wrte");
+        assert_eq!(typo.len(), 1);
+        assert_eq!(typo[0].severity, Severity::Warning);
+        assert_eq!(typo[0].suggestion.as_deref(), Some("Did you mean \"Write\"?"));
+
+        let session = diagnostics("SessionStart", "startup|resum");
+        assert_eq!(session[0].suggestion.as_deref(), Some("Did you mean \"resume\"?"));
+
+        let ignored = diagnostics("Stop", "Bash");
+        assert_eq!(ignored.len(), 1);
+        assert!(ignored[0].message.contains("ignore the matcher"));
+        assert!(diagnostics("Stop", "*").is_empty());
+
+        let invalid = diagnostics("PreToolUse", "Bash(");
+        assert_eq!(invalid[0].severity, Severity::Error);
+        assert!(invalid[0].message.starts_with("Invalid matcher regex"));
+    }
+
+    #[test]
+    fn exit_codes_decide_when_there_is_no_json() {
+        let blocked = decode_decision("PreToolUse", Some(2), false, "", "no rm please\n");
+        assert_eq!(blocked.outcome, HookOutcome::Block);
+        assert_eq!(blocked.reason.as_deref(), Some("no rm please"));
+        // Exit code 2 can't block a notification; it's reported as an error
+        assert_eq!(decode_decision("Notification", Some(2), false, "", "").outcome, HookOutcome::Error);
+
+        let failed = decode_decision("PreToolUse", Some(1), false, "", "");
+        assert_eq!(failed.outcome, HookOutcome::Error);
+        assert_eq!(failed.reason.as_deref(), Some("Hook exited with Some(1)"));
+        assert_eq!(decode_decision("PreToolUse", None, true, "", "").outcome, HookOutcome::TimedOut);
+
+        let context = decode_decision("UserPromptSubmit", Some(0), false, "Today is Friday\n", "");
+        assert_eq!(context.outcome, HookOutcome::Proceed);
+        assert_eq!(context.additional_context.as_deref(), Some("Today is Friday"));
+        assert_eq!(decode_decision("PostToolUse", Some(0), false, "logged", "").additional_context, None);
+    }
+
+    #[test]
+    fn json_output_decides_on_success() {
+        let stdout = r#"{"hookSpecificOutput": {"permissionDecision": "deny", "permissionDecisionReason": "prod"}, "systemMessage": "hi"}"#;
+        let denied = decode_decision("PreToolUse", Some(0), false, stdout, "");
+        assert_eq!(denied.outcome, HookOutcome::Block);
+        assert_eq!(denied.reason.as_deref(), Some("prod"));
+        assert_eq!(denied.system_message.as_deref(), Some("hi"));
+        assert!(denied.json_output.is_some());
+
+        let ask = decode_decision("PreToolUse", Some(0), false, r#"{"hookSpecificOutput": {"permissionDecision": "ask"}}"#, "");
+        assert_eq!(ask.outcome, HookOutcome::Ask);
+
+        let legacy = decode_decision("Stop", Some(0), false, r#"{"decision": "block", "reason": "tests fail"}"#, "");
+        assert_eq!(legacy.outcome, HookOutcome::Block);
+        assert_eq!(legacy.reason.as_deref(), Some("tests fail"));
+
+        let stopped = r#"{"decision": "approve", "continue": false, "stopReason": "done", "suppressOutput": true}"#;
+        let stopped = decode_decision("PostToolUse", Some(0), false, stopped, "");
+        assert_eq!(stopped.outcome, HookOutcome::Stop);
+        assert_eq!(stopped.reason.as_deref(), Some("done"));
+        assert!(stopped.suppress_output);
+    }
+
+    #[test]
+    fn malformed_groups_are_reported_not_dropped() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("settings.json");
+        let text = r#"{
+  "hooks": {
+    "PreToolUse": [
+      {"matcher": "Bash", "hooks": [{"type": "command", "command": "audit"}]},
+      {"matcher": "Edit", "hooks": "fmt"}
+    ]
+  }
+}"#;
+        fs::write(&path, text).unwrap();
+        let layer = LoadedLayer {
+            layer: SettingsLayer { scope: SettingsScope::Project, path: path.to_string_lossy().into_owned(), exists: true, error: None },
+            value: Some(serde_json::from_str(text).unwrap()),
+        };
+
+        let list = hooks_in_layers(vec![layer]);
+        assert_eq!(list.hooks.len(), 1);
+        assert_eq!(list.hooks[0].pointer, "/hooks/PreToolUse/0/hooks/0");
+        assert_eq!(list.errors.len(), 1);
+        let error = &list.errors[0].diagnostic;
+        assert_eq!(error.severity, Severity::Error);
+        assert_eq!(error.pointer, "/hooks/PreToolUse/1");
+        assert_eq!(error.line, Some(5));
+        assert!(error.message.starts_with("Invalid matcher group"));
+    }
+}
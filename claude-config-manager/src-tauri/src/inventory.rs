@@ -0,0 +1,328 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::definitions::{parse_definition_text, Definition, DefinitionKind};
+use crate::diagnostics::Diagnostic;
+
+/// Built-in slash commands always win over custom commands of the same name.
+const BUILTIN_COMMANDS: &[&str] = &[
+    "add-dir", "agents", "bug", "clear", "compact", "config", "context", "cost", "doctor", "exit", "export",
+    "help", "hooks", "init", "login", "logout", "mcp", "memory", "model", "output-style", "permissions",
+    "plugin", "pr-comments", "privacy-settings", "release-notes", "resume", "review", "rewind", "sandbox",
+    "security-review", "status", "statusline", "terminal-setup", "todos", "usage", "vim",
+];
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryScope {
+    User,       // ~/.claude/{agents,commands,skills}
+    Project,    // [ProjectRoot]/.claude/{agents,commands,skills}; wins over user
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Active,
+    Shadowed,       // A project definition with the same name wins
+    Duplicate,      // Another file in the same scope already uses the name
+    Unreachable,    // Claude Code can't load or invoke it; see `reason`
+}
+
+#[derive(Serialize)]
+pub struct InventoryItem {
+    pub kind: DefinitionKind,
+    pub name: String,                   // Agent/skill name, or command name like "frontend:component"
+    pub scope: InventoryScope,
+    pub path: String,
+    pub description: Option<String>,
+    pub definition: Option<Definition>,
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+    pub status: ItemStatus,
+    pub reason: Option<String>,
+    pub related_path: Option<String>,   // The winning definition for shadowed/duplicate items
+}
+
+#[derive(Serialize)]
+pub struct Inventory {
+    pub items: Vec<InventoryItem>,
+    pub active: usize,
+    pub shadowed: usize,
+    pub duplicated: usize,
+    pub unreachable: usize,
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|e| e.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    entries.retain(|p| !p.file_name().and_then(|n| n.to_str()).unwrap_or_default().starts_with('.'));
+    entries.sort();
+    entries
+}
+
+fn unreachable_item(kind: DefinitionKind, scope: InventoryScope, path: &Path, reason: &str) -> InventoryItem {
+    InventoryItem {
+        kind,
+        name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+        scope,
+        path: path.to_string_lossy().into_owned(),
+        description: None,
+        definition: None,
+        valid: false,
+        diagnostics: Vec::new(),
+        status: ItemStatus::Unreachable,
+        reason: Some(reason.to_string()),
+        related_path: None,
+    }
+}
+
+/// Parse a definition file into an item. `default_name` is used when the
+/// frontmatter doesn't name it (and always for commands).
+fn parse_item(kind: DefinitionKind, scope: InventoryScope, path: &Path, default_name: String) -> InventoryItem {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => return unreachable_item(kind, scope, path, &format!("Failed to read file: {}", e)),
+    };
+    let parsed = parse_definition_text(path, kind, &text);
+
+    let first_line = || parsed.body.lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_string);
+    let (name, description) = match &parsed.definition {
+        Some(Definition::Agent(agent)) => (agent.name.clone(), Some(agent.description.clone())),
+        Some(Definition::Skill(skill)) => (skill.name.clone(), Some(skill.description.clone())),
+        Some(Definition::Command(command)) => (default_name.clone(), command.description.clone().or_else(first_line)),
+        None => (String::new(), None),
+    };
+    let name = if name.trim().is_empty() { default_name } else { name };
+
+    // Agents and skills without a name and description are skipped by Claude
+    // Code; commands load even with broken frontmatter.
+    let loadable = kind == DefinitionKind::Command || parsed.valid;
+    InventoryItem {
+        kind,
+        name,
+        scope,
+        path: parsed.path,
+        description: description.filter(|d| !d.trim().is_empty()),
+        definition: parsed.definition,
+        valid: parsed.valid,
+        diagnostics: parsed.diagnostics,
+        status: if loadable { ItemStatus::Active } else { ItemStatus::Unreachable },
+        reason: (!loadable).then(|| "Frontmatter has errors, so Claude Code skips this file".to_string()),
+        related_path: None,
+    }
+}
+
+fn scan_agents(dir: &Path, scope: InventoryScope, out: &mut Vec<InventoryItem>) {
+    for path in sorted_entries(dir) {
+        if path.is_dir() {
+            scan_agents(&path, scope, out);
+        } else if path.extension().and_then(|x| x.to_str()) == Some("md") {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            out.push(parse_item(DefinitionKind::Agent, scope, &path, stem));
+        } else {
+            out.push(unreachable_item(DefinitionKind::Agent, scope, &path, "Not a markdown file"));
+        }
+    }
+}
+
+/// Commands in subdirectories are namespaced: commands/frontend/component.md
+/// is `frontend:component`.
+fn scan_commands(root: &Path, dir: &Path, scope: InventoryScope, out: &mut Vec<InventoryItem>) {
+    for path in sorted_entries(dir) {
+        if path.is_dir() {
+            scan_commands(root, &path, scope, out);
+            continue;
+        }
+        if path.extension().and_then(|x| x.to_str()) != Some("md") {
+            out.push(unreachable_item(DefinitionKind::Command, scope, &path, "Not a markdown file"));
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(&path).with_extension("");
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(":");
+        let mut item = parse_item(DefinitionKind::Command, scope, &path, name);
+        if BUILTIN_COMMANDS.contains(&item.name.as_str()) {
+            item.status = ItemStatus::Unreachable;
+            item.reason = Some(format!("/{} is a built-in command, which takes precedence", item.name));
+        }
+        out.push(item);
+    }
+}
+
+/// Each skill is a directory directly under skills/ containing SKILL.md.
+fn scan_skills(dir: &Path, scope: InventoryScope, out: &mut Vec<InventoryItem>) {
+    for path in sorted_entries(dir) {
+        if !path.is_dir() {
+            out.push(unreachable_item(DefinitionKind::Skill, scope, &path, "Skills must be directories containing SKILL.md"));
+            continue;
+        }
+        let skill_md = path.join("SKILL.md");
+        if !skill_md.is_file() {
+            let mut item = unreachable_item(DefinitionKind::Skill, scope, &path, "Missing SKILL.md");
+            item.name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            out.push(item);
+            continue;
+        }
+        let dir_name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        out.push(parse_item(DefinitionKind::Skill, scope, &skill_md, dir_name));
+    }
+}
+
+/// Mark duplicates within a scope, then user items shadowed by project ones.
+fn resolve_conflicts(items: &mut [InventoryItem]) {
+    let mut winners: HashMap<(DefinitionKind, String, bool), String> = HashMap::new();
+    for scope in [InventoryScope::Project, InventoryScope::User] {
+        for item in items.iter_mut().filter(|i| i.scope == scope && i.status == ItemStatus::Active) {
+            let is_project = scope == InventoryScope::Project;
+            if let Some(winner) = winners.get(&(item.kind, item.name.clone(), is_project)) {
+                item.status = ItemStatus::Duplicate;
+                item.reason = Some(format!("\"{}\" is already defined in this scope", item.name));
+                item.related_path = Some(winner.clone());
+                continue;
+            }
+            winners.insert((item.kind, item.name.clone(), is_project), item.path.clone());
+            if !is_project {
+                if let Some(winner) = winners.get(&(item.kind, item.name.clone(), true)) {
+                    item.status = ItemStatus::Shadowed;
+                    item.reason = Some(format!("The project's \"{}\" takes precedence", item.name));
+                    item.related_path = Some(winner.clone());
+                }
+            }
+        }
+    }
+}
+
+pub fn build_inventory(project_path: Option<&Path>) -> Inventory {
+    let paths = crate::get_config_paths();
+    let mut items = Vec::new();
+
+    let mut scan = |scope: InventoryScope, agents: &str, commands: &str, skills: &str| {
+        scan_agents(Path::new(agents), scope, &mut items);
+        scan_commands(Path::new(commands), Path::new(commands), scope, &mut items);
+        scan_skills(Path::new(skills), scope, &mut items);
+    };
+    scan(InventoryScope::User, &paths.user.agents.path, &paths.user.commands.path, &paths.user.skills.path);
+    if let Some(project) = project_path {
+        let files = crate::project_config_files(project);
+        scan(InventoryScope::Project, &files.agents.path, &files.commands.path, &files.skills.path);
+    }
+
+    resolve_conflicts(&mut items);
+
+    let count = |status: ItemStatus| items.iter().filter(|i| i.status == status).count();
+    Inventory {
+        active: count(ItemStatus::Active),
+        shadowed: count(ItemStatus::Shadowed),
+        duplicated: count(ItemStatus::Duplicate),
+        unreachable: count(ItemStatus::Unreachable),
+        items,
+    }
+}
+
+/// Every agent, command and skill visible to a project (or only user-level
+/// ones without a project), with shadowing and duplicate detection.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn inventory(project: Option<String>) -> Inventory {
+    build_inventory(project.as_deref().map(Path::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn put(path: PathBuf, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    fn agent(name: &str) -> String {
+        format!("---\nname: {}\ndescription: Does {}\n---\nPrompt\n", name, name)
+    }
+
+    /// Scan a user and a project `.claude` directory and resolve conflicts.
+    fn scan(user: &Path, project: &Path) -> Vec<InventoryItem> {
+        let mut items = Vec::new();
+        for (scope, root) in [(InventoryScope::User, user), (InventoryScope::Project, project)] {
+            scan_agents(&root.join("agents"), scope, &mut items);
+            scan_commands(&root.join("commands"), &root.join("commands"), scope, &mut items);
+            scan_skills(&root.join("skills"), scope, &mut items);
+        }
+        resolve_conflicts(&mut items);
+        items
+    }
+
+    fn find<'a>(items: &'a [InventoryItem], name: &str, scope: InventoryScope, file: &str) -> &'a InventoryItem {
+        items
+            .iter()
+            .find(|i| i.name == name && i.scope == scope && i.path.ends_with(file))
+            .unwrap_or_else(|| panic!("no {:?} item {} in {}", scope, name, file))
+    }
+
+    #[test]
+    fn project_agents_shadow_user_agents() {
+        let temp = TempDir::new().unwrap();
+        let (user, project) = (temp.path().join("home/.claude"), temp.path().join("app/.claude"));
+        put(user.join("agents/reviewer.md"), &agent("reviewer"));
+        put(user.join("agents/helper.md"), &agent("helper"));
+        // The frontmatter name counts, not the file name
+        put(project.join("agents/code-review.md"), &agent("reviewer"));
+
+        let items = scan(&user, &project);
+        let shadowed = find(&items, "reviewer", InventoryScope::User, "reviewer.md");
+        let winner = find(&items, "reviewer", InventoryScope::Project, "code-review.md");
+        assert_eq!(shadowed.status, ItemStatus::Shadowed);
+        assert_eq!(shadowed.related_path.as_deref(), Some(winner.path.as_str()));
+        assert_eq!(winner.status, ItemStatus::Active);
+        assert_eq!(find(&items, "helper", InventoryScope::User, "helper.md").status, ItemStatus::Active);
+    }
+
+    #[test]
+    fn namespaced_commands_and_duplicates() {
+        let temp = TempDir::new().unwrap();
+        let (user, project) = (temp.path().join("home/.claude"), temp.path().join("app/.claude"));
+        put(user.join("commands/frontend/component.md"), "Make a component\n");
+        put(project.join("commands/frontend/component.md"), "---\ndescription: Project component\n---\nBody\n");
+        put(project.join("commands/component.md"), "Not namespaced\n");
+        put(project.join("agents/a.md"), &agent("twin"));
+        put(project.join("agents/b.md"), &agent("twin"));
+
+        let items = scan(&user, &project);
+        let project_command = find(&items, "frontend:component", InventoryScope::Project, "frontend/component.md");
+        assert_eq!(project_command.status, ItemStatus::Active);
+        assert_eq!(project_command.description.as_deref(), Some("Project component"));
+        assert_eq!(find(&items, "frontend:component", InventoryScope::User, "frontend/component.md").status, ItemStatus::Shadowed);
+        // `component` and `frontend:component` are different commands
+        assert_eq!(find(&items, "component", InventoryScope::Project, "commands/component.md").status, ItemStatus::Active);
+
+        let first = find(&items, "twin", InventoryScope::Project, "a.md");
+        let second = find(&items, "twin", InventoryScope::Project, "b.md");
+        assert_eq!(first.status, ItemStatus::Active);
+        assert_eq!(second.status, ItemStatus::Duplicate);
+        assert_eq!(second.related_path.as_deref(), Some(first.path.as_str()));
+    }
+
+    #[test]
+    fn builtin_names_are_unreachable_and_never_win() {
+        let temp = TempDir::new().unwrap();
+        let (user, project) = (temp.path().join("home/.claude"), temp.path().join("app/.claude"));
+        put(user.join("commands/review.md"), "My review\n");
+        put(project.join("commands/review.md"), "Project review\n");
+        put(project.join("commands/git/review.md"), "Namespaced review\n");
+
+        let items = scan(&user, &project);
+        for scope in [InventoryScope::User, InventoryScope::Project] {
+            let item = find(&items, "review", scope, "commands/review.md");
+            assert_eq!(item.status, ItemStatus::Unreachable);
+            assert_eq!(item.reason.as_deref(), Some("/review is a built-in command, which takes precedence"));
+            assert_eq!(item.related_path, None);
+        }
+        assert_eq!(find(&items, "git:review", InventoryScope::Project, "git/review.md").status, ItemStatus::Active);
+    }
+}
@@ -1,9 +1,36 @@
+// Without the GUI, commands only reachable through the invoke handler are unused
+#![cfg_attr(not(feature = "gui"), allow(dead_code))]
+
 use serde::Serialize;
-use std::path::{PathBuf};
+use std::path::{Path, PathBuf};
 use std::env;
 use std::fs;
 
-#[derive(Serialize)]
+mod budget;
+mod bundle;
+pub mod cli;
+mod definitions;
+mod diagnostics;
+mod discovery;
+mod doctor;
+mod enterprise;
+mod hooks;
+mod inventory;
+mod mcp;
+mod memory;
+mod permissions;
+mod policy;
+mod profiles;
+mod sandbox;
+mod schema;
+mod secrets;
+mod settings;
+mod storage;
+mod trash;
+#[cfg(feature = "gui")]
+mod watcher;
+
+#[derive(Serialize, Clone)]
 pub struct ConfigPathInfo {
     pub path: String,
     pub exists: bool,
@@ -35,10 +62,22 @@ pub struct UserPaths {
     pub skills: ConfigPathInfo,                 // ~/.claude/skills/
 }
 
-#[tauri::command]
-fn get_config_paths() -> ConfigPaths {
+fn home_dir() -> PathBuf {
     let home = env::var("HOME").or_else(|_| env::var("USERPROFILE")).unwrap_or_else(|_| ".".to_string());
-    let home_path = PathBuf::from(home);
+    PathBuf::from(home)
+}
+
+fn make_info(pb: PathBuf) -> ConfigPathInfo {
+    ConfigPathInfo {
+        exists: pb.exists(),
+        is_dir: pb.is_dir(),
+        path: pb.to_string_lossy().into_owned(),
+    }
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+fn get_config_paths() -> ConfigPaths {
+    let home_path = home_dir();
 
     // Enterprise paths per official docs:
     // - Windows: C:\Program Files\ClaudeCode\
@@ -52,14 +91,6 @@ fn get_config_paths() -> ConfigPaths {
         PathBuf::from("/etc/claude-code")
     };
 
-    let make_info = |pb: PathBuf| {
-        ConfigPathInfo {
-            exists: pb.exists(),
-            is_dir: pb.is_dir(),
-            path: pb.to_string_lossy().into_owned(),
-        }
-    };
-
     ConfigPaths {
         enterprise: EnterprisePaths {
             claude_md: make_info(enterprise_base.join("CLAUDE.md")),
@@ -79,19 +110,14 @@ fn get_config_paths() -> ConfigPaths {
     }
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn file_exists(path: String) -> bool {
     PathBuf::from(path).exists()
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn get_path_info(path: String) -> ConfigPathInfo {
-    let pb = PathBuf::from(path);
-    ConfigPathInfo {
-        exists: pb.exists(),
-        is_dir: pb.is_dir(),
-        path: pb.to_string_lossy().into_owned(),
-    }
+    make_info(PathBuf::from(path))
 }
 
 #[derive(Serialize)]
@@ -117,62 +143,43 @@ pub struct ProjectInfo {
     pub config_files: ProjectConfigFiles,
 }
 
-#[tauri::command]
-fn list_projects(base_dir: String) -> Vec<ProjectInfo> {
-    let mut projects = Vec::new();
-
-    let make_info = |pb: PathBuf| {
-        ConfigPathInfo {
-            exists: pb.exists(),
-            is_dir: pb.is_dir(),
-            path: pb.to_string_lossy().into_owned(),
-        }
-    };
+fn project_config_files(path: &Path) -> ProjectConfigFiles {
+    ProjectConfigFiles {
+        claude_md_root: make_info(path.join("CLAUDE.md")),
+        claude_md_dotclaude: make_info(path.join(".claude").join("CLAUDE.md")),
+        claude_local_md: make_info(path.join("CLAUDE.local.md")),
+        settings: make_info(path.join(".claude").join("settings.json")),
+        settings_local: make_info(path.join(".claude").join("settings.local.json")),
+        rules: make_info(path.join(".claude").join("rules")),
+        commands: make_info(path.join(".claude").join("commands")),
+        agents: make_info(path.join(".claude").join("agents")),
+        skills: make_info(path.join(".claude").join("skills")),
+        mcp: make_info(path.join(".mcp.json")),
+    }
+}
 
-    if let Ok(entries) = fs::read_dir(base_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
-                let claude_md_root = path.join("CLAUDE.md");
-                let claude_md_dotclaude = path.join(".claude").join("CLAUDE.md");
-                let has_claude_md = claude_md_root.exists() || claude_md_dotclaude.exists();
-
-                let is_project = has_claude_md ||
-                               path.join(".git").exists() ||
-                               path.join("package.json").exists() ||
-                               path.join("tsconfig.json").exists() ||
-                               path.join("pyproject.toml").exists() ||
-                               path.join("go.mod").exists() ||
-                               path.join("Cargo.toml").exists();
-
-                if is_project {
-                    let config_files = ProjectConfigFiles {
-                        claude_md_root: make_info(claude_md_root),
-                        claude_md_dotclaude: make_info(claude_md_dotclaude),
-                        claude_local_md: make_info(path.join("CLAUDE.local.md")),
-                        settings: make_info(path.join(".claude").join("settings.json")),
-                        settings_local: make_info(path.join(".claude").join("settings.local.json")),
-                        rules: make_info(path.join(".claude").join("rules")),
-                        commands: make_info(path.join(".claude").join("commands")),
-                        agents: make_info(path.join(".claude").join("agents")),
-                        skills: make_info(path.join(".claude").join("skills")),
-                        mcp: make_info(path.join(".mcp.json")),
-                    };
-
-                    projects.push(ProjectInfo {
-                        id: name.clone(),
-                        path: path.to_string_lossy().into_owned(),
-                        name,
-                        has_claude_md,
-                        config_files,
-                    });
-                }
-            }
-        }
+fn project_info(path: &Path, id: String) -> ProjectInfo {
+    let config_files = project_config_files(path);
+    ProjectInfo {
+        id,
+        path: path.to_string_lossy().into_owned(),
+        name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        has_claude_md: config_files.claude_md_root.exists || config_files.claude_md_dotclaude.exists,
+        config_files,
     }
-    projects
 }
+
+/// Find projects under `base_dir`. Without options this walks three levels
+/// deep, honours .gitignore and stops at the first project root on each branch.
+#[cfg_attr(feature = "gui", tauri::command)]
+fn list_projects(base_dir: String, options: Option<discovery::ScanOptions>) -> Result<Vec<ProjectInfo>, String> {
+    let projects = discovery::scan_projects(Path::new(&base_dir), &options.unwrap_or_default())?;
+    // Markers come from the webview; only the built-in ones make a root
+    let markers = discovery::ScanOptions::default().markers;
+    sandbox::register_projects(projects.iter().map(|p| Path::new(&p.path)).filter(|p| discovery::is_project_dir(p, &markers)));
+    Ok(projects)
+}
+
 #[derive(Serialize)]
 pub struct SubdirClaudeMd {
     pub relative_path: String,    // e.g., "src/billing"
@@ -180,92 +187,149 @@ pub struct SubdirClaudeMd {
     pub exists: bool,
 }
 
-/// Recursively discover CLAUDE.md files in subdirectories of a project
-#[tauri::command]
-fn discover_subdirectory_claude_md(project_path: String, max_depth: Option<u32>) -> Vec<SubdirClaudeMd> {
-    let mut results = Vec::new();
-    let base = PathBuf::from(&project_path);
-    let max_depth = max_depth.unwrap_or(5);
-
-    fn recurse(
-        base: &PathBuf,
-        current: &PathBuf,
-        depth: u32,
-        max_depth: u32,
-        results: &mut Vec<SubdirClaudeMd>,
-    ) {
+/// How far below a project nested CLAUDE.md files are looked for.
+const SUBDIR_CLAUDE_MD_DEPTH: u32 = 5;
+
+/// Subdirectories of a project that may hold their own CLAUDE.md, depth
+/// first, skipping hidden, dependency and build output directories.
+pub fn project_subdirs(base: &Path, max_depth: Option<u32>) -> Vec<PathBuf> {
+    fn recurse(current: &Path, depth: u32, max_depth: u32, dirs: &mut Vec<PathBuf>) {
         if depth > max_depth {
             return;
         }
-
-        if let Ok(entries) = fs::read_dir(current) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-
-                // Skip hidden directories (except .claude which we already handle at project level)
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if name.starts_with('.') {
-                        continue;
-                    }
-                }
-
-                // Skip common non-source directories
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if matches!(name, "node_modules" | "target" | "dist" | "build" | "__pycache__" | "vendor" | ".git") {
-                        continue;
-                    }
-                }
-
-                if path.is_dir() {
-                    // Check for CLAUDE.md in this subdirectory
-                    let claude_md_path = path.join("CLAUDE.md");
-                    if claude_md_path.exists() {
-                        let relative = path.strip_prefix(base).unwrap_or(&path);
-                        results.push(SubdirClaudeMd {
-                            relative_path: relative.to_string_lossy().into_owned(),
-                            full_path: claude_md_path.to_string_lossy().into_owned(),
-                            exists: true,
-                        });
-                    }
-
-                    // Also check for CLAUDE.local.md
-                    let claude_local_path = path.join("CLAUDE.local.md");
-                    if claude_local_path.exists() {
-                        let relative = path.strip_prefix(base).unwrap_or(&path);
-                        results.push(SubdirClaudeMd {
-                            relative_path: format!("{} (local)", relative.to_string_lossy()),
-                            full_path: claude_local_path.to_string_lossy().into_owned(),
-                            exists: true,
-                        });
-                    }
-
-                    // Recurse into subdirectory
-                    recurse(base, &path, depth + 1, max_depth, results);
+        let Ok(entries) = fs::read_dir(current) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Hidden directories (.claude is handled at project level) and
+            // common non-source directories
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if name.starts_with('.') || matches!(name, "node_modules" | "target" | "dist" | "build" | "__pycache__" | "vendor") {
+                    continue;
                 }
             }
+            if path.is_dir() {
+                dirs.push(path.clone());
+                recurse(&path, depth + 1, max_depth, dirs);
+            }
         }
     }
 
-    recurse(&base, &base, 0, max_depth, &mut results);
+    let mut dirs = Vec::new();
+    recurse(base, 0, max_depth.unwrap_or(SUBDIR_CLAUDE_MD_DEPTH), &mut dirs);
+    dirs
+}
+
+/// Recursively discover CLAUDE.md files in subdirectories of a project
+#[cfg_attr(feature = "gui", tauri::command)]
+fn discover_subdirectory_claude_md(project_path: String, max_depth: Option<u32>) -> Vec<SubdirClaudeMd> {
+    let base = PathBuf::from(&project_path);
+    let mut results = Vec::new();
+    for dir in project_subdirs(&base, max_depth) {
+        let relative = dir.strip_prefix(&base).unwrap_or(&dir).to_string_lossy().into_owned();
+        let claude_md_path = dir.join("CLAUDE.md");
+        if claude_md_path.exists() {
+            results.push(SubdirClaudeMd {
+                relative_path: relative.clone(),
+                full_path: claude_md_path.to_string_lossy().into_owned(),
+                exists: true,
+            });
+        }
+        let claude_local_path = dir.join("CLAUDE.local.md");
+        if claude_local_path.exists() {
+            results.push(SubdirClaudeMd {
+                relative_path: format!("{} (local)", relative),
+                full_path: claude_local_path.to_string_lossy().into_owned(),
+                exists: true,
+            });
+        }
+    }
     results
 }
 
-#[tauri::command]
-fn read_config_file(path: String) -> Result<String, String> {
-    let pb = PathBuf::from(&path);
+#[derive(Serialize)]
+pub struct ConfigFileContents {
+    pub content: String,
+    pub version: String,        // Pass back to save_config_file to detect external edits
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+fn read_config_file(path: String) -> Result<ConfigFileContents, String> {
+    let pb = sandbox::check_path(&path)?;
     if pb.is_dir() {
         return Err("This path is a directory. Please select a file within it to view its contents.".into());
     }
-    fs::read_to_string(path).map_err(|e| e.to_string())
+    let content = fs::read_to_string(pb).map_err(|e| e.to_string())?;
+    Ok(ConfigFileContents {
+        version: storage::content_version(content.as_bytes()),
+        content,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SaveError {
+    /// The file changed on disk since it was read; nothing was written.
+    Conflict {
+        message: String,
+        current_content: Option<String>,    // None if the file was deleted
+        current_version: Option<String>,
+    },
+    /// The content contains what looks like credentials and the file is
+    /// tracked by git, or is in a repository without being ignored; nothing
+    /// was written. Retry with `allow_secrets`.
+    SecretsInTrackedFile {
+        message: String,
+        findings: Vec<secrets::SecretMatch>,
+    },
+    /// The path is outside every config root (see `sandbox`).
+    PathNotAllowed { message: String },
+    Io { message: String },
 }
 
-#[tauri::command]
-fn save_config_file(path: String, content: String) -> Result<(), String> {
-    let path_buf = PathBuf::from(&path);
-    if let Some(parent) = path_buf.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+impl From<String> for SaveError {
+    fn from(message: String) -> Self {
+        SaveError::Io { message }
     }
-    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Save a config file. When `expected_version` is given (from
+/// `read_config_file`) the save is refused if the file has changed since.
+/// Saving what looks like a secret into a file git tracks or would pick up
+/// (in a work tree and not ignored) is refused unless `allow_secrets` is
+/// set. Returns the version of the newly written content.
+/// Paths outside the config roots are refused with `PathNotAllowed`.
+#[cfg_attr(feature = "gui", tauri::command)]
+fn save_config_file(
+    path: String,
+    content: String,
+    expected_version: Option<String>,
+    allow_secrets: Option<bool>,
+) -> Result<String, SaveError> {
+    let path_buf = sandbox::check_path(&path).map_err(|message| SaveError::PathNotAllowed { message })?;
+
+    if let Some(expected) = expected_version {
+        let current_version = storage::file_version(&path_buf);
+        if current_version.as_deref() != Some(expected.as_str()) {
+            return Err(SaveError::Conflict {
+                message: format!("{} was modified by another program since it was opened", path),
+                current_content: fs::read_to_string(&path_buf).ok(),
+                current_version,
+            });
+        }
+    }
+
+    if !allow_secrets.unwrap_or(false) {
+        let findings = secrets::scan_text(&content);
+        if !findings.is_empty() && secrets::is_git_committable(&path_buf) {
+            return Err(SaveError::SecretsInTrackedFile {
+                message: format!("{} could be committed to git and would contain {} possible secret(s)", path, findings.len()),
+                findings,
+            });
+        }
+    }
+
+    storage::write_with_backup(&path_buf, content.as_bytes())?;
+    Ok(storage::content_version(content.as_bytes()))
 }
 
 #[derive(Serialize)]
@@ -275,14 +339,15 @@ pub struct DirectoryEntry {
     pub is_dir: bool,
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn list_directory(path: String) -> Result<Vec<DirectoryEntry>, String> {
     let mut entries = Vec::new();
 
-    let read_dir = fs::read_dir(&path).map_err(|e| e.to_string())?;
+    let read_dir = fs::read_dir(sandbox::check_path(&path)?).map_err(|e| e.to_string())?;
 
     for entry in read_dir.flatten() {
-        let entry_path = entry.path();
+        // Report entries under the path as given, not its canonical form
+        let entry_path = Path::new(&path).join(entry.file_name());
         let name = entry_path.file_name()
             .unwrap_or_default()
             .to_string_lossy()
@@ -307,26 +372,25 @@ fn list_directory(path: String) -> Result<Vec<DirectoryEntry>, String> {
     Ok(entries)
 }
 
-#[tauri::command]
-fn delete_path(path: String) -> Result<(), String> {
-    let path_buf = PathBuf::from(&path);
-
-    if path_buf.is_dir() {
-        fs::remove_dir_all(&path).map_err(|e| e.to_string())
-    } else {
-        fs::remove_file(&path).map_err(|e| e.to_string())
-    }
+/// Move a file or directory to the app's trash; see `trash::restore_from_trash`.
+#[cfg_attr(feature = "gui", tauri::command)]
+fn delete_path(path: String) -> Result<trash::TrashEntry, String> {
+    trash::move_to_trash(&sandbox::check_removable(&path)?)
 }
 
-#[tauri::command]
+#[cfg_attr(feature = "gui", tauri::command)]
 fn create_directory(path: String) -> Result<(), String> {
-    fs::create_dir_all(&path).map_err(|e| e.to_string())
+    fs::create_dir_all(sandbox::check_path(&path)?).map_err(|e| e.to_string())
 }
 
-use tauri::menu::{Menu, MenuItem};
+#[cfg(feature = "gui")]
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+#[cfg(feature = "gui")]
 use tauri::tray::TrayIconBuilder;
+#[cfg(feature = "gui")]
 use tauri::Manager;
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -342,14 +406,51 @@ pub fn run() {
             discover_subdirectory_claude_md,
             list_directory,
             delete_path,
-            create_directory
+            create_directory,
+            settings::resolve_effective_settings,
+            storage::list_backups,
+            storage::restore_backup,
+            watcher::watch_projects,
+            discovery::list_known_projects,
+            doctor::run_doctor,
+            enterprise::preview_enterprise_file,
+            enterprise::write_enterprise_file,
+            schema::validate_settings,
+            permissions::evaluate_permission,
+            policy::audit_policy,
+            mcp::test_mcp_server,
+            mcp::list_mcp_servers,
+            mcp::upsert_mcp_server,
+            mcp::remove_mcp_server,
+            hooks::list_hooks,
+            hooks::dry_run_hook,
+            memory::resolve_claude_md_imports,
+            memory::resolve_memory_for_cwd,
+            budget::token_budget_report,
+            definitions::parse_definition,
+            definitions::validate_definitions,
+            inventory::inventory,
+            profiles::list_profiles,
+            profiles::save_profile,
+            profiles::delete_profile,
+            profiles::diff_profiles,
+            profiles::activate_profile,
+            bundle::export_bundle,
+            bundle::import_bundle,
+            secrets::scan_secrets,
+            trash::list_trash,
+            trash::restore_from_trash,
+            trash::empty_trash,
+            trash::get_trash_retention,
+            trash::set_trash_retention
         ])
         .setup(|app| {
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let show_i = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
+            app.manage(watcher::ConfigWatcher::start(app.handle().clone()));
+            std::thread::spawn(trash::purge_expired);
+
+            let menu = tray_menu(app.handle())?;
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .show_menu_on_left_click(true)
@@ -362,7 +463,14 @@ pub fn run() {
                         window.show().unwrap();
                         window.set_focus().unwrap();
                     }
-                    _ => {}
+                    id => {
+                        if let Some(name) = id.strip_prefix(profiles::PROFILE_MENU_PREFIX) {
+                            // Activation touches many files; keep it off the event loop
+                            let app = app.clone();
+                            let name = name.to_string();
+                            std::thread::spawn(move || profiles::activate_from_tray(&app, &name));
+                        }
+                    }
                 })
                 .build(app)?;
 
@@ -371,3 +479,33 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(feature = "gui")]
+const TRAY_ID: &str = "main";
+
+/// Tray menu: Show, a Profiles submenu with the active profile checked, and Quit.
+#[cfg(feature = "gui")]
+fn tray_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let show_i = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+
+    let profiles_menu = Submenu::new(app, "Profiles", true)?;
+    let profiles = profiles::profile_infos(false);
+    if profiles.is_empty() {
+        profiles_menu.append(&MenuItem::new(app, "No saved profiles", false, None::<&str>)?)?;
+    }
+    for profile in profiles {
+        let id = format!("{}{}", profiles::PROFILE_MENU_PREFIX, profile.name);
+        profiles_menu.append(&CheckMenuItem::with_id(app, id, &profile.name, true, profile.active, None::<&str>)?)?;
+    }
+
+    Menu::with_items(app, &[&show_i, &profiles_menu, &PredefinedMenuItem::separator(app)?, &quit_i])
+}
+
+/// Rebuild the tray menu after profiles are saved, deleted or activated.
+#[cfg(feature = "gui")]
+pub fn refresh_tray_menu(app: &tauri::AppHandle) {
+    if let (Some(tray), Ok(menu)) = (app.tray_by_id(TRAY_ID), tray_menu(app)) {
+        let _ = tray.set_menu(Some(menu));
+    }
+}
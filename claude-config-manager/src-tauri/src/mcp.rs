@@ -0,0 +1,718 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::settings::pointer_segment;
+
+/// Protocol revision we offer in `initialize`; servers answer with the one
+/// they actually speak.
+const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Cap on captured stderr so a chatty server can't exhaust memory.
+const MAX_STDERR_BYTES: usize = 64 * 1024;
+
+/// One entry of an `mcpServers` map. `type` is omitted for stdio servers in
+/// most files, so this is a flat struct rather than a tagged enum.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct McpServerConfig {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,           // "stdio" (default), "sse" or "http"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl McpServerConfig {
+    pub fn transport(&self) -> &str {
+        self.kind.as_deref().unwrap_or("stdio")
+    }
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` the way Claude Code does for
+/// .mcp.json values. Unset variables without a default become empty.
+pub fn expand_env_vars(input: &str, env: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let expr = &after[..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((n, d)) => (n, Some(d)),
+            None => (expr, None),
+        };
+        out.push_str(&env(name).or(default.map(str::to_string)).unwrap_or_default());
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Serialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub input_schema: Option<Value>,
+}
+
+#[derive(Serialize, Default)]
+pub struct McpProbeResult {
+    pub ok: bool,
+    pub protocol_version: Option<String>,
+    pub server_info: Option<Value>,     // { name, version } from the server
+    pub capabilities: Option<Value>,
+    pub tools: Vec<McpToolInfo>,
+    pub stderr: String,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Look up a server by name in an .mcp.json, ~/.claude.json or
+/// managed-mcp.json file. For ~/.claude.json the project's own `mcpServers`
+/// are checked before the top-level ones.
+pub fn find_server_config(config_path: &str, server_name: &str, project_path: Option<&str>) -> Result<McpServerConfig, String> {
+    let text = fs::read_to_string(config_path).map_err(|e| e.to_string())?;
+    let root: Value = serde_json::from_str(&text).map_err(|e| format!("Invalid JSON in {}: {}", config_path, e))?;
+
+    let project_servers = project_path.and_then(|p| root.get("projects")?.get(p)?.get("mcpServers"));
+    let server = project_servers
+        .and_then(|s| s.get(server_name))
+        .or_else(|| root.get("mcpServers")?.get(server_name))
+        .ok_or_else(|| format!("No MCP server named \"{}\" in {}", server_name, config_path))?;
+
+    serde_json::from_value(server.clone()).map_err(|e| format!("Invalid config for \"{}\": {}", server_name, e))
+}
+
+struct StdioSession {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    stderr: Arc<Mutex<String>>,
+    deadline: Instant,
+}
+
+impl StdioSession {
+    fn send(&mut self, message: &Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        match self.stdin.write_all(line.as_bytes()).and_then(|_| self.stdin.flush()) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Err(self.closed_error()),
+            Err(e) => Err(format!("Failed to write to server: {}", e)),
+        }
+    }
+
+    /// The server closed a pipe; wait (until the deadline) for it to exit
+    /// so the error can carry its exit status.
+    fn closed_error(&mut self) -> String {
+        loop {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                return format!("Server exited ({}) before responding", status);
+            }
+            if Instant::now() >= self.deadline {
+                return "Server closed its pipes without responding".into();
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Wait for the response to request `id`, skipping notifications, server
+    /// requests and any non-JSON noise on stdout.
+    fn response(&mut self, id: u64) -> Result<Value, String> {
+        loop {
+            let remaining = self.deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err("Timed out waiting for the server to respond".into());
+            }
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Disconnected) => return Err(self.closed_error()),
+                Err(RecvTimeoutError::Timeout) => return Err("Timed out waiting for the server to respond".into()),
+            };
+            let Ok(message) = serde_json::from_str::<Value>(&line) else { continue };
+            if message.get("id").and_then(|v| v.as_u64()) != Some(id) || message.get("method").is_some() {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(format!("Server returned an error: {}", error));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    fn request(&mut self, id: u64, method: &str, params: Value) -> Result<Value, String> {
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))?;
+        self.response(id)
+    }
+}
+
+fn spawn_stdio(config: &McpServerConfig, timeout: Duration) -> Result<StdioSession, String> {
+    let lookup = |name: &str| config.env.get(name).cloned().or_else(|| std::env::var(name).ok());
+    let command = config
+        .command
+        .as_deref()
+        .map(|c| expand_env_vars(c, &lookup))
+        .filter(|c| !c.trim().is_empty())
+        .ok_or("Server has no command")?;
+    let args: Vec<String> = config.args.iter().map(|a| expand_env_vars(a, &lookup)).collect();
+    let env_lookup = |name: &str| std::env::var(name).ok();
+    let env: Vec<(String, String)> = config
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), expand_env_vars(v, &env_lookup)))
+        .collect();
+
+    let mut child = Command::new(&command)
+        .args(&args)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start \"{}\": {}", command, e))?;
+
+    let stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+    let mut stderr_pipe = child.stderr.take().ok_or("Failed to open server stderr")?;
+
+    let (tx, lines) = channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let stderr = Arc::new(Mutex::new(String::new()));
+    let stderr_sink = stderr.clone();
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = stderr_pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let mut captured = stderr_sink.lock().unwrap();
+            if captured.len() < MAX_STDERR_BYTES {
+                captured.push_str(&String::from_utf8_lossy(&buf[..n]));
+            }
+        }
+    });
+
+    Ok(StdioSession {
+        child,
+        stdin,
+        lines,
+        stderr,
+        deadline: Instant::now() + timeout,
+    })
+}
+
+fn run_handshake(session: &mut StdioSession, result: &mut McpProbeResult) -> Result<(), String> {
+    let init = session.request(
+        1,
+        "initialize",
+        json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "claude-config-manager", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    )?;
+    result.protocol_version = init.get("protocolVersion").and_then(|v| v.as_str()).map(str::to_string);
+    result.server_info = init.get("serverInfo").cloned();
+    result.capabilities = init.get("capabilities").cloned();
+
+    session.send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))?;
+
+    let has_tools = result.capabilities.as_ref().map(|c| c.get("tools").is_some()).unwrap_or(true);
+    if !has_tools {
+        return Ok(());
+    }
+
+    let mut cursor: Option<String> = None;
+    for page in 0..20u64 {
+        let params = match &cursor {
+            Some(c) => json!({ "cursor": c }),
+            None => json!({}),
+        };
+        let listed = session.request(2 + page, "tools/list", params)?;
+        for tool in listed.get("tools").and_then(|t| t.as_array()).into_iter().flatten() {
+            result.tools.push(McpToolInfo {
+                name: tool.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+                description: tool.get("description").and_then(|d| d.as_str()).map(str::to_string),
+                input_schema: tool.get("inputSchema").cloned(),
+            });
+        }
+        cursor = listed.get("nextCursor").and_then(|c| c.as_str()).map(str::to_string);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Start a stdio server, run `initialize` and `tools/list`, then shut it down.
+pub fn probe_stdio_server(config: &McpServerConfig, timeout: Duration) -> McpProbeResult {
+    let started = Instant::now();
+    let mut result = McpProbeResult::default();
+
+    if config.transport() != "stdio" {
+        result.error = Some(format!("Only stdio servers can be tested (this one uses {})", config.transport()));
+        return result;
+    }
+
+    match spawn_stdio(config, timeout) {
+        Ok(mut session) => {
+            let outcome = run_handshake(&mut session, &mut result);
+            let _ = session.child.kill();
+            let _ = session.child.wait();
+            // Give the stderr reader a moment to drain what the server printed
+            thread::sleep(Duration::from_millis(50));
+            result.stderr = session.stderr.lock().unwrap().clone();
+            match outcome {
+                Ok(()) => result.ok = true,
+                Err(e) => result.error = Some(e),
+            }
+        }
+        Err(e) => result.error = Some(e),
+    }
+
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+/// Connect to a configured MCP server and report what it offers. The
+/// handshake blocks for up to `timeout_ms`, so it runs on the blocking pool
+/// rather than an async worker.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn test_mcp_server(
+    config_path: String,
+    server_name: String,
+    project_path: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<McpProbeResult, String> {
+    let config_path = crate::sandbox::check_path(&config_path)?;
+    let config = find_server_config(&config_path.to_string_lossy(), &server_name, project_path.as_deref())?;
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    tauri::async_runtime::spawn_blocking(move || probe_stdio_server(&config, timeout))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Where an MCP server is defined. Claude Code prefers local over project
+/// over user when the same name appears in more than one place.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum McpScope {
+    Managed,    // managed-mcp.json (read-only)
+    User,       // ~/.claude.json -> mcpServers
+    Project,    // [ProjectRoot]/.mcp.json -> mcpServers
+    Local,      // ~/.claude.json -> projects[ProjectRoot].mcpServers
+}
+
+#[derive(Serialize)]
+pub struct McpServerEntry {
+    pub name: String,
+    pub scope: McpScope,
+    pub source_path: String,
+    pub transport: String,
+    pub config: McpServerConfig,
+    pub overridden_by: Option<McpScope>,    // A higher-precedence scope defines the same name
+}
+
+/// A file or server entry that couldn't be read. The rest of the listing is
+/// still returned.
+#[derive(Serialize)]
+pub struct McpListingError {
+    pub scope: McpScope,
+    pub source_path: String,
+    pub name: Option<String>,               // None when the whole file is unreadable
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct McpServerListing {
+    pub servers: Vec<McpServerEntry>,
+    pub errors: Vec<McpListingError>,
+}
+
+/// The file holding a scope's servers and the JSON pointer of its
+/// `mcpServers` object within that file.
+fn scope_location(scope: McpScope, project_path: Option<&str>) -> Result<(PathBuf, String), String> {
+    let paths = crate::get_config_paths();
+    let project = || project_path.ok_or_else(|| format!("The {:?} scope needs a project path", scope).to_lowercase());
+    match scope {
+        McpScope::Managed => Ok((PathBuf::from(paths.enterprise.managed_mcp.path), "/mcpServers".into())),
+        McpScope::User => Ok((PathBuf::from(paths.user.mcp.path), "/mcpServers".into())),
+        McpScope::Project => Ok((
+            PathBuf::from(crate::project_config_files(Path::new(project()?)).mcp.path),
+            "/mcpServers".into(),
+        )),
+        McpScope::Local => Ok((
+            PathBuf::from(paths.user.mcp.path),
+            format!("/projects/{}/mcpServers", pointer_segment(project()?)),
+        )),
+    }
+}
+
+pub fn read_json_object(path: &Path) -> Result<Value, String> {
+    match fs::read_to_string(path) {
+        Ok(text) if text.trim().is_empty() => Ok(Value::Object(Map::new())),
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Invalid JSON in {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Value::Object(Map::new())),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Apply `edit` to the JSON in `path` and write it back, leaving every other
+/// key untouched. Claude Code rewrites ~/.claude.json while it runs, so the
+/// write is retried if the file changes between our read and our write.
+pub fn update_json_file(path: &Path, edit: impl Fn(&mut Value) -> Result<(), String>) -> Result<(), String> {
+    for _ in 0..3 {
+        let before = crate::storage::file_version(path);
+        let mut root = read_json_object(path)?;
+        edit(&mut root)?;
+        let mut text = serde_json::to_string_pretty(&root).map_err(|e| e.to_string())?;
+        text.push('\n');
+        if crate::storage::file_version(path) != before {
+            continue;
+        }
+        return crate::storage::write_with_backup(path, text.as_bytes());
+    }
+    Err(format!("{} kept changing while saving; try again", path.display()))
+}
+
+/// The object at `pointer`, creating empty objects along the way.
+fn object_at<'a>(root: &'a mut Value, pointer: &str) -> Result<&'a mut Map<String, Value>, String> {
+    let mut current = root;
+    for segment in pointer.split('/').skip(1) {
+        let key = segment.replace("~1", "/").replace("~0", "~");
+        current = current
+            .as_object_mut()
+            .ok_or_else(|| format!("Expected an object while resolving {}", pointer))?
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    current
+        .as_object_mut()
+        .ok_or_else(|| format!("Expected an object at {}", pointer))
+}
+
+pub fn validate_server_config(name: &str, config: &McpServerConfig) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Server name is required".into());
+    }
+    match config.transport() {
+        "stdio" => {
+            if config.command.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+                return Err(format!("stdio server \"{}\" needs a command", name));
+            }
+            if !config.headers.is_empty() || config.url.is_some() {
+                return Err(format!("stdio server \"{}\" can't have a url or headers", name));
+            }
+        }
+        "sse" | "http" => {
+            if config.url.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+                return Err(format!("{} server \"{}\" needs a url", config.transport(), name));
+            }
+            if config.command.is_some() || !config.args.is_empty() {
+                return Err(format!("{} server \"{}\" can't have a command or args", config.transport(), name));
+            }
+        }
+        other => return Err(format!("Unknown MCP transport \"{}\"; expected stdio, sse or http", other)),
+    }
+    Ok(())
+}
+
+/// Every MCP server visible to a project (or only managed and user servers
+/// when no project is given), with the scope each one comes from. Unreadable
+/// files and malformed entries are reported in `errors` and skipped.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_mcp_servers(project: Option<String>) -> McpServerListing {
+    let mut scopes = vec![McpScope::Managed, McpScope::User];
+    if project.is_some() {
+        scopes.push(McpScope::Project);
+        scopes.push(McpScope::Local);
+    }
+    let locations: Vec<(McpScope, PathBuf, String)> = scopes
+        .into_iter()
+        .filter_map(|scope| scope_location(scope, project.as_deref()).ok().map(|(path, pointer)| (scope, path, pointer)))
+        .collect();
+    servers_at(&locations)
+}
+
+/// Servers under each `(scope, file, pointer)` location, with `overridden_by`
+/// worked out across them.
+fn servers_at(locations: &[(McpScope, PathBuf, String)]) -> McpServerListing {
+    let mut entries: Vec<McpServerEntry> = Vec::new();
+    let mut errors = Vec::new();
+    for (scope, path, pointer) in locations {
+        let scope = *scope;
+        if !path.is_file() {
+            continue;
+        }
+        let source_path = path.to_string_lossy().into_owned();
+        let error = |name: Option<&String>, message: String| McpListingError {
+            scope,
+            source_path: source_path.clone(),
+            name: name.cloned(),
+            message,
+        };
+        let root = match read_json_object(path) {
+            Ok(root) => root,
+            Err(message) => {
+                errors.push(error(None, message));
+                continue;
+            }
+        };
+        let Some(servers) = root.pointer(pointer).and_then(|v| v.as_object()) else { continue };
+        for (name, value) in servers {
+            let config: McpServerConfig = match serde_json::from_value(value.clone()) {
+                Ok(config) => config,
+                Err(e) => {
+                    errors.push(error(Some(name), format!("Invalid config for \"{}\": {}", name, e)));
+                    continue;
+                }
+            };
+            entries.push(McpServerEntry {
+                name: name.clone(),
+                scope,
+                source_path: source_path.clone(),
+                transport: config.transport().to_string(),
+                config,
+                overridden_by: None,
+            });
+        }
+    }
+
+    // Managed servers can't be overridden; otherwise local > project > user
+    let precedence = |scope: McpScope| match scope {
+        McpScope::Managed => 3,
+        McpScope::Local => 2,
+        McpScope::Project => 1,
+        McpScope::User => 0,
+    };
+    let winners: Vec<(String, McpScope)> = entries.iter().map(|e| (e.name.clone(), e.scope)).collect();
+    for entry in entries.iter_mut() {
+        entry.overridden_by = winners
+            .iter()
+            .filter(|(name, scope)| *name == entry.name && precedence(*scope) > precedence(entry.scope))
+            .max_by_key(|(_, scope)| precedence(*scope))
+            .map(|(_, scope)| *scope);
+    }
+    McpServerListing { servers: entries, errors }
+}
+
+/// Add or replace a server in one scope, preserving everything else in the file.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn upsert_mcp_server(
+    scope: McpScope,
+    name: String,
+    config: McpServerConfig,
+    project_path: Option<String>,
+) -> Result<(), String> {
+    if scope == McpScope::Managed {
+        return Err("Managed MCP servers are read-only".into());
+    }
+    validate_server_config(&name, &config)?;
+    let (path, pointer) = scope_location(scope, project_path.as_deref())?;
+    let path = crate::sandbox::check_path(&path.to_string_lossy())?;
+    upsert_server_at(&path, &pointer, &name, &config)
+}
+
+fn upsert_server_at(path: &Path, pointer: &str, name: &str, config: &McpServerConfig) -> Result<(), String> {
+    let value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    update_json_file(path, |root| {
+        object_at(root, pointer)?.insert(name.to_string(), value.clone());
+        Ok(())
+    })
+}
+
+/// Remove a server from one scope, preserving everything else in the file.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn remove_mcp_server(scope: McpScope, name: String, project_path: Option<String>) -> Result<(), String> {
+    if scope == McpScope::Managed {
+        return Err("Managed MCP servers are read-only".into());
+    }
+    let (path, pointer) = scope_location(scope, project_path.as_deref())?;
+    let path = crate::sandbox::check_path(&path.to_string_lossy())?;
+    remove_server_at(&path, &pointer, &name)
+}
+
+fn remove_server_at(path: &Path, pointer: &str, name: &str) -> Result<(), String> {
+    let exists = read_json_object(path)?
+        .pointer(pointer)
+        .and_then(|s| s.get(name))
+        .is_some();
+    if !exists {
+        return Err(format!("No MCP server named \"{}\" in {}", name, path.display()));
+    }
+
+    update_json_file(path, |root| {
+        object_at(root, pointer)?.shift_remove(name);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A stdio server written in sh: `script` runs with the client's
+    /// messages on stdin.
+    fn sh_server(script: &str) -> McpServerConfig {
+        serde_json::from_value(json!({ "command": "sh", "args": ["-c", script] })).unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn probes_a_stdio_server() {
+        let server = sh_server(
+            r#"echo starting >&2
+            echo 'not json'
+            while IFS= read -r line; do
+              case "$line" in
+                *'"initialize"'*) echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"1.0"}}}' ;;
+                *'"tools/list"'*) echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","description":"Echo the input","inputSchema":{"type":"object"}}]}}' ;;
+              esac
+            done"#,
+        );
+        let result = probe_stdio_server(&server, Duration::from_secs(10));
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(result.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(result.server_info.unwrap()["name"], "fake");
+        assert_eq!(result.tools.len(), 1);
+        assert_eq!(result.tools[0].name, "echo");
+        assert_eq!(result.tools[0].description.as_deref(), Some("Echo the input"));
+        assert!(result.stderr.contains("starting"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn times_out_on_a_silent_server() {
+        let result = probe_stdio_server(&sh_server("while read -r line; do :; done"), Duration::from_millis(300));
+        assert!(!result.ok);
+        assert!(result.error.unwrap().contains("Timed out"));
+        assert!(result.duration_ms < 5_000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_a_server_that_exits() {
+        let result = probe_stdio_server(&sh_server("echo 'bad token' >&2; exit 3"), Duration::from_secs(10));
+        assert!(result.error.unwrap().contains("exited"));
+        assert!(result.stderr.contains("bad token"));
+    }
+
+    fn write_json(path: &Path, value: Value) {
+        fs::write(path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+    }
+
+    fn keys(value: &Value) -> Vec<&str> {
+        value.as_object().unwrap().keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn upsert_and_remove_keep_unrelated_keys_in_order() {
+        let temp = TempDir::new().unwrap();
+        crate::storage::set_test_data_dir(&temp.path().join("data"));
+        let path = temp.path().join(".claude.json");
+        write_json(&path, json!({
+            "numStartups": 42,
+            "projects": {
+                "/work/app": {"allowedTools": [], "mcpServers": {"old": {"command": "old"}}, "history": ["x"]},
+                "/work/other": {"mcpServers": {"keep": {"command": "keep"}}},
+            },
+            "mcpServers": {"zeta": {"command": "z"}, "alpha": {"command": "a"}},
+            "theme": "dark",
+        }));
+        let server: McpServerConfig = serde_json::from_value(json!({"command": "npx", "args": ["-y", "srv"]})).unwrap();
+
+        upsert_server_at(&path, "/mcpServers", "middle", &server).unwrap();
+        upsert_server_at(&path, &format!("/projects/{}/mcpServers", pointer_segment("/work/app")), "new", &server).unwrap();
+        remove_server_at(&path, "/projects/~1work~1app/mcpServers", "old").unwrap();
+
+        let root = read_json_object(&path).unwrap();
+        assert_eq!(keys(&root), ["numStartups", "projects", "mcpServers", "theme"]);
+        assert_eq!(keys(&root["mcpServers"]), ["zeta", "alpha", "middle"]);
+        assert_eq!(root["mcpServers"]["middle"]["args"], json!(["-y", "srv"]));
+        assert_eq!(keys(&root["projects"]["/work/app"]), ["allowedTools", "mcpServers", "history"]);
+        assert_eq!(keys(&root["projects"]["/work/app"]["mcpServers"]), ["new"]);
+        assert_eq!(root["projects"]["/work/other"], json!({"mcpServers": {"keep": {"command": "keep"}}}));
+        assert_eq!(root["numStartups"], 42);
+
+        let missing = remove_server_at(&path, "/mcpServers", "old").unwrap_err();
+        assert!(missing.contains("No MCP server named \"old\""));
+    }
+
+    #[test]
+    fn upsert_creates_the_file_and_parents() {
+        let temp = TempDir::new().unwrap();
+        crate::storage::set_test_data_dir(&temp.path().join("data"));
+        let path = temp.path().join(".mcp.json");
+        let server: McpServerConfig = serde_json::from_value(json!({"type": "http", "url": "https://example.com/mcp"})).unwrap();
+        upsert_server_at(&path, "/mcpServers", "remote", &server).unwrap();
+        assert_eq!(read_json_object(&path).unwrap(), json!({"mcpServers": {"remote": {"type": "http", "url": "https://example.com/mcp"}}}));
+    }
+
+    #[test]
+    fn listing_follows_scope_precedence() {
+        let temp = TempDir::new().unwrap();
+        let claude_json = temp.path().join(".claude.json");
+        let mcp_json = temp.path().join(".mcp.json");
+        let managed_json = temp.path().join("managed-mcp.json");
+        write_json(&claude_json, json!({
+            "mcpServers": {"shared": {"command": "user"}, "user-only": {"command": "u"}, "pinned": {"command": "u"}},
+            "projects": {"/work/app": {"mcpServers": {"shared": {"command": "local"}, "broken": {"args": "not a list"}}}},
+        }));
+        write_json(&mcp_json, json!({"mcpServers": {"shared": {"command": "project"}, "team": {"type": "sse", "url": "https://x"}}}));
+        write_json(&managed_json, json!({"mcpServers": {"pinned": {"command": "managed"}}}));
+
+        let listing = servers_at(&[
+            (McpScope::Managed, managed_json, "/mcpServers".into()),
+            (McpScope::User, claude_json.clone(), "/mcpServers".into()),
+            (McpScope::Project, mcp_json, "/mcpServers".into()),
+            (McpScope::Local, claude_json, "/projects/~1work~1app/mcpServers".into()),
+            (McpScope::Project, temp.path().join("missing.json"), "/mcpServers".into()),
+        ]);
+        let overridden = |name: &str, scope: McpScope| {
+            listing.servers.iter().find(|s| s.name == name && s.scope == scope).map(|s| s.overridden_by).unwrap()
+        };
+        assert_eq!(overridden("shared", McpScope::User), Some(McpScope::Local));
+        assert_eq!(overridden("shared", McpScope::Project), Some(McpScope::Local));
+        assert_eq!(overridden("shared", McpScope::Local), None);
+        assert_eq!(overridden("pinned", McpScope::User), Some(McpScope::Managed));
+        assert_eq!(overridden("pinned", McpScope::Managed), None);
+        assert_eq!(overridden("user-only", McpScope::User), None);
+        assert_eq!(listing.servers.iter().find(|s| s.name == "team").unwrap().transport, "sse");
+
+        assert_eq!(listing.errors.len(), 1);
+        assert_eq!(listing.errors[0].scope, McpScope::Local);
+        assert_eq!(listing.errors[0].name.as_deref(), Some("broken"));
+    }
+}
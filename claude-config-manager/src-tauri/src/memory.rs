@@ -0,0 +1,454 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::budget::estimate_tokens;
+
+/// Claude Code follows `@` imports at most this many hops from the file
+/// that is loaded.
+pub const MAX_IMPORT_DEPTH: usize = 5;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Resolved,
+    Missing,            // Target doesn't exist or isn't a readable file
+    Cycle,              // Target is already being expanded further up the chain
+    AlreadyIncluded,    // Target was expanded earlier; Claude Code loads a file once
+    DepthExceeded,      // Beyond MAX_IMPORT_DEPTH; not followed
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportEdge {
+    pub from: String,
+    pub raw: String,            // As written, without the leading '@'
+    pub line: usize,            // 1-based line in `from`
+    pub target: String,         // Resolved absolute path
+    pub depth: usize,           // 1 for imports made by the root file
+    pub status: ImportStatus,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportedFile {
+    pub path: String,
+    pub depth: usize,           // 0 for the root file
+    pub size: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ImportGraph {
+    pub root: String,
+    pub files: Vec<ImportedFile>,       // Every file read, in expansion order
+    pub edges: Vec<ImportEdge>,
+    pub expanded: String,               // Root text with each import's content inserted
+}
+
+/// `@path` references in memory text, with their 1-based line numbers.
+/// Matches Claude Code: an import starts at the beginning of a line or after
+/// whitespace, and nothing inside code spans or fenced code blocks counts.
+pub fn parse_imports(text: &str) -> Vec<(usize, String)> {
+    let mut imports = Vec::new();
+    let mut fence: Option<String> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(trimmed[..3].to_string());
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut in_code_span = false;
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '`' => in_code_span = !in_code_span,
+                '@' if !in_code_span && (i == 0 || chars[i - 1].is_whitespace()) => {
+                    let mut raw = String::new();
+                    let mut j = i + 1;
+                    while j < chars.len() && !chars[j].is_whitespace() {
+                        // "\ " escapes a space inside the path
+                        if chars[j] == '\\' && chars.get(j + 1) == Some(&' ') {
+                            raw.push(' ');
+                            j += 2;
+                            continue;
+                        }
+                        raw.push(chars[j]);
+                        j += 1;
+                    }
+                    if !raw.is_empty() {
+                        imports.push((index + 1, raw));
+                    }
+                    i = j;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+    imports
+}
+
+/// Resolve an import relative to the directory of the file that contains it.
+pub fn resolve_import_path(raw: &str, base_dir: &Path) -> PathBuf {
+    if raw == "~" {
+        crate::home_dir()
+    } else if let Some(rest) = raw.strip_prefix("~/") {
+        crate::home_dir().join(rest)
+    } else if Path::new(raw).is_absolute() {
+        PathBuf::from(raw)
+    } else {
+        base_dir.join(raw)
+    }
+}
+
+/// A stable identity for cycle checks; falls back to the path itself for
+/// files that don't exist.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+struct Resolver {
+    files: Vec<ImportedFile>,
+    edges: Vec<ImportEdge>,
+    visited: HashSet<PathBuf>,
+}
+
+impl Resolver {
+    /// Expand `text` (the content of `path`), recursing into its imports.
+    /// `stack` holds the files currently being expanded, for cycle detection.
+    fn expand(&mut self, path: &Path, text: &str, depth: usize, stack: &mut Vec<PathBuf>) -> String {
+        let base_dir = path.parent().unwrap_or(Path::new("/"));
+        let imports = parse_imports(text);
+        let mut insertions: Vec<(usize, String)> = Vec::new();
+
+        for (line, raw) in imports {
+            let target = resolve_import_path(&raw, base_dir);
+            let key = canonical(&target);
+            let mut edge = ImportEdge {
+                from: path.to_string_lossy().into_owned(),
+                raw,
+                line,
+                target: target.to_string_lossy().into_owned(),
+                depth: depth + 1,
+                status: ImportStatus::Resolved,
+            };
+
+            let content = if depth + 1 > MAX_IMPORT_DEPTH {
+                edge.status = ImportStatus::DepthExceeded;
+                None
+            } else if stack.contains(&key) {
+                edge.status = ImportStatus::Cycle;
+                None
+            } else if self.visited.contains(&key) {
+                edge.status = ImportStatus::AlreadyIncluded;
+                None
+            } else {
+                match fs::read_to_string(&target).ok().filter(|_| target.is_file()) {
+                    Some(content) => Some(content),
+                    None => {
+                        edge.status = ImportStatus::Missing;
+                        None
+                    }
+                }
+            };
+            self.edges.push(edge);
+
+            if let Some(content) = content {
+                self.visited.insert(key.clone());
+                self.files.push(ImportedFile {
+                    path: target.to_string_lossy().into_owned(),
+                    depth: depth + 1,
+                    size: content.len() as u64,
+                });
+                stack.push(key);
+                let expanded = self.expand(&target, &content, depth + 1, stack);
+                stack.pop();
+                insertions.push((line, expanded));
+            }
+        }
+
+        if insertions.is_empty() {
+            return text.to_string();
+        }
+
+        // Imported content goes right after the line that references it, so
+        // prose like "see @docs/style.md" still reads naturally.
+        let mut out = String::with_capacity(text.len());
+        for (index, line) in text.lines().enumerate() {
+            out.push_str(line);
+            out.push('\n');
+            for (_, content) in insertions.iter().filter(|(l, _)| *l == index + 1) {
+                out.push_str(content.trim_end_matches('\n'));
+                out.push('\n');
+            }
+        }
+        if !text.ends_with('\n') {
+            out.pop();
+        }
+        out
+    }
+}
+
+/// Follow every import reachable from `path`.
+pub fn resolve_imports(path: &Path) -> Result<ImportGraph, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let root = canonical(path);
+
+    let mut resolver = Resolver {
+        files: vec![ImportedFile {
+            path: path.to_string_lossy().into_owned(),
+            depth: 0,
+            size: text.len() as u64,
+        }],
+        edges: Vec::new(),
+        visited: HashSet::from([root.clone()]),
+    };
+    let expanded = resolver.expand(path, &text, 0, &mut vec![root]);
+
+    Ok(ImportGraph {
+        root: path.to_string_lossy().into_owned(),
+        files: resolver.files,
+        edges: resolver.edges,
+        expanded,
+    })
+}
+
+/// The import graph of a CLAUDE.md (or any memory file) and the text Claude
+/// actually receives once imports are expanded.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn resolve_claude_md_imports(path: String) -> Result<ImportGraph, String> {
+    let path = crate::sandbox::check_path(&path)?;
+    resolve_imports(&path)
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MemorySource {
+    Enterprise,     // Enterprise CLAUDE.md
+    User,           // ~/.claude/CLAUDE.md
+    UserLocal,      // ~/.claude/CLAUDE.local.md
+    Ancestor,       // CLAUDE.md, .claude/CLAUDE.md or CLAUDE.local.md from root down to cwd
+    Rule,           // .claude/rules/*.md in cwd or an ancestor
+    Subdirectory,   // Below cwd; loaded only when Claude reads files there
+}
+
+#[derive(Serialize)]
+pub struct MemoryEntry {
+    pub source: MemorySource,
+    pub path: String,
+    pub directory: String,          // Directory the file applies to
+    pub on_demand: bool,            // Not part of the initial context
+    pub size: u64,                  // Bytes in the file itself
+    pub expanded_size: u64,         // Bytes after imports are expanded
+    pub estimated_tokens: usize,    // Of the expanded text
+    pub imports: Vec<ImportEdge>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MemoryStack {
+    pub cwd: String,
+    pub entries: Vec<MemoryEntry>,      // In the order Claude Code loads them
+    pub startup_tokens: usize,          // Loaded at session start
+    pub on_demand_tokens: usize,        // Subdirectory files, if all were pulled in
+}
+
+fn memory_entry(source: MemorySource, path: &Path, directory: &Path, on_demand: bool) -> MemoryEntry {
+    let mut entry = MemoryEntry {
+        source,
+        path: path.to_string_lossy().into_owned(),
+        directory: directory.to_string_lossy().into_owned(),
+        on_demand,
+        size: 0,
+        expanded_size: 0,
+        estimated_tokens: 0,
+        imports: Vec::new(),
+        error: None,
+    };
+    match resolve_imports(path) {
+        Ok(graph) => {
+            entry.size = graph.files.first().map(|f| f.size).unwrap_or_default();
+            entry.expanded_size = graph.expanded.len() as u64;
+            entry.estimated_tokens = estimate_tokens(&graph.expanded);
+            entry.imports = graph.edges;
+        }
+        Err(e) => entry.error = Some(e),
+    }
+    entry
+}
+
+fn sorted_rule_files(dir: &Path) -> Vec<PathBuf> {
+    let mut rules: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().and_then(|x| x.to_str()) == Some("md"))
+                .collect()
+        })
+        .unwrap_or_default();
+    rules.sort();
+    rules
+}
+
+/// (source, file, directory it applies to, on demand)
+type Candidate = (MemorySource, PathBuf, PathBuf, bool);
+
+/// The enterprise and user memory files, which apply to every session.
+fn global_candidates() -> Vec<Candidate> {
+    let paths = crate::get_config_paths();
+    let enterprise = PathBuf::from(&paths.enterprise.claude_md.path);
+    let user = PathBuf::from(&paths.user.claude_md.path);
+    let user_local = PathBuf::from(&paths.user.claude_local_md.path);
+    vec![
+        (MemorySource::Enterprise, enterprise.clone(), enterprise.parent().unwrap_or(Path::new("/")).to_path_buf(), false),
+        (MemorySource::User, user, crate::home_dir(), false),
+        (MemorySource::UserLocal, user_local, crate::home_dir(), false),
+    ]
+}
+
+/// Entries for the candidates that exist. The same file can show up twice,
+/// e.g. ~/.claude/CLAUDE.md when cwd is under the home directory.
+fn memory_entries(candidates: Vec<Candidate>) -> Vec<MemoryEntry> {
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|(_, file, _, _)| file.is_file() && seen.insert(canonical(file)))
+        .map(|(source, file, dir, on_demand)| memory_entry(source, &file, &dir, on_demand))
+        .collect()
+}
+
+/// The enterprise and user memory files alone, for when there is no project.
+pub fn global_memory() -> Vec<MemoryEntry> {
+    memory_entries(global_candidates())
+}
+
+/// Every memory file Claude Code would load for a session started in `cwd`.
+pub fn memory_stack(cwd: &Path) -> MemoryStack {
+    let mut candidates = global_candidates();
+
+    // Root first, so files closer to cwd come later and take precedence
+    let mut ancestors: Vec<&Path> = cwd.ancestors().collect();
+    ancestors.reverse();
+    for dir in ancestors {
+        for file in [dir.join("CLAUDE.md"), dir.join(".claude").join("CLAUDE.md"), dir.join("CLAUDE.local.md")] {
+            candidates.push((MemorySource::Ancestor, file, dir.to_path_buf(), false));
+        }
+        for rule in sorted_rule_files(&dir.join(".claude").join("rules")) {
+            candidates.push((MemorySource::Rule, rule, dir.to_path_buf(), false));
+        }
+    }
+
+    for subdir in crate::discover_subdirectory_claude_md(cwd.to_string_lossy().into_owned(), None) {
+        let file = PathBuf::from(&subdir.full_path);
+        let dir = file.parent().unwrap_or(cwd).to_path_buf();
+        candidates.push((MemorySource::Subdirectory, file, dir, true));
+    }
+
+    let entries = memory_entries(candidates);
+
+    let total = |on_demand: bool| entries.iter().filter(|e| e.on_demand == on_demand).map(|e| e.estimated_tokens).sum();
+    MemoryStack {
+        cwd: cwd.to_string_lossy().into_owned(),
+        startup_tokens: total(false),
+        on_demand_tokens: total(true),
+        entries,
+    }
+}
+
+/// The ordered memory stack for a working directory, with sizes and token
+/// estimates. A file path is treated as its containing directory.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn resolve_memory_for_cwd(path: String) -> Result<MemoryStack, String> {
+    let path = PathBuf::from(path);
+    let cwd = if path.is_file() { path.parent().map(Path::to_path_buf).unwrap_or(path) } else { path };
+    if !cwd.is_dir() {
+        return Err(format!("{} is not a directory", cwd.display()));
+    }
+    Ok(memory_stack(&cwd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A temporary directory holding the given `(name, content)` files,
+    /// removed when the guard is dropped.
+    fn fixture(files: &[(&str, &str)]) -> (TempDir, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        for (file, content) in files {
+            fs::write(temp.path().join(file), content).unwrap();
+        }
+        let dir = fs::canonicalize(temp.path()).unwrap();
+        (temp, dir)
+    }
+
+    fn statuses(graph: &ImportGraph) -> Vec<(String, ImportStatus)> {
+        graph.edges.iter().map(|e| (e.raw.clone(), e.status)).collect()
+    }
+
+    #[test]
+    fn ignores_code_and_mid_word_references() {
+        let text = "See @docs/a.md and `@not/this`\nmail me@example.com\n```\n@fenced.md\n```\n@my\\ notes.md";
+        assert_eq!(parse_imports(text), [(1, "docs/a.md".to_string()), (6, "my notes.md".to_string())]);
+    }
+
+    #[test]
+    fn stops_at_cycles() {
+        let (_temp, dir) = fixture(&[("CLAUDE.md", "root\n@a.md\n"), ("a.md", "a\n@b.md\n"), ("b.md", "b\n@a.md\n@CLAUDE.md\n")]);
+        let graph = resolve_imports(&dir.join("CLAUDE.md")).unwrap();
+        assert_eq!(
+            statuses(&graph),
+            [
+                ("a.md".to_string(), ImportStatus::Resolved),
+                ("b.md".to_string(), ImportStatus::Resolved),
+                ("a.md".to_string(), ImportStatus::Cycle),
+                ("CLAUDE.md".to_string(), ImportStatus::Cycle),
+            ]
+        );
+        assert_eq!(graph.files.len(), 3);
+        assert_eq!(graph.expanded, "root\n@a.md\na\n@b.md\nb\n@a.md\n@CLAUDE.md\n");
+    }
+
+    #[test]
+    fn includes_shared_files_once() {
+        let (_temp, dir) = fixture(&[("CLAUDE.md", "@a.md\n@b.md\n"), ("a.md", "@shared.md\n"), ("b.md", "@shared.md\n"), ("shared.md", "shared\n")]);
+        let graph = resolve_imports(&dir.join("CLAUDE.md")).unwrap();
+        let shared: Vec<ImportStatus> = graph.edges.iter().filter(|e| e.raw == "shared.md").map(|e| e.status).collect();
+        assert_eq!(shared, [ImportStatus::Resolved, ImportStatus::AlreadyIncluded]);
+        assert_eq!(graph.expanded.matches("shared\n").count(), 1);
+    }
+
+    #[test]
+    fn stops_past_the_depth_limit() {
+        // CLAUDE.md -> 1.md -> 2.md -> ...; 6.md would be the sixth hop
+        let mut files: Vec<(String, String)> = (1..=7).map(|n| (format!("{}.md", n), format!("level {}\n@{}.md\n", n, n + 1))).collect();
+        files.push(("CLAUDE.md".into(), "@1.md\n".into()));
+        let files: Vec<(&str, &str)> = files.iter().map(|(n, c)| (n.as_str(), c.as_str())).collect();
+        let (_temp, dir) = fixture(&files);
+        let graph = resolve_imports(&dir.join("CLAUDE.md")).unwrap();
+
+        assert_eq!(graph.files.iter().map(|f| f.depth).max(), Some(MAX_IMPORT_DEPTH));
+        let last = graph.edges.last().unwrap();
+        assert_eq!((last.raw.as_str(), last.depth, last.status), ("6.md", MAX_IMPORT_DEPTH + 1, ImportStatus::DepthExceeded));
+        assert!(graph.expanded.contains("level 5") && !graph.expanded.contains("level 6"));
+    }
+
+    #[test]
+    fn reports_missing_targets() {
+        let (_temp, dir) = fixture(&[("CLAUDE.md", "@gone.md\n@.\n")]);
+        let graph = resolve_imports(&dir.join("CLAUDE.md")).unwrap();
+        assert_eq!(statuses(&graph), [("gone.md".to_string(), ImportStatus::Missing), (".".to_string(), ImportStatus::Missing)]);
+        assert_eq!(graph.files.len(), 1);
+    }
+}
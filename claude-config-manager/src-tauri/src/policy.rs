@@ -0,0 +1,434 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::mcp::{McpScope, McpServerListing};
+use crate::permissions::{layer_rules, parse_permission_rule, rule_overlap, EvalContext, MatchedRule, PermissionDecision, PermissionMode, RuleOverlap};
+use crate::settings::{leaves, load_settings_layers, pointer_segment, LoadedLayer, SettingsScope};
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyIssueKind {
+    Overridden,     // A managed setting replaces this value
+    Blocked,        // A managed deny or ask rule stops this rule from taking effect
+    Ignored,        // Policy turns this off for anything that isn't managed
+    Disallowed,     // MCP server excluded by the managed allow/deny lists
+    Unchecked,      // MCP config that couldn't be read, so policy couldn't be checked
+}
+
+/// Where the offending setting lives. Managed files are the policy, so they
+/// never appear here.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditScope {
+    User,
+    UserLocal,
+    Project,
+    ProjectLocal,
+    Local,          // MCP servers in ~/.claude.json for one project
+}
+
+#[derive(Serialize)]
+pub struct PolicyIssue {
+    pub kind: PolicyIssueKind,
+    pub scope: AuditScope,
+    pub path: String,
+    pub pointer: String,                // JSON pointer of the setting, rule or server
+    pub value: Value,                   // What the user or project asked for
+    pub policy_pointer: String,         // Managed setting responsible
+    pub policy_value: Option<Value>,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct PolicyAudit {
+    pub managed_settings_path: String,
+    pub managed_settings_exists: bool,
+    pub managed_mcp_exists: bool,       // managed-mcp.json takes exclusive control of MCP servers
+    pub issues: Vec<PolicyIssue>,
+    pub overridden: usize,
+    pub blocked: usize,
+    pub ignored: usize,
+    pub disallowed: usize,
+    pub unchecked: usize,
+}
+
+fn audit_scope(scope: SettingsScope) -> Option<AuditScope> {
+    match scope {
+        SettingsScope::User => Some(AuditScope::User),
+        SettingsScope::UserLocal => Some(AuditScope::UserLocal),
+        SettingsScope::Project => Some(AuditScope::Project),
+        SettingsScope::ProjectLocal => Some(AuditScope::ProjectLocal),
+        SettingsScope::Managed => None,
+    }
+}
+
+fn mcp_audit_scope(scope: McpScope) -> Option<AuditScope> {
+    match scope {
+        McpScope::User => Some(AuditScope::User),
+        McpScope::Project => Some(AuditScope::Project),
+        McpScope::Local => Some(AuditScope::Local),
+        McpScope::Managed => None,
+    }
+}
+
+fn list_name(list: PermissionDecision) -> &'static str {
+    match list {
+        PermissionDecision::Allow => "allow",
+        PermissionDecision::Ask => "ask",
+        PermissionDecision::Deny => "deny",
+    }
+}
+
+/// Non-managed values that the managed layer replaces. Uses the same leaf
+/// model as the settings merge: objects merge per key, permission lists are
+/// combined (and handled by `check_rules`), anything else is replaced.
+fn check_overrides(managed: &Value, layers: &[LoadedLayer], issues: &mut Vec<PolicyIssue>) {
+    let mut policy = BTreeMap::new();
+    leaves(managed, "", &mut policy);
+    for layer in layers {
+        let (Some(scope), Some(value)) = (audit_scope(layer.layer.scope), &layer.value) else { continue };
+        let mut values = BTreeMap::new();
+        leaves(value, "", &mut values);
+        for (pointer, value) in values {
+            // The managed value may sit at this pointer or replace a whole ancestor
+            let governing = policy.iter().find(|(p, _)| pointer == **p || pointer.starts_with(&format!("{}/", p)));
+            let Some((policy_pointer, policy_value)) = governing else { continue };
+            if pointer == *policy_pointer && value == *policy_value {
+                continue;
+            }
+            issues.push(PolicyIssue {
+                kind: PolicyIssueKind::Overridden,
+                scope,
+                path: layer.layer.path.clone(),
+                message: format!("{} is {} here, but managed settings set it to {}", pointer, value, policy_value),
+                pointer,
+                value,
+                policy_pointer: policy_pointer.clone(),
+                policy_value: Some(policy_value.clone()),
+            });
+        }
+    }
+}
+
+fn rule_pointer(rule: &MatchedRule, layers: &[LoadedLayer]) -> String {
+    let list = list_name(rule.list);
+    let index = layers
+        .iter()
+        .find(|l| l.layer.path == rule.path)
+        .and_then(|l| l.value.as_ref()?.pointer(&format!("/permissions/{}", list))?.as_array()?.iter().position(|r| r.as_str() == Some(&rule.rule)));
+    match index {
+        Some(i) => format!("/permissions/{}/{}", list, i),
+        None => format!("/permissions/{}", list),
+    }
+}
+
+/// Non-managed allow and ask rules that a stricter managed rule covers, and
+/// every non-managed rule when policy allows managed rules only.
+fn check_rules(managed: &Value, layers: &[LoadedLayer], ctx: &EvalContext, issues: &mut Vec<PolicyIssue>) {
+    let rules = layer_rules(layers);
+    let (policy, own): (Vec<&MatchedRule>, Vec<&MatchedRule>) = rules.iter().partition(|r| r.scope == SettingsScope::Managed);
+    let managed_only = managed.get("allowManagedPermissionRulesOnly").and_then(Value::as_bool) == Some(true);
+
+    for rule in own {
+        let Some(scope) = audit_scope(rule.scope) else { continue };
+        let issue = |kind: PolicyIssueKind, policy_pointer: String, policy_value: Option<Value>, message: String| PolicyIssue {
+            kind,
+            scope,
+            path: rule.path.clone(),
+            pointer: rule_pointer(rule, layers),
+            value: Value::String(rule.rule.clone()),
+            policy_pointer,
+            policy_value,
+            message,
+        };
+        if managed_only {
+            issues.push(issue(
+                PolicyIssueKind::Ignored,
+                "/allowManagedPermissionRulesOnly".into(),
+                Some(Value::Bool(true)),
+                format!("{} rule {} is ignored; policy only allows managed permission rules", list_name(rule.list), rule.rule),
+            ));
+            continue;
+        }
+        if rule.list == PermissionDecision::Deny {
+            continue;
+        }
+        let Ok(parsed) = parse_permission_rule(&rule.rule) else { continue };
+        let stricter = policy.iter().filter(|p| p.list == PermissionDecision::Deny || (p.list == PermissionDecision::Ask && rule.list == PermissionDecision::Allow));
+        for managed_rule in stricter {
+            let Ok(managed_parsed) = parse_permission_rule(&managed_rule.rule) else { continue };
+            // `Bash(npm:*)` allowed here but `Bash(npm publish:*)` denied by policy: only part of the rule is blocked
+            let full = match rule_overlap(&managed_parsed, Some(Path::new(&managed_rule.path)), &parsed, Some(Path::new(&rule.path)), ctx) {
+                RuleOverlap::None => continue,
+                overlap => overlap == RuleOverlap::Full,
+            };
+            let effect = if managed_rule.list == PermissionDecision::Deny { "denies" } else { "asks for" };
+            let message = if full {
+                format!("{} rule {} has no effect: managed policy {} {}", list_name(rule.list), rule.rule, effect, managed_rule.rule)
+            } else {
+                format!("{} rule {} is narrowed: managed policy still {} {}", list_name(rule.list), rule.rule, effect, managed_rule.rule)
+            };
+            issues.push(issue(
+                PolicyIssueKind::Blocked,
+                rule_pointer(managed_rule, layers),
+                Some(Value::String(managed_rule.rule.clone())),
+                message,
+            ));
+        }
+    }
+}
+
+/// Policy switches that make parts of user and project settings inert.
+fn check_switches(managed: &Value, layers: &[LoadedLayer], issues: &mut Vec<PolicyIssue>) {
+    let bypass_disabled = managed.pointer("/permissions/disableBypassPermissionsMode").and_then(Value::as_str) == Some("disable");
+    let hooks_policy = [("/allowManagedHooksOnly", "policy only allows managed hooks"), ("/disableAllHooks", "policy disables all hooks")]
+        .into_iter()
+        .find(|(pointer, _)| managed.pointer(pointer).and_then(Value::as_bool) == Some(true));
+
+    for layer in layers {
+        let (Some(scope), Some(value)) = (audit_scope(layer.layer.scope), &layer.value) else { continue };
+        let mut push = |pointer: String, value: &Value, policy_pointer: &str, message: String| {
+            issues.push(PolicyIssue {
+                kind: PolicyIssueKind::Ignored,
+                scope,
+                path: layer.layer.path.clone(),
+                pointer,
+                value: value.clone(),
+                policy_pointer: policy_pointer.to_string(),
+                policy_value: managed.pointer(policy_pointer).cloned(),
+                message,
+            });
+        };
+        if bypass_disabled {
+            if let Some(mode) = value.pointer("/permissions/defaultMode").filter(|m| m.as_str() == Some("bypassPermissions")) {
+                push(
+                    "/permissions/defaultMode".into(),
+                    mode,
+                    "/permissions/disableBypassPermissionsMode",
+                    "bypassPermissions mode is disabled by policy".into(),
+                );
+            }
+        }
+        if let Some((policy_pointer, reason)) = hooks_policy {
+            for (event, matchers) in value.get("hooks").and_then(Value::as_object).into_iter().flatten() {
+                push(format!("/hooks/{}", pointer_segment(event)), matchers, policy_pointer, format!("{} hooks are ignored; {}", event, reason));
+            }
+        }
+    }
+}
+
+fn server_names(managed: &Value, key: &str) -> Option<Vec<String>> {
+    let list = managed.get(key)?.as_array()?;
+    Some(list.iter().filter_map(|s| s.get("serverName")?.as_str().map(str::to_string)).collect())
+}
+
+/// MCP servers outside the managed allowlist, on the denylist, or replaced
+/// by managed-mcp.json.
+fn check_mcp(managed: &Value, listing: McpServerListing, managed_mcp: bool, issues: &mut Vec<PolicyIssue>) {
+    let allowed = server_names(managed, "allowedMcpServers");
+    let denied = server_names(managed, "deniedMcpServers").unwrap_or_default();
+
+    for error in listing.errors {
+        let Some(scope) = mcp_audit_scope(error.scope) else { continue };
+        issues.push(PolicyIssue {
+            kind: PolicyIssueKind::Unchecked,
+            scope,
+            path: error.source_path,
+            pointer: error.name.map(|n| format!("/mcpServers/{}", pointer_segment(&n))).unwrap_or_default(),
+            value: Value::Null,
+            policy_pointer: String::new(),
+            policy_value: None,
+            message: error.message,
+        });
+    }
+    for server in listing.servers {
+        let Some(scope) = mcp_audit_scope(server.scope) else { continue };
+        let (kind, policy_pointer, message) = if managed_mcp {
+            (PolicyIssueKind::Ignored, "", format!("MCP server \"{}\" is ignored; managed-mcp.json controls which servers run", server.name))
+        } else if server.overridden_by == Some(McpScope::Managed) {
+            (PolicyIssueKind::Overridden, "", format!("MCP server \"{}\" is replaced by the managed server of the same name", server.name))
+        } else if denied.contains(&server.name) {
+            (PolicyIssueKind::Disallowed, "/deniedMcpServers", format!("MCP server \"{}\" is on the managed denylist", server.name))
+        } else if allowed.as_ref().is_some_and(|a| !a.contains(&server.name)) {
+            (PolicyIssueKind::Disallowed, "/allowedMcpServers", format!("MCP server \"{}\" is not on the managed allowlist", server.name))
+        } else {
+            continue;
+        };
+        let policy_value = if policy_pointer.is_empty() { None } else { managed.pointer(policy_pointer).cloned() };
+        issues.push(PolicyIssue {
+            kind,
+            scope,
+            pointer: format!("/mcpServers/{}", pointer_segment(&server.name)),
+            path: server.source_path,
+            value: serde_json::to_value(&server.config).unwrap_or_default(),
+            policy_pointer: policy_pointer.to_string(),
+            policy_value,
+            message,
+        });
+    }
+}
+
+pub fn audit(project: Option<&Path>) -> PolicyAudit {
+    let paths = crate::get_config_paths();
+    let layers = load_settings_layers(project);
+    let managed = layers
+        .iter()
+        .find(|l| l.layer.scope == SettingsScope::Managed)
+        .and_then(|l| l.value.clone())
+        .unwrap_or(Value::Object(Default::default()));
+    let managed_mcp = PathBuf::from(&paths.enterprise.managed_mcp.path).is_file();
+
+    let ctx = EvalContext {
+        cwd: project.map(Path::to_path_buf).unwrap_or_else(crate::home_dir),
+        home: crate::home_dir(),
+        mode: PermissionMode::Default,
+        additional_dirs: Vec::new(),
+    };
+    let listing = crate::mcp::list_mcp_servers(project.map(|p| p.to_string_lossy().into_owned()));
+
+    let mut issues = Vec::new();
+    check_overrides(&managed, &layers, &mut issues);
+    check_rules(&managed, &layers, &ctx, &mut issues);
+    check_switches(&managed, &layers, &mut issues);
+    check_mcp(&managed, listing, managed_mcp, &mut issues);
+
+    issues.sort_by(|a, b| (a.scope, &a.path, a.kind).cmp(&(b.scope, &b.path, b.kind)));
+    let count = |kind: PolicyIssueKind| issues.iter().filter(|i| i.kind == kind).count();
+    PolicyAudit {
+        managed_settings_path: paths.enterprise.managed_settings.path.clone(),
+        managed_settings_exists: paths.enterprise.managed_settings.exists,
+        managed_mcp_exists: managed_mcp,
+        overridden: count(PolicyIssueKind::Overridden),
+        blocked: count(PolicyIssueKind::Blocked),
+        ignored: count(PolicyIssueKind::Ignored),
+        disallowed: count(PolicyIssueKind::Disallowed),
+        unchecked: count(PolicyIssueKind::Unchecked),
+        issues,
+    }
+}
+
+/// Every user, project and local setting that managed policy overrides,
+/// blocks or ignores, with the managed setting responsible.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn audit_policy(project: Option<String>) -> Result<PolicyAudit, String> {
+    tauri::async_runtime::spawn_blocking(move || audit(project.as_deref().map(Path::new)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::McpServerEntry;
+    use crate::settings::SettingsLayer;
+    use serde_json::json;
+
+    fn layer(scope: SettingsScope, value: Value) -> LoadedLayer {
+        LoadedLayer {
+            layer: SettingsLayer { scope, path: format!("/work/{:?}.json", scope), exists: true, error: None },
+            value: Some(value),
+        }
+    }
+
+    fn rule_issues(managed: Value, own: Value) -> Vec<PolicyIssue> {
+        let layers = [layer(SettingsScope::Project, own), layer(SettingsScope::Managed, managed.clone())];
+        let ctx = EvalContext {
+            cwd: PathBuf::from("/work"),
+            home: PathBuf::from("/home/me"),
+            mode: PermissionMode::Default,
+            additional_dirs: Vec::new(),
+        };
+        let mut issues = Vec::new();
+        check_rules(&managed, &layers, &ctx, &mut issues);
+        issues
+    }
+
+    fn server(name: &str, scope: McpScope) -> McpServerEntry {
+        McpServerEntry {
+            name: name.into(),
+            scope,
+            source_path: format!("/{:?}.json", scope),
+            transport: "stdio".into(),
+            config: serde_json::from_value(json!({"command": "npx"})).unwrap(),
+            overridden_by: None,
+        }
+    }
+
+    fn mcp_issues(managed: Value, servers: Vec<McpServerEntry>, managed_mcp: bool) -> Vec<(String, PolicyIssueKind)> {
+        let mut issues = Vec::new();
+        check_mcp(&managed, McpServerListing { servers, errors: Vec::new() }, managed_mcp, &mut issues);
+        issues.into_iter().map(|i| (i.pointer, i.kind)).collect()
+    }
+
+    #[test]
+    fn managed_deny_blocks_a_user_allow() {
+        let issues = rule_issues(
+            json!({"permissions": {"deny": ["Bash(npm publish:*)", "WebFetch"]}}),
+            json!({"permissions": {"allow": ["WebFetch", "Bash(npm:*)", "Read"]}}),
+        );
+        assert_eq!(issues.len(), 2);
+        let full = issues.iter().find(|i| i.value == "WebFetch").unwrap();
+        assert_eq!(full.kind, PolicyIssueKind::Blocked);
+        assert_eq!(full.pointer, "/permissions/allow/0");
+        assert_eq!(full.policy_pointer, "/permissions/deny/1");
+        assert!(full.message.contains("has no effect"));
+        let narrowed = issues.iter().find(|i| i.value == "Bash(npm:*)").unwrap();
+        assert_eq!(narrowed.pointer, "/permissions/allow/1");
+        assert!(narrowed.message.contains("is narrowed"), "{}", narrowed.message);
+    }
+
+    #[test]
+    fn managed_only_rules_ignore_every_other_rule() {
+        let issues = rule_issues(
+            json!({"allowManagedPermissionRulesOnly": true, "permissions": {"deny": ["WebFetch"]}}),
+            json!({"permissions": {"allow": ["Read"], "deny": ["Bash(rm:*)"]}}),
+        );
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|i| i.kind == PolicyIssueKind::Ignored && i.policy_pointer == "/allowManagedPermissionRulesOnly"));
+    }
+
+    #[test]
+    fn disabled_bypass_mode_is_reported() {
+        let managed = json!({"permissions": {"disableBypassPermissionsMode": "disable"}});
+        let layers = [
+            layer(SettingsScope::User, json!({"permissions": {"defaultMode": "bypassPermissions"}})),
+            layer(SettingsScope::Project, json!({"permissions": {"defaultMode": "plan"}})),
+        ];
+        let mut issues = Vec::new();
+        check_switches(&managed, &layers, &mut issues);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].scope, AuditScope::User);
+        assert_eq!(issues[0].pointer, "/permissions/defaultMode");
+        assert_eq!(issues[0].policy_value, Some(json!("disable")));
+    }
+
+    #[test]
+    fn mcp_allow_and_deny_lists() {
+        let managed = json!({
+            "allowedMcpServers": [{"serverName": "github"}, {"serverName": "blocked"}],
+            "deniedMcpServers": [{"serverName": "blocked"}],
+        });
+        let servers = vec![server("github", McpScope::User), server("blocked", McpScope::Project), server("other", McpScope::Local)];
+        assert_eq!(
+            mcp_issues(managed.clone(), servers, false),
+            [("/mcpServers/blocked".to_string(), PolicyIssueKind::Disallowed), ("/mcpServers/other".to_string(), PolicyIssueKind::Disallowed)],
+        );
+        // No allowlist means anything not denied is allowed
+        let servers = vec![server("github", McpScope::User), server("other", McpScope::Local)];
+        assert!(mcp_issues(json!({"deniedMcpServers": []}), servers, false).is_empty());
+    }
+
+    #[test]
+    fn managed_mcp_file_ignores_other_servers() {
+        let shadowed = || McpServerEntry { overridden_by: Some(McpScope::Managed), ..server("shared", McpScope::User) };
+        let servers = vec![server("shared", McpScope::Managed), shadowed(), server("mine", McpScope::Project)];
+        assert_eq!(
+            mcp_issues(json!({}), servers, true),
+            [("/mcpServers/shared".to_string(), PolicyIssueKind::Ignored), ("/mcpServers/mine".to_string(), PolicyIssueKind::Ignored)],
+        );
+        assert_eq!(mcp_issues(json!({}), vec![shadowed()], false), [("/mcpServers/shared".to_string(), PolicyIssueKind::Overridden)]);
+    }
+}
@@ -0,0 +1,569 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::TextDiff;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "gui")]
+use tauri::{AppHandle, Emitter};
+
+use crate::storage::{app_data_dir, atomic_write, content_version, create_backup, unix_millis, write_with_backup};
+
+/// Event emitted when a profile is activated from the tray, so the UI can
+/// report the outcome.
+pub const PROFILE_ACTIVATED_EVENT: &str = "profile-activated";
+
+/// Prefix of tray menu item ids that activate a profile.
+pub const PROFILE_MENU_PREFIX: &str = "profile:";
+
+/// Logical name of the `mcpServers` section of ~/.claude.json inside a
+/// profile. Only that section is captured; the rest of the file is Claude
+/// Code's own state and must not be rolled back.
+const MCP_SERVERS_ENTRY: &str = "mcpServers.json";
+
+/// Snapshot of user-level config: logical path (e.g. "settings.json",
+/// "agents/reviewer.md") to file contents.
+type Snapshot = BTreeMap<String, Vec<u8>>;
+
+#[derive(Serialize, Deserialize)]
+struct ProfileManifest {
+    name: String,
+    created_at: u64,        // Unix seconds
+    updated_at: u64,
+}
+
+#[derive(Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub file_count: usize,
+    pub active: bool,
+    pub modified_since_activation: bool,    // Only meaningful for the active profile
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Serialize)]
+pub struct ProfileFileDiff {
+    pub path: String,                   // Logical path within the profile
+    pub change: FileChange,
+    pub diff: Option<String>,           // Unified diff; None for binary files
+}
+
+#[derive(Serialize, Clone)]
+pub struct ActivationResult {
+    pub name: String,
+    pub written: usize,
+    pub removed: usize,
+    pub previous_snapshot: String,      // Config as it was before activation
+}
+
+#[derive(Serialize, Clone)]
+struct ProfileActivatedPayload {
+    name: String,
+    result: Option<ActivationResult>,
+    error: Option<String>,
+}
+
+fn profiles_dir() -> PathBuf {
+    app_data_dir().join("profiles")
+}
+
+fn profile_dir(profiles: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = name.trim();
+    let allowed = |c: char| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.');
+    if name.is_empty() || name.len() > 64 || name.starts_with('.') || !name.chars().all(allowed) {
+        return Err(format!(
+            "Invalid profile name \"{}\"; use letters, numbers, spaces, '-', '_' and '.'",
+            name
+        ));
+    }
+    Ok(profiles.join(name))
+}
+
+/// Where the files a profile captures live.
+struct LiveConfig {
+    roots: Vec<(&'static str, PathBuf)>,    // Logical root to live location
+    claude_json: PathBuf,                   // Holds the captured `mcpServers`
+}
+
+impl LiveConfig {
+    fn current() -> Self {
+        let user = crate::get_config_paths().user;
+        LiveConfig {
+            roots: vec![
+                ("CLAUDE.md", PathBuf::from(user.claude_md.path)),
+                ("CLAUDE.local.md", PathBuf::from(user.claude_local_md.path)),
+                ("settings.json", PathBuf::from(user.settings.path)),
+                ("settings.local.json", PathBuf::from(user.settings_local.path)),
+                ("agents", PathBuf::from(user.agents.path)),
+                ("commands", PathBuf::from(user.commands.path)),
+                ("skills", PathBuf::from(user.skills.path)),
+            ],
+            claude_json: PathBuf::from(user.mcp.path),
+        }
+    }
+
+    fn path(&self, logical: &str) -> Option<PathBuf> {
+        let (root, rest) = logical.split_once('/').unwrap_or((logical, ""));
+        let (_, base) = self.roots.iter().find(|(name, _)| *name == root)?;
+        Some(if rest.is_empty() { base.clone() } else { base.join(rest) })
+    }
+
+    fn capture(&self) -> Result<Snapshot, String> {
+        let mut snapshot = Snapshot::new();
+        for (name, path) in &self.roots {
+            if path.is_dir() {
+                read_tree(path, name, &mut snapshot)?;
+            } else if path.is_file() {
+                let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                snapshot.insert(name.to_string(), bytes);
+            }
+        }
+
+        if self.claude_json.is_file() {
+            if let Some(servers) = crate::mcp::read_json_object(&self.claude_json)?.get("mcpServers") {
+                let text = serde_json::to_string_pretty(servers).map_err(|e| e.to_string())?;
+                snapshot.insert(MCP_SERVERS_ENTRY.to_string(), text.into_bytes());
+            }
+        }
+        Ok(snapshot)
+    }
+
+    fn remove(&self, logical: &str) -> Result<(), String> {
+        if logical == MCP_SERVERS_ENTRY {
+            return crate::mcp::update_json_file(&self.claude_json, |root| {
+                if let Some(map) = root.as_object_mut() {
+                    map.shift_remove("mcpServers");
+                }
+                Ok(())
+            });
+        }
+        let path = self.path(logical).ok_or_else(|| format!("Unknown profile entry {}", logical))?;
+        create_backup(&path)?;
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove {}: {}", path.display(), e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn write(&self, logical: &str, bytes: &[u8]) -> Result<(), String> {
+        if logical == MCP_SERVERS_ENTRY {
+            let servers: Value = serde_json::from_slice(bytes).map_err(|e| format!("Invalid {}: {}", MCP_SERVERS_ENTRY, e))?;
+            return crate::mcp::update_json_file(&self.claude_json, |root| {
+                root.as_object_mut()
+                    .ok_or("~/.claude.json must contain a JSON object")?
+                    .insert("mcpServers".into(), servers.clone());
+                Ok(())
+            });
+        }
+        let path = self.path(logical).ok_or_else(|| format!("Unknown profile entry {}", logical))?;
+        write_with_backup(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Make the live config match `target`, given that it currently matches
+    /// `current`. Returns (written, removed).
+    fn apply(&self, target: &Snapshot, current: &Snapshot) -> Result<(usize, usize), String> {
+        let mut written = 0;
+        let mut removed = 0;
+        for (logical, bytes) in target {
+            if current.get(logical) != Some(bytes) {
+                self.write(logical, bytes)?;
+                written += 1;
+            }
+        }
+        for logical in current.keys().filter(|k| !target.contains_key(*k)) {
+            self.remove(logical)?;
+            removed += 1;
+        }
+        Ok((written, removed))
+    }
+}
+
+/// Add every file under `dir` to `snapshot`, keyed `prefix/relative/path`.
+/// Our own temp files are skipped.
+fn read_tree(dir: &Path, prefix: &str, snapshot: &mut Snapshot) -> Result<(), String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Ok(()) };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') && name.ends_with(".tmp") {
+            continue;
+        }
+        let key = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        if path.is_dir() {
+            read_tree(&path, &key, snapshot)?;
+        } else {
+            let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            snapshot.insert(key, bytes);
+        }
+    }
+    Ok(())
+}
+
+fn read_profile(profiles: &Path, name: &str) -> Result<Snapshot, String> {
+    let dir = profile_dir(profiles, name)?;
+    if !dir.join("profile.json").is_file() {
+        return Err(format!("No profile named \"{}\"", name));
+    }
+    let mut snapshot = Snapshot::new();
+    read_tree(&dir.join("files"), "", &mut snapshot)?;
+    Ok(snapshot)
+}
+
+fn read_manifest(dir: &Path) -> Option<ProfileManifest> {
+    serde_json::from_str(&fs::read_to_string(dir.join("profile.json")).ok()?).ok()
+}
+
+/// Write a snapshot as a complete profile directory. The new copy is built
+/// next to the old one and swapped in, so a failed save leaves the old
+/// profile intact.
+fn write_profile(dir: &Path, name: &str, snapshot: &Snapshot) -> Result<(), String> {
+    let now = (unix_millis() / 1000) as u64;
+    let created_at = read_manifest(dir).map(|m| m.created_at).unwrap_or(now);
+    let parent = dir.parent().ok_or("Invalid profile directory")?;
+    let staging = parent.join(format!(".{}.{}.staging", name, unix_millis()));
+
+    let result = (|| -> Result<(), String> {
+        for (logical, bytes) in snapshot {
+            atomic_write(&staging.join("files").join(logical), bytes)?;
+        }
+        let manifest = ProfileManifest { name: name.to_string(), created_at, updated_at: now };
+        let text = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        atomic_write(&staging.join("profile.json"), text.as_bytes())?;
+
+        if dir.exists() {
+            fs::remove_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        fs::rename(&staging, dir).map_err(|e| e.to_string())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+fn active_profile() -> Option<String> {
+    fs::read_to_string(profiles_dir().join("active"))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Content hash of a whole snapshot, paths included.
+fn snapshot_version(snapshot: &Snapshot) -> String {
+    let mut bytes = Vec::new();
+    for (logical, contents) in snapshot {
+        bytes.extend_from_slice(logical.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(contents);
+    }
+    content_version(&bytes)
+}
+
+/// Record `name` as the active profile along with the hash of what it
+/// applied, so later checks don't have to re-read the profile.
+fn set_active(profiles: &Path, name: &str, snapshot: &Snapshot) -> Result<(), String> {
+    atomic_write(&profiles.join("active.version"), snapshot_version(snapshot).as_bytes())?;
+    atomic_write(&profiles.join("active"), name.trim().as_bytes())
+}
+
+/// Whether the live config differs from what the active profile applied.
+fn live_modified() -> bool {
+    let Ok(recorded) = fs::read_to_string(profiles_dir().join("active.version")) else { return true };
+    LiveConfig::current().capture().map(|live| snapshot_version(&live) != recorded.trim()).unwrap_or(true)
+}
+
+/// Switch `live` to a profile stored in `profiles`. If any write fails,
+/// everything already changed is put back; the pre-activation state is also
+/// kept on disk in case the rollback itself fails.
+fn activate_in(profiles: &Path, live: &LiveConfig, name: &str) -> Result<ActivationResult, String> {
+    let target = read_profile(profiles, name)?;
+    let current = live.capture()?;
+
+    let previous_dir = profiles.join(".previous");
+    write_profile(&previous_dir, ".previous", &current)
+        .map_err(|e| format!("Failed to save the current config before switching: {}", e))?;
+
+    match live.apply(&target, &current) {
+        Ok((written, removed)) => {
+            set_active(profiles, name, &target)?;
+            Ok(ActivationResult {
+                name: name.trim().to_string(),
+                written,
+                removed,
+                previous_snapshot: previous_dir.to_string_lossy().into_owned(),
+            })
+        }
+        Err(e) => {
+            let rollback = live.capture().and_then(|partial| live.apply(&current, &partial));
+            Err(match rollback {
+                Ok(_) => format!("Failed to activate \"{}\": {}. Your previous config was restored.", name, e),
+                Err(r) => format!(
+                    "Failed to activate \"{}\": {}. Rolling back also failed ({}); a copy of your previous config is in {}",
+                    name,
+                    e,
+                    r,
+                    previous_dir.display()
+                ),
+            })
+        }
+    }
+}
+
+/// Switch the user-level config to a profile.
+pub fn activate(name: &str) -> Result<ActivationResult, String> {
+    activate_in(&profiles_dir(), &LiveConfig::current(), name)
+}
+
+/// Activate a profile picked from the tray menu and tell the UI how it went.
+#[cfg(feature = "gui")]
+pub fn activate_from_tray(app: &AppHandle, name: &str) {
+    let payload = match activate(name) {
+        Ok(result) => ProfileActivatedPayload { name: name.to_string(), result: Some(result), error: None },
+        Err(e) => ProfileActivatedPayload { name: name.to_string(), result: None, error: Some(e) },
+    };
+    let _ = app.emit(PROFILE_ACTIVATED_EVENT, payload);
+    crate::refresh_tray_menu(app);
+}
+
+/// Every saved profile. The live config is only read when `check_modified`
+/// is set, and then once for the active profile; the tray menu skips it.
+pub fn profile_infos(check_modified: bool) -> Vec<ProfileInfo> {
+    let active = active_profile();
+    let mut profiles: Vec<ProfileInfo> = fs::read_dir(profiles_dir())
+        .map(|entries| entries.flatten().map(|e| e.path()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|dir| !dir.file_name().unwrap_or_default().to_string_lossy().starts_with('.'))
+        .filter_map(|dir| {
+            let manifest = read_manifest(&dir)?;
+            let mut files = Snapshot::new();
+            let _ = read_tree(&dir.join("files"), "", &mut files);
+            let is_active = active.as_deref() == Some(manifest.name.as_str());
+            Some(ProfileInfo {
+                modified_since_activation: is_active && check_modified && live_modified(),
+                name: manifest.name,
+                created_at: manifest.created_at,
+                updated_at: manifest.updated_at,
+                file_count: files.len(),
+                active: is_active,
+            })
+        })
+        .collect();
+    profiles.sort_by_key(|p| p.name.to_lowercase());
+    profiles
+}
+
+/// Text of a snapshot entry (empty when absent), or None for binary files.
+fn as_text(bytes: Option<&Vec<u8>>) -> Option<&str> {
+    match bytes {
+        Some(bytes) => std::str::from_utf8(bytes).ok(),
+        None => Some(""),
+    }
+}
+
+fn diff_snapshots(left: &Snapshot, right: &Snapshot, left_label: &str, right_label: &str) -> Vec<ProfileFileDiff> {
+    let mut paths: Vec<&String> = left.keys().chain(right.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (a, b) = (left.get(path), right.get(path));
+            let change = match (a, b) {
+                (None, Some(_)) => FileChange::Added,
+                (Some(_), None) => FileChange::Removed,
+                (Some(a), Some(b)) if a != b => FileChange::Modified,
+                _ => return None,
+            };
+            let diff = match (as_text(a), as_text(b)) {
+                (Some(a), Some(b)) => Some(
+                    TextDiff::from_lines(a, b)
+                        .unified_diff()
+                        .header(&format!("{}/{}", left_label, path), &format!("{}/{}", right_label, path))
+                        .to_string(),
+                ),
+                _ => None,
+            };
+            Some(ProfileFileDiff { path: path.clone(), change, diff })
+        })
+        .collect()
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_profiles() -> Vec<ProfileInfo> {
+    profile_infos(true)
+}
+
+/// Snapshot the current user-level config into a profile, replacing any
+/// profile of the same name.
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn save_profile(app: AppHandle, name: String) -> Result<ProfileInfo, String> {
+    let dir = profile_dir(&profiles_dir(), &name)?;
+    let name = name.trim().to_string();
+    let live = LiveConfig::current().capture()?;
+    write_profile(&dir, &name, &live)?;
+    if active_profile().as_deref() == Some(name.as_str()) {
+        set_active(&profiles_dir(), &name, &live)?;
+    }
+    crate::refresh_tray_menu(&app);
+    profile_infos(true)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Profile \"{}\" was not saved", name))
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    let dir = profile_dir(&profiles_dir(), &name)?;
+    if !dir.is_dir() {
+        return Err(format!("No profile named \"{}\"", name));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    if active_profile().as_deref() == Some(name.trim()) {
+        let _ = fs::remove_file(profiles_dir().join("active"));
+        let _ = fs::remove_file(profiles_dir().join("active.version"));
+    }
+    crate::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Differences between two profiles, or between a profile and the current
+/// config when `right` is omitted. Paths are reported from `left` to `right`.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn diff_profiles(left: String, right: Option<String>) -> Result<Vec<ProfileFileDiff>, String> {
+    let profiles = profiles_dir();
+    let left_snapshot = read_profile(&profiles, &left)?;
+    let (right_snapshot, right_label) = match &right {
+        Some(name) => (read_profile(&profiles, name)?, name.trim().to_string()),
+        None => (LiveConfig::current().capture()?, "current".to_string()),
+    };
+    Ok(diff_snapshots(&left_snapshot, &right_snapshot, left.trim(), &right_label))
+}
+
+#[cfg(feature = "gui")]
+#[tauri::command]
+pub async fn activate_profile(app: AppHandle, name: String) -> Result<ActivationResult, String> {
+    let result = tauri::async_runtime::spawn_blocking(move || activate(&name))
+        .await
+        .map_err(|e| e.to_string())?;
+    crate::refresh_tray_menu(&app);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn snapshot(entries: &[(&str, &str)]) -> Snapshot {
+        entries.iter().map(|(k, v)| (k.to_string(), v.as_bytes().to_vec())).collect()
+    }
+
+    /// A temporary profile store and a live config with settings.json and
+    /// agents/. ~/.claude.json doesn't exist, so MCP servers stay out of it.
+    fn fixture() -> (TempDir, PathBuf, LiveConfig) {
+        let temp = TempDir::new().unwrap();
+        crate::storage::set_test_data_dir(&temp.path().join("data"));
+        let claude = temp.path().join("home/.claude");
+        let live = LiveConfig {
+            roots: vec![("settings.json", claude.join("settings.json")), ("agents", claude.join("agents"))],
+            claude_json: temp.path().join("home/.claude.json"),
+        };
+        let profiles = temp.path().join("profiles");
+        (temp, profiles, live)
+    }
+
+    fn put(live: &LiveConfig, logical: &str, text: &str) {
+        live.write(logical, text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn writes_and_reads_profiles() {
+        let (_temp, profiles, _) = fixture();
+        let files = snapshot(&[("settings.json", "{}"), ("agents/nested/a.md", "a")]);
+        let dir = profile_dir(&profiles, " work ").unwrap();
+        write_profile(&dir, "work", &files).unwrap();
+        assert_eq!(read_profile(&profiles, "work").unwrap(), files);
+        let created_at = read_manifest(&dir).unwrap().created_at;
+
+        // Saving again replaces the files but keeps the creation time
+        write_profile(&dir, "work", &snapshot(&[("settings.json", "{}")])).unwrap();
+        assert_eq!(read_profile(&profiles, "work").unwrap().len(), 1);
+        assert_eq!(read_manifest(&dir).unwrap().created_at, created_at);
+        assert!(profile_dir(&profiles, "../work").is_err());
+        assert!(read_profile(&profiles, "missing").is_err());
+    }
+
+    #[test]
+    fn diffs_snapshots() {
+        let left = snapshot(&[("settings.json", "{\"model\": \"a\"}\n"), ("agents/old.md", "old")]);
+        let mut right = snapshot(&[("settings.json", "{\"model\": \"b\"}\n"), ("agents/new.md", "new")]);
+        right.insert("bin".into(), vec![0xff, 0xfe]);
+        let diffs = diff_snapshots(&left, &right, "a", "b");
+        let changes: Vec<(&str, FileChange)> = diffs.iter().map(|d| (d.path.as_str(), d.change)).collect();
+        assert_eq!(
+            changes,
+            [
+                ("agents/new.md", FileChange::Added),
+                ("agents/old.md", FileChange::Removed),
+                ("bin", FileChange::Added),
+                ("settings.json", FileChange::Modified),
+            ]
+        );
+        let settings = diffs[3].diff.as_deref().unwrap();
+        assert!(settings.contains("--- a/settings.json") && settings.contains("+{\"model\": \"b\"}"));
+        assert!(diffs[2].diff.is_none());
+        assert!(diff_snapshots(&left, &left, "a", "a").is_empty());
+    }
+
+    #[test]
+    fn activates_a_profile() {
+        let (temp, profiles, live) = fixture();
+        put(&live, "settings.json", "current");
+        put(&live, "agents/only-here.md", "x");
+        let target = snapshot(&[("settings.json", "profile"), ("agents/a.md", "a")]);
+        write_profile(&profiles.join("work"), "work", &target).unwrap();
+
+        let result = activate_in(&profiles, &live, "work").unwrap();
+        assert_eq!((result.written, result.removed), (2, 1));
+        assert_eq!(live.capture().unwrap(), target);
+        assert_eq!(fs::read_to_string(profiles.join("active")).unwrap(), "work");
+        assert_eq!(fs::read_to_string(profiles.join("active.version")).unwrap(), snapshot_version(&target));
+        let mut previous = Snapshot::new();
+        read_tree(&PathBuf::from(result.previous_snapshot).join("files"), "", &mut previous).unwrap();
+        assert_eq!(previous, snapshot(&[("settings.json", "current"), ("agents/only-here.md", "x")]));
+        // The overwritten settings.json and the removed agent were backed up
+        assert_eq!(fs::read_dir(temp.path().join("data/backups")).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn rolls_back_a_failed_activation() {
+        let (_temp, profiles, live) = fixture();
+        put(&live, "settings.json", "current");
+        put(&live, "agents/keep.md", "keep");
+        // agents/a.md is written first, then the write under agents/blocked fails
+        let target = snapshot(&[("agents/a.md", "a"), ("agents/blocked/b.md", "b"), ("settings.json", "profile")]);
+        write_profile(&profiles.join("work"), "work", &target).unwrap();
+        put(&live, "agents/blocked", "a file where a directory is needed");
+        let before = live.capture().unwrap();
+
+        let err = activate_in(&profiles, &live, "work").err().expect("activation should fail");
+        assert!(err.contains("previous config was restored"), "{}", err);
+        assert_eq!(live.capture().unwrap(), before);
+        assert!(!profiles.join("active").exists());
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Every policy violation starts with this, so the frontend can tell them
+/// apart from ordinary I/O errors.
+pub const POLICY_ERROR_PREFIX: &str = "Path not allowed";
+
+/// Canonical project directories the backend has handed to the webview
+/// (from `list_projects` / `list_known_projects`). Only a project's Claude
+/// config is reachable through it, never the whole tree.
+static PROJECTS: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+enum Root {
+    Dir(PathBuf),       // Everything below
+    File(PathBuf),
+    Memory(PathBuf),    // CLAUDE*.md files anywhere in a project
+}
+
+pub fn policy_error(path: &Path, reason: &str) -> String {
+    format!("{}: {} {}", POLICY_ERROR_PREFIX, path.display(), reason)
+}
+
+/// Canonicalize `path`, resolving symlinks, even if its last components
+/// don't exist yet (a file about to be created). The missing part may not
+/// contain `.` or `..`.
+pub fn resolve(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err(policy_error(path, "is not an absolute path"));
+    }
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(canonical) => {
+                if missing.iter().any(|c| !matches!(c, Component::Normal(_))) {
+                    return Err(policy_error(path, "contains relative components"));
+                }
+                return Ok(missing.iter().rev().fold(canonical, |acc, c| acc.join(c)));
+            }
+            Err(_) => {
+                let Some(parent) = existing.parent() else { return Err(policy_error(path, "has no existing ancestor")) };
+                missing.push(existing.components().next_back().unwrap());
+                existing = parent;
+            }
+        }
+    }
+}
+
+/// Resolve the directory containing `path` but not `path` itself, so a
+/// symlink is judged (and removed) by where it lives rather than what it
+/// points to.
+fn resolve_entry(path: &Path) -> Result<PathBuf, String> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => Ok(resolve(parent)?.join(name)),
+        _ => Err(policy_error(path, "is not a file or directory inside a config root")),
+    }
+}
+
+fn is_memory_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with("CLAUDE") && name.ends_with(".md")
+}
+
+/// The roots for a set of config locations. Unresolvable entries are skipped.
+fn roots_for<'a>(
+    enterprise: Option<&Path>,
+    claude_dir: &Path,
+    claude_json: &Path,
+    projects: impl IntoIterator<Item = &'a PathBuf>,
+) -> Vec<Root> {
+    let mut roots = Vec::new();
+    let dirs = enterprise.into_iter().chain([claude_dir]);
+    roots.extend(dirs.filter_map(|dir| resolve(dir).ok()).map(Root::Dir));
+    roots.extend(resolve(claude_json).ok().map(Root::File));
+    for project in projects {
+        // A symlinked `.claude` or `.mcp.json` may only point inside the project
+        let inside = |path: PathBuf| resolve(&path).ok().filter(|r| r.starts_with(project) && r != project);
+        roots.extend(inside(project.join(".claude")).map(Root::Dir));
+        roots.extend(inside(project.join(".mcp.json")).map(Root::File));
+        roots.push(Root::Memory(project.clone()));
+    }
+    roots
+}
+
+fn allowed_roots() -> Vec<Root> {
+    let paths = crate::get_config_paths();
+    let enterprise = Path::new(&paths.enterprise.managed_settings.path).parent();
+    let projects = PROJECTS.lock().unwrap();
+    roots_for(enterprise, &crate::home_dir().join(".claude"), Path::new(&paths.user.mcp.path), projects.iter())
+}
+
+fn check_resolved(original: &Path, resolved: PathBuf, allow_root: bool, roots: &[Root]) -> Result<PathBuf, String> {
+    for root in roots {
+        match root {
+            Root::File(file) if *file == resolved => return Ok(resolved),
+            Root::Dir(dir) if resolved.starts_with(dir) => {
+                if resolved == *dir && !allow_root {
+                    return Err(policy_error(original, "is a config root and can't be removed"));
+                }
+                return Ok(resolved);
+            }
+            Root::Memory(project) if resolved.starts_with(project) && resolved != *project && is_memory_file(&resolved) => {
+                return Ok(resolved);
+            }
+            _ => {}
+        }
+    }
+    Err(policy_error(original, "is outside the enterprise, user and project config locations"))
+}
+
+/// Whether `project` may become a root. Home, filesystem roots and anything
+/// containing ~/.claude would expose far more than a project's config.
+fn registrable(project: &Path, home: &Path) -> bool {
+    let claude_dir = home.join(".claude");
+    project.parent().is_some() && project != home && !claude_dir.starts_with(project)
+}
+
+/// Make these project directories available to the file commands.
+pub fn register_projects<'a>(projects: impl IntoIterator<Item = &'a Path>) {
+    let home = fs::canonicalize(crate::home_dir()).unwrap_or_else(|_| crate::home_dir());
+    let mut registered = PROJECTS.lock().unwrap();
+    for project in projects {
+        if let Ok(canonical) = fs::canonicalize(project) {
+            if registrable(&canonical, &home) {
+                registered.insert(canonical);
+            }
+        }
+    }
+}
+
+/// The canonical form of `path` if reading or writing it is allowed.
+pub fn check_path(path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    check_resolved(path, resolve(path)?, true, &allowed_roots())
+}
+
+/// The canonical location of `path` if deleting it is allowed. Config roots
+/// themselves can't be deleted, and a symlink is checked (and deleted) as
+/// the link rather than its target.
+pub fn check_removable(path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path);
+    check_resolved(path, resolve_entry(path)?, false, &allowed_roots())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A temporary directory laid out like a home with ~/.claude,
+    /// ~/.claude.json and one project, plus the roots for it.
+    fn fixture() -> (TempDir, PathBuf, PathBuf, Vec<Root>) {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path();
+        let home = base.join("home");
+        let project = base.join("work/app");
+        fs::create_dir_all(home.join(".claude/agents")).unwrap();
+        fs::create_dir_all(project.join(".claude")).unwrap();
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(home.join(".claude.json"), "{}").unwrap();
+        let home = fs::canonicalize(home).unwrap();
+        let project = fs::canonicalize(project).unwrap();
+        let roots = roots_for(None, &home.join(".claude"), &home.join(".claude.json"), [&project]);
+        (temp, home, project, roots)
+    }
+
+    fn check(path: &Path, roots: &[Root]) -> Result<PathBuf, String> {
+        check_resolved(path, resolve(path)?, true, roots)
+    }
+
+    fn check_remove(path: &Path, roots: &[Root]) -> Result<PathBuf, String> {
+        check_resolved(path, resolve_entry(path)?, false, roots)
+    }
+
+    #[test]
+    fn allows_config_locations() {
+        let (_temp, home, project, roots) = fixture();
+        assert!(check(&home.join(".claude/agents/new.md"), &roots).is_ok());
+        assert!(check(&home.join(".claude.json"), &roots).is_ok());
+        assert!(check(&project.join(".claude/settings.json"), &roots).is_ok());
+        assert!(check(&project.join(".mcp.json"), &roots).is_ok());
+        assert!(check(&project.join("CLAUDE.md"), &roots).is_ok());
+        assert!(check(&project.join("src/CLAUDE.local.md"), &roots).is_ok());
+    }
+
+    #[test]
+    fn refuses_the_rest_of_a_project() {
+        let (_temp, home, project, roots) = fixture();
+        assert!(check(&project.join("src/main.rs"), &roots).is_err());
+        assert!(check(&project.join("package.json"), &roots).is_err());
+        assert!(check(&home.join(".bashrc"), &roots).is_err());
+        assert!(check(&home.join(".ssh/id_rsa"), &roots).is_err());
+    }
+
+    #[test]
+    fn refuses_traversal() {
+        let (_temp, home, project, roots) = fixture();
+        // Existing components are canonicalized away; missing ones may not climb
+        let err = check(&home.join(".claude/../.bashrc"), &roots).unwrap_err();
+        assert!(err.starts_with(POLICY_ERROR_PREFIX));
+        assert!(check(&project.join(".claude/missing/../../../x"), &roots).is_err());
+        assert!(check(Path::new("relative/.claude/settings.json"), &roots).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlink_escape() {
+        let (_temp, home, project, roots) = fixture();
+        fs::write(home.join("secret"), "x").unwrap();
+        std::os::unix::fs::symlink(home.join("secret"), project.join(".claude/link")).unwrap();
+        std::os::unix::fs::symlink(&home, project.join(".claude/home")).unwrap();
+        assert!(check(&project.join(".claude/link"), &roots).is_err());
+        assert!(check(&project.join(".claude/home/.bashrc"), &roots).is_err());
+        // Deleting the link itself is fine; it lives inside the root
+        assert_eq!(check_remove(&project.join(".claude/link"), &roots).unwrap(), project.join(".claude/link"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlinked_config_roots() {
+        let (_temp, home, _, _) = fixture();
+        let project = home.parent().unwrap().join("work/linked");
+        fs::create_dir_all(&project).unwrap();
+        std::os::unix::fs::symlink(&home, project.join(".claude")).unwrap();
+        std::os::unix::fs::symlink(home.join(".claude.json"), project.join(".mcp.json")).unwrap();
+        let roots = roots_for(None, &home.join(".claude"), &home.join(".claude.json"), [&project]);
+        assert!(check(&home.join(".bashrc"), &roots).is_err());
+        assert!(check(&project.join(".claude/.bashrc"), &roots).is_err());
+        assert!(check(&project.join(".claude/.claude/settings.json"), &roots).is_ok());
+        assert!(check(&project.join("CLAUDE.md"), &roots).is_ok());
+    }
+
+    #[test]
+    fn refuses_deleting_roots() {
+        let (_temp, home, project, roots) = fixture();
+        assert!(check_remove(&home.join(".claude"), &roots).is_err());
+        assert!(check_remove(&project.join(".claude"), &roots).is_err());
+        assert!(check_remove(&project, &roots).is_err());
+        assert!(check_remove(&home.join(".claude/agents"), &roots).is_ok());
+    }
+
+    #[test]
+    fn refuses_unregistered_projects() {
+        let (_temp, home, _, roots) = fixture();
+        let other = home.parent().unwrap().join("work/other");
+        fs::create_dir_all(other.join(".claude")).unwrap();
+        assert!(check(&other.join(".claude/settings.json"), &roots).is_err());
+        assert!(check(&other.join("CLAUDE.md"), &roots).is_err());
+    }
+
+    #[test]
+    fn never_registers_home_or_its_ancestors() {
+        let home = Path::new("/home/me");
+        assert!(!registrable(home, home));
+        assert!(!registrable(Path::new("/home"), home));
+        assert!(!registrable(Path::new("/"), home));
+        assert!(registrable(Path::new("/home/me/code/app"), home));
+    }
+}
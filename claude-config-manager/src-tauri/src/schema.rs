@@ -0,0 +1,527 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::diagnostics::{closest_match, has_errors, Diagnostic, JsonLocator, Severity};
+use crate::permissions::PermissionMode;
+use crate::settings::pointer_segment;
+
+// ---------------------------------------------------------------------------
+// Typed model of settings.json. Unknown keys are kept in `extra` so a file
+// round-trips without losing anything Claude Code adds in newer versions.
+// Reading is lenient: a known key with the wrong shape reads as unset rather
+// than failing the whole file, and `check_shape` reports it.
+// ---------------------------------------------------------------------------
+
+fn lenient<'de, D: Deserializer<'de>, T: DeserializeOwned + Default>(deserializer: D) -> Result<T, D::Error> {
+    Ok(T::deserialize(Value::deserialize(deserializer)?).unwrap_or_default())
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeSettings {
+    #[serde(rename = "$schema", default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub api_key_helper: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub aws_auth_refresh: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub aws_credential_export: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub otel_headers_helper: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub cleanup_period_days: Option<u64>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub company_announcements: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub include_co_authored_by: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Permissions>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<BTreeMap<String, Vec<HookMatcher>>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub disable_all_hooks: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub allow_managed_hooks_only: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub allow_managed_permission_rules_only: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub status_line: Option<StatusLine>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub output_style: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub force_login_method: Option<String>,
+    #[serde(rename = "forceLoginOrgUUID", default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub force_login_org_uuid: Option<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub enable_all_project_mcp_servers: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub enabled_mcpjson_servers: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub disabled_mcpjson_servers: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub allowed_mcp_servers: Option<Vec<McpServerRef>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub denied_mcp_servers: Option<Vec<McpServerRef>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub always_thinking_enabled: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub spinner_tips_enabled: Option<bool>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<Value>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub enabled_plugins: Option<Map<String, Value>>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub extra_known_marketplaces: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ClaudeSettings {
+    /// Read settings from parsed JSON. Anything that isn't an object reads
+    /// as empty settings.
+    pub fn from_value(value: &Value) -> Self {
+        Self::deserialize(value).unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Permissions {
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Vec::is_empty")]
+    pub ask: Vec<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Vec::is_empty")]
+    pub additional_directories: Vec<String>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub default_mode: Option<PermissionMode>,
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub disable_bypass_permissions_mode: Option<String>,    // Only "disable" has an effect
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Permissions {
+    /// Whether `defaultMode: bypassPermissions` is turned off.
+    pub fn bypass_disabled(&self) -> bool {
+        self.disable_bypass_permissions_mode.as_deref() == Some("disable")
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct HookMatcher {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    #[serde(default)]
+    pub hooks: Vec<HookCommand>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct HookCommand {
+    #[serde(rename = "type")]
+    pub kind: String,                   // "command" or "prompt"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,           // Seconds
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct StatusLine {
+    #[serde(rename = "type")]
+    pub kind: String,                   // Only "command" so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub padding: Option<i64>,
+}
+
+/// An entry of `allowedMcpServers` or `deniedMcpServers`.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerRef {
+    pub server_name: String,
+}
+
+// ---------------------------------------------------------------------------
+// Schema description used for diagnostics. Kept as data rather than derived
+// from the structs above so we can report every problem in one pass, with
+// the exact key that is wrong.
+// ---------------------------------------------------------------------------
+
+pub enum Shape {
+    Any,
+    Bool,
+    Str,
+    Int,
+    Enum(&'static [&'static str]),
+    Array(&'static Shape),
+    Map(&'static Shape),                                    // Free-form keys
+    KeyedMap(&'static [&'static str], &'static Shape),      // Known keys, same value shape
+    Object(&'static [Field]),
+}
+
+pub struct Field {
+    pub name: &'static str,
+    pub shape: Shape,
+}
+
+const fn field(name: &'static str, shape: Shape) -> Field {
+    Field { name, shape }
+}
+
+pub const PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions", "dontAsk"];
+
+pub const HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+const HOOK_COMMAND: Shape = Shape::Object(&[
+    field("type", Shape::Enum(&["command", "prompt"])),
+    field("command", Shape::Str),
+    field("prompt", Shape::Str),
+    field("timeout", Shape::Int),
+]);
+
+const HOOK_MATCHER: Shape = Shape::Object(&[
+    field("matcher", Shape::Str),
+    field("hooks", Shape::Array(&HOOK_COMMAND)),
+]);
+
+const MCP_SERVER_REF: Shape = Shape::Object(&[field("serverName", Shape::Str)]);
+
+const SANDBOX: Shape = Shape::Object(&[
+    field("enabled", Shape::Bool),
+    field("autoAllowBashIfSandboxed", Shape::Bool),
+    field("excludedCommands", Shape::Array(&Shape::Str)),
+    field("allowUnsandboxedCommands", Shape::Bool),
+    field("enableWeakerNestedSandbox", Shape::Bool),
+    field(
+        "network",
+        Shape::Object(&[
+            field("allowUnixSockets", Shape::Array(&Shape::Str)),
+            field("allowLocalBinding", Shape::Bool),
+            field("httpProxyPort", Shape::Int),
+            field("socksProxyPort", Shape::Int),
+        ]),
+    ),
+]);
+
+pub const SETTINGS_SCHEMA: Shape = Shape::Object(&[
+    field("$schema", Shape::Str),
+    field("apiKeyHelper", Shape::Str),
+    field("awsAuthRefresh", Shape::Str),
+    field("awsCredentialExport", Shape::Str),
+    field("otelHeadersHelper", Shape::Str),
+    field("cleanupPeriodDays", Shape::Int),
+    field("companyAnnouncements", Shape::Array(&Shape::Str)),
+    field("env", Shape::Map(&Shape::Str)),
+    field("includeCoAuthoredBy", Shape::Bool),
+    field(
+        "permissions",
+        Shape::Object(&[
+            field("allow", Shape::Array(&Shape::Str)),
+            field("ask", Shape::Array(&Shape::Str)),
+            field("deny", Shape::Array(&Shape::Str)),
+            field("additionalDirectories", Shape::Array(&Shape::Str)),
+            field("defaultMode", Shape::Enum(PERMISSION_MODES)),
+            field("disableBypassPermissionsMode", Shape::Enum(&["disable"])),
+        ]),
+    ),
+    field("hooks", Shape::KeyedMap(HOOK_EVENTS, &Shape::Array(&HOOK_MATCHER))),
+    field("disableAllHooks", Shape::Bool),
+    field("allowManagedHooksOnly", Shape::Bool),
+    field("allowManagedPermissionRulesOnly", Shape::Bool),
+    field("model", Shape::Str),
+    field(
+        "statusLine",
+        Shape::Object(&[
+            field("type", Shape::Enum(&["command"])),
+            field("command", Shape::Str),
+            field("padding", Shape::Int),
+        ]),
+    ),
+    field("outputStyle", Shape::Str),
+    field("forceLoginMethod", Shape::Enum(&["claudeai", "console"])),
+    field("forceLoginOrgUUID", Shape::Str),
+    field("enableAllProjectMcpServers", Shape::Bool),
+    field("enabledMcpjsonServers", Shape::Array(&Shape::Str)),
+    field("disabledMcpjsonServers", Shape::Array(&Shape::Str)),
+    field("allowedMcpServers", Shape::Array(&MCP_SERVER_REF)),
+    field("deniedMcpServers", Shape::Array(&MCP_SERVER_REF)),
+    field("alwaysThinkingEnabled", Shape::Bool),
+    field("spinnerTipsEnabled", Shape::Bool),
+    field("sandbox", SANDBOX),
+    field("enabledPlugins", Shape::Map(&Shape::Any)),
+    field("extraKnownMarketplaces", Shape::Map(&Shape::Any)),
+]);
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn unknown_key(pointer: &str, key: &str, known: &[&str], what: &str) -> Diagnostic {
+    let diagnostic = Diagnostic::new(
+        Severity::Warning,
+        pointer,
+        format!("Unknown {} \"{}\"; Claude Code will ignore it", what, key),
+    );
+    match closest_match(key, known) {
+        Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+        None => diagnostic,
+    }
+}
+
+/// Check `value` against `shape`, appending a diagnostic for every problem.
+pub fn check_shape(value: &Value, shape: &Shape, pointer: &str, out: &mut Vec<Diagnostic>) {
+    let mismatch = |expected: &str| {
+        Diagnostic::new(
+            Severity::Error,
+            pointer,
+            format!("Expected {}, found {}", expected, type_name(value)),
+        )
+    };
+
+    match shape {
+        Shape::Any => {}
+        Shape::Bool => {
+            if !value.is_boolean() {
+                out.push(mismatch("a boolean"));
+            }
+        }
+        Shape::Str => {
+            if !value.is_string() {
+                out.push(mismatch("a string"));
+            }
+        }
+        Shape::Int => {
+            if !(value.is_u64() || value.is_i64()) {
+                out.push(mismatch("an integer"));
+            }
+        }
+        Shape::Enum(options) => match value.as_str() {
+            Some(s) if options.contains(&s) => {}
+            Some(s) => {
+                let diagnostic = Diagnostic::new(
+                    Severity::Error,
+                    pointer,
+                    format!("Invalid value \"{}\"; expected one of: {}", s, options.join(", ")),
+                );
+                out.push(match closest_match(s, options) {
+                    Some(candidate) => diagnostic.with_suggestion(format!("Did you mean \"{}\"?", candidate)),
+                    None => diagnostic,
+                });
+            }
+            None => out.push(mismatch("a string")),
+        },
+        Shape::Array(item) => match value.as_array() {
+            Some(items) => {
+                for (i, v) in items.iter().enumerate() {
+                    check_shape(v, item, &format!("{}/{}", pointer, i), out);
+                }
+            }
+            None => out.push(mismatch("an array")),
+        },
+        Shape::Map(item) => match value.as_object() {
+            Some(map) => {
+                for (k, v) in map {
+                    check_shape(v, item, &format!("{}/{}", pointer, pointer_segment(k)), out);
+                }
+            }
+            None => out.push(mismatch("an object")),
+        },
+        Shape::KeyedMap(keys, item) => match value.as_object() {
+            Some(map) => {
+                for (k, v) in map {
+                    let child = format!("{}/{}", pointer, pointer_segment(k));
+                    if keys.contains(&k.as_str()) {
+                        check_shape(v, item, &child, out);
+                    } else {
+                        out.push(unknown_key(&child, k, keys, "key"));
+                    }
+                }
+            }
+            None => out.push(mismatch("an object")),
+        },
+        Shape::Object(fields) => match value.as_object() {
+            Some(map) => {
+                let names: Vec<&str> = fields.iter().map(|f| f.name).collect();
+                for (k, v) in map {
+                    let child = format!("{}/{}", pointer, pointer_segment(k));
+                    match fields.iter().find(|f| f.name == k) {
+                        Some(f) => check_shape(v, &f.shape, &child, out),
+                        None => out.push(unknown_key(&child, k, &names, "setting")),
+                    }
+                }
+            }
+            None => out.push(mismatch("an object")),
+        },
+    }
+}
+
+/// Checks that span more than one key, on the typed settings. Keys with the
+/// wrong shape read as unset, so these only see values `check_shape` accepts.
+fn semantic_diagnostics(settings: &ClaudeSettings, out: &mut Vec<Diagnostic>) {
+    if let Some(permissions) = &settings.permissions {
+        out.extend(crate::permissions::rule_diagnostics(permissions));
+        if permissions.bypass_disabled() && permissions.default_mode == Some(PermissionMode::BypassPermissions) {
+            out.push(
+                Diagnostic::new(Severity::Warning, "/permissions/defaultMode", "bypassPermissions is disabled in the same file")
+                    .with_suggestion("Pick another defaultMode or remove disableBypassPermissionsMode"),
+            );
+        }
+    }
+    if let Some(status_line) = &settings.status_line {
+        if status_line.command.as_deref().map(str::trim).unwrap_or_default().is_empty() {
+            out.push(Diagnostic::new(Severity::Error, "/statusLine", "A command status line needs a \"command\""));
+        }
+    }
+    if settings.api_key_helper.as_deref().is_some_and(|helper| helper.trim().is_empty()) {
+        out.push(Diagnostic::new(Severity::Error, "/apiKeyHelper", "apiKeyHelper is empty").with_suggestion("Remove it, or set the script to run"));
+    }
+}
+
+/// Validate settings.json text. Diagnostics carry line/column positions.
+pub fn validate_settings_text(text: &str) -> Vec<Diagnostic> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(e) => {
+            return vec![Diagnostic::new(Severity::Error, "", format!("Invalid JSON: {}", e))
+                .at(e.line().max(1), e.column().max(1))];
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    check_shape(&value, &SETTINGS_SCHEMA, "", &mut diagnostics);
+    semantic_diagnostics(&ClaudeSettings::from_value(&value), &mut diagnostics);
+    diagnostics.extend(crate::hooks::hook_diagnostics(&value));
+
+    JsonLocator::new(text).annotate(&mut diagnostics);
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+#[derive(Serialize)]
+pub struct SettingsValidation {
+    pub path: String,
+    pub valid: bool,                    // No error-severity diagnostics
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn validate_settings(path: String) -> Result<SettingsValidation, String> {
+    let text = fs::read_to_string(crate::sandbox::check_path(&path)?).map_err(|e| e.to_string())?;
+    let diagnostics = validate_settings_text(&text);
+    Ok(SettingsValidation {
+        path,
+        valid: !has_errors(&diagnostics),
+        diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(diagnostics: &'a [Diagnostic], pointer: &str) -> &'a Diagnostic {
+        diagnostics.iter().find(|d| d.pointer == pointer).unwrap_or_else(|| panic!("no diagnostic at {}", pointer))
+    }
+
+    #[test]
+    fn unknown_keys_suggest_the_closest_setting() {
+        let text = "{\n  \"modle\": \"opus\",\n  \"permissions\": {\"alow\": [\"Read\"]},\n  \"statusLine\": {\"type\": \"command\", \"command\": \"x\", \"paddding\": 1}\n}";
+        let diagnostics = validate_settings_text(text);
+        assert_eq!(diagnostics.len(), 3);
+
+        let model = find(&diagnostics, "/modle");
+        assert_eq!(model.severity, Severity::Warning);
+        assert_eq!(model.suggestion.as_deref(), Some("Did you mean \"model\"?"));
+        assert_eq!((model.line, model.column), (Some(2), Some(3)));
+        assert_eq!(find(&diagnostics, "/permissions/alow").suggestion.as_deref(), Some("Did you mean \"allow\"?"));
+        assert_eq!(find(&diagnostics, "/statusLine/paddding").suggestion.as_deref(), Some("Did you mean \"padding\"?"));
+        assert!(!has_errors(&diagnostics));
+    }
+
+    #[test]
+    fn wrong_shapes_and_values_are_errors() {
+        let text = r#"{"env": {"A": 1}, "permissions": {"defaultMode": "acceptEdit", "allow": ["Raed(./x)"]}, "hooks": {"PreTooluse": []}}"#;
+        let diagnostics = validate_settings_text(text);
+        assert!(find(&diagnostics, "/env/A").message.contains("Expected a string"));
+        let mode = find(&diagnostics, "/permissions/defaultMode");
+        assert_eq!(mode.severity, Severity::Error);
+        assert_eq!(mode.suggestion.as_deref(), Some("Did you mean \"acceptEdits\"?"));
+        assert_eq!(find(&diagnostics, "/permissions/allow/0").suggestion.as_deref(), Some("Did you mean \"Read\"?"));
+        assert_eq!(find(&diagnostics, "/hooks/PreTooluse").suggestion.as_deref(), Some("Did you mean \"PreToolUse\"?"));
+
+        let invalid = validate_settings_text("{\n  \"model\": \"opus\",\n}");
+        assert_eq!(invalid.len(), 1);
+        assert!(invalid[0].message.starts_with("Invalid JSON"));
+        assert_eq!(invalid[0].line, Some(3));
+    }
+
+    #[test]
+    fn typed_checks_span_keys() {
+        let text = r#"{"apiKeyHelper": " ", "statusLine": {"type": "command"},
+            "permissions": {"defaultMode": "bypassPermissions", "disableBypassPermissionsMode": "disable"}}"#;
+        let diagnostics = validate_settings_text(text);
+        assert_eq!(find(&diagnostics, "/apiKeyHelper").severity, Severity::Error);
+        assert_eq!(find(&diagnostics, "/statusLine").severity, Severity::Error);
+        assert_eq!(find(&diagnostics, "/permissions/defaultMode").severity, Severity::Warning);
+    }
+
+    #[test]
+    fn typed_settings_keep_unknown_keys_and_tolerate_bad_values() {
+        let value = serde_json::json!({
+            "model": "opus",
+            "env": {"A": "1"},
+            "permissions": {"allow": ["Read"], "deny": "Bash", "additionalDirectories": ["../lib"], "defaultMode": "plan", "future": 1},
+            "statusLine": {"type": "command", "command": "status.sh"},
+            "allowedMcpServers": [{"serverName": "github"}],
+            "cleanupPeriodDays": "soon",
+            "newSetting": {"x": true},
+        });
+        let settings = ClaudeSettings::from_value(&value);
+        assert_eq!(settings.model.as_deref(), Some("opus"));
+        assert_eq!(settings.env.as_ref().unwrap()["A"], "1");
+        let permissions = settings.permissions.as_ref().unwrap();
+        assert_eq!(permissions.allow, ["Read"]);
+        assert!(permissions.deny.is_empty());
+        assert_eq!(permissions.additional_directories, ["../lib"]);
+        assert_eq!(permissions.default_mode, Some(PermissionMode::Plan));
+        assert_eq!(settings.status_line.as_ref().unwrap().command.as_deref(), Some("status.sh"));
+        assert_eq!(settings.allowed_mcp_servers.as_ref().unwrap()[0].server_name, "github");
+        assert_eq!(settings.cleanup_period_days, None);
+
+        let round_trip = serde_json::to_value(&settings).unwrap();
+        assert_eq!(round_trip["newSetting"], value["newSetting"]);
+        assert_eq!(round_trip["permissions"]["future"], 1);
+        assert_eq!(ClaudeSettings::from_value(&serde_json::json!([1])).model, None);
+    }
+}
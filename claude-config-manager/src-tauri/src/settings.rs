@@ -0,0 +1,302 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{get_config_paths, project_config_files};
+
+/// A settings file in Claude Code's precedence chain, lowest priority first.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsScope {
+    User,           // ~/.claude/settings.json
+    UserLocal,      // ~/.claude/settings.local.json
+    Project,        // [ProjectRoot]/.claude/settings.json
+    ProjectLocal,   // [ProjectRoot]/.claude/settings.local.json
+    Managed,        // managed-settings.json (always wins)
+}
+
+#[derive(Serialize, Clone)]
+pub struct SettingsLayer {
+    pub scope: SettingsScope,
+    pub path: String,
+    pub exists: bool,
+    pub error: Option<String>,      // Read or parse failure; the layer is skipped
+}
+
+#[derive(Serialize, Clone)]
+pub struct SettingsSource {
+    pub scope: SettingsScope,
+    pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct EffectiveSettings {
+    pub settings: Value,
+    pub provenance: BTreeMap<String, SettingsSource>,   // JSON pointer -> file that supplied it
+    pub layers: Vec<SettingsLayer>,
+}
+
+/// A layer that has been read from disk. `value` is `None` when the file is
+/// missing or could not be parsed.
+pub struct LoadedLayer {
+    pub layer: SettingsLayer,
+    pub value: Option<Value>,
+}
+
+/// Paths of every settings layer, ordered from lowest to highest precedence.
+pub fn settings_layer_paths(project_path: Option<&Path>) -> Vec<(SettingsScope, PathBuf)> {
+    let paths = get_config_paths();
+    let mut layers = vec![
+        (SettingsScope::User, PathBuf::from(paths.user.settings.path)),
+        (SettingsScope::UserLocal, PathBuf::from(paths.user.settings_local.path)),
+    ];
+    if let Some(project) = project_path {
+        let files = project_config_files(project);
+        layers.push((SettingsScope::Project, PathBuf::from(files.settings.path)));
+        layers.push((SettingsScope::ProjectLocal, PathBuf::from(files.settings_local.path)));
+    }
+    layers.push((SettingsScope::Managed, PathBuf::from(paths.enterprise.managed_settings.path)));
+    layers
+}
+
+pub fn load_settings_layers(project_path: Option<&Path>) -> Vec<LoadedLayer> {
+    settings_layer_paths(project_path)
+        .into_iter()
+        .map(|(scope, path)| {
+            let exists = path.is_file();
+            let mut error = None;
+            let mut value = None;
+
+            if exists {
+                match fs::read_to_string(&path) {
+                    Ok(text) => match serde_json::from_str::<Value>(&text) {
+                        Ok(v) if v.is_object() => value = Some(v),
+                        Ok(_) => error = Some("Settings file must contain a JSON object".into()),
+                        Err(e) => error = Some(e.to_string()),
+                    },
+                    Err(e) => error = Some(e.to_string()),
+                }
+            }
+
+            LoadedLayer {
+                layer: SettingsLayer {
+                    scope,
+                    path: path.to_string_lossy().into_owned(),
+                    exists,
+                    error,
+                },
+                value,
+            }
+        })
+        .collect()
+}
+
+/// Escape a single key for use in a JSON pointer (RFC 6901).
+pub fn pointer_segment(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Arrays under these pointers are concatenated across layers rather than
+/// replaced, matching how Claude Code combines permission rules and the
+/// matcher lists of each hook event.
+pub fn is_merged_array(pointer: &str) -> bool {
+    pointer.starts_with("/permissions/") || pointer.strip_prefix("/hooks/").is_some_and(|event| !event.contains('/'))
+}
+
+/// Leaf values by JSON pointer. Permission lists and hook matchers are
+/// merged across layers rather than overridden, so they're skipped here.
+pub fn leaves(value: &Value, pointer: &str, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                leaves(child, &format!("{}/{}", pointer, pointer_segment(key)), out);
+            }
+        }
+        _ if value.is_array() && is_merged_array(pointer) => {}
+        _ => {
+            out.insert(pointer.to_string(), value.clone());
+        }
+    }
+}
+
+fn record_leaves(
+    value: &Value,
+    pointer: &str,
+    source: &SettingsSource,
+    provenance: &mut BTreeMap<String, SettingsSource>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                record_leaves(v, &format!("{}/{}", pointer, pointer_segment(k)), source, provenance);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, v) in items.iter().enumerate() {
+                record_leaves(v, &format!("{}/{}", pointer, i), source, provenance);
+            }
+        }
+        _ => {
+            provenance.insert(pointer.to_string(), source.clone());
+        }
+    }
+}
+
+fn clear_provenance(pointer: &str, provenance: &mut BTreeMap<String, SettingsSource>) {
+    let prefix = format!("{}/", pointer);
+    provenance.retain(|k, _| k != pointer && !k.starts_with(&prefix));
+}
+
+fn merge_value(
+    target: &mut Value,
+    source: &Value,
+    pointer: &str,
+    layer: &SettingsSource,
+    provenance: &mut BTreeMap<String, SettingsSource>,
+) {
+    match (target, source) {
+        (Value::Object(target_map), Value::Object(source_map)) => {
+            for (key, value) in source_map {
+                let child = format!("{}/{}", pointer, pointer_segment(key));
+                match target_map.get_mut(key) {
+                    Some(existing) => merge_value(existing, value, &child, layer, provenance),
+                    None => {
+                        clear_provenance(&child, provenance);
+                        record_leaves(value, &child, layer, provenance);
+                        target_map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(target_items), Value::Array(source_items)) if is_merged_array(pointer) => {
+            for item in source_items {
+                if !target_items.contains(item) {
+                    record_leaves(item, &format!("{}/{}", pointer, target_items.len()), layer, provenance);
+                    target_items.push(item.clone());
+                }
+            }
+        }
+        (target, source) => {
+            clear_provenance(pointer, provenance);
+            record_leaves(source, pointer, layer, provenance);
+            *target = source.clone();
+        }
+    }
+}
+
+/// Deep-merge loaded layers in precedence order. Objects merge key by key,
+/// permission and hook arrays are concatenated without duplicates and every other value
+/// is replaced by the higher-precedence layer, so managed settings always win.
+pub fn merge_layers(layers: &[LoadedLayer]) -> (Value, BTreeMap<String, SettingsSource>) {
+    let mut merged = Value::Object(Map::new());
+    let mut provenance = BTreeMap::new();
+
+    for loaded in layers {
+        if let Some(value) = &loaded.value {
+            let source = SettingsSource {
+                scope: loaded.layer.scope,
+                path: loaded.layer.path.clone(),
+            };
+            merge_value(&mut merged, value, "", &source, &mut provenance);
+        }
+    }
+
+    (merged, provenance)
+}
+
+/// Resolve the settings Claude Code would actually use for a project (or for
+/// the user alone when no project is given).
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn resolve_effective_settings(project_path: Option<String>) -> EffectiveSettings {
+    let project = project_path.map(PathBuf::from);
+    let layers = load_settings_layers(project.as_deref());
+    let (settings, provenance) = merge_layers(&layers);
+
+    EffectiveSettings {
+        settings,
+        provenance,
+        layers: layers.into_iter().map(|l| l.layer).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layer(scope: SettingsScope, value: Value) -> LoadedLayer {
+        LoadedLayer {
+            layer: SettingsLayer { scope, path: format!("/{:?}.json", scope), exists: true, error: None },
+            value: Some(value),
+        }
+    }
+
+    #[test]
+    fn higher_layers_replace_values() {
+        let (merged, provenance) = merge_layers(&[
+            layer(SettingsScope::User, json!({"model": "sonnet", "env": {"A": "1", "B": "1"}})),
+            layer(SettingsScope::Project, json!({"env": {"B": "2"}})),
+            layer(SettingsScope::Managed, json!({"model": "opus"})),
+        ]);
+        assert_eq!(merged, json!({"model": "opus", "env": {"A": "1", "B": "2"}}));
+        assert_eq!(provenance["/model"].scope, SettingsScope::Managed);
+        assert_eq!(provenance["/env/A"].scope, SettingsScope::User);
+        assert_eq!(provenance["/env/B"].scope, SettingsScope::Project);
+    }
+
+    #[test]
+    fn replacing_a_subtree_drops_its_provenance() {
+        let (merged, provenance) = merge_layers(&[
+            layer(SettingsScope::User, json!({"statusLine": {"type": "command", "command": "x"}})),
+            layer(SettingsScope::Project, json!({"statusLine": "off"})),
+        ]);
+        assert_eq!(merged["statusLine"], "off");
+        assert_eq!(provenance["/statusLine"].scope, SettingsScope::Project);
+        assert!(!provenance.contains_key("/statusLine/command"));
+    }
+
+    #[test]
+    fn permission_lists_are_concatenated() {
+        let (merged, provenance) = merge_layers(&[
+            layer(SettingsScope::User, json!({"permissions": {"allow": ["Read", "Bash(ls)"], "defaultMode": "plan"}})),
+            layer(SettingsScope::Project, json!({"permissions": {"allow": ["Bash(ls)", "Edit"], "defaultMode": "acceptEdits"}})),
+        ]);
+        assert_eq!(merged["permissions"]["allow"], json!(["Read", "Bash(ls)", "Edit"]));
+        assert_eq!(merged["permissions"]["defaultMode"], "acceptEdits");
+        assert_eq!(provenance["/permissions/allow/1"].scope, SettingsScope::User);
+        assert_eq!(provenance["/permissions/allow/2"].scope, SettingsScope::Project);
+    }
+
+    #[test]
+    fn hook_matchers_are_concatenated() {
+        let user = json!({"matcher": "Bash", "hooks": [{"type": "command", "command": "audit"}]});
+        let project = json!({"matcher": "Edit", "hooks": [{"type": "command", "command": "fmt"}]});
+        let (merged, provenance) = merge_layers(&[
+            layer(SettingsScope::User, json!({"hooks": {"PreToolUse": [user.clone()]}})),
+            layer(SettingsScope::Project, json!({"hooks": {"PreToolUse": [project.clone()], "Stop": []}})),
+        ]);
+        assert_eq!(merged["hooks"]["PreToolUse"], json!([user, project]));
+        assert_eq!(provenance["/hooks/PreToolUse/0/hooks/0/command"].scope, SettingsScope::User);
+        assert_eq!(provenance["/hooks/PreToolUse/1/matcher"].scope, SettingsScope::Project);
+        // Arrays inside a matcher still belong to that matcher
+        assert!(!is_merged_array("/hooks/PreToolUse/0/hooks"));
+    }
+
+    #[test]
+    fn leaves_skip_merged_arrays() {
+        let settings = json!({
+            "model": "opus",
+            "env": {"A": "1"},
+            "permissions": {"allow": ["Bash(ls)"], "defaultMode": "plan"},
+            "hooks": {"PreToolUse": [{"hooks": []}]},
+            "additionalDirectories": ["../shared"],
+        });
+        let mut out = BTreeMap::new();
+        leaves(&settings, "", &mut out);
+        let pointers: Vec<&str> = out.keys().map(String::as_str).collect();
+        assert_eq!(pointers, ["/additionalDirectories", "/env/A", "/model", "/permissions/defaultMode"]);
+        assert_eq!(out["/additionalDirectories"], json!(["../shared"]));
+    }
+}
@@ -0,0 +1,237 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Must match `identifier` in tauri.conf.json so the app and any tooling
+/// share the same data directory.
+const APP_IDENTIFIER: &str = "com.scott.claude-config-manager";
+
+/// Number of backups kept per file before the oldest are pruned.
+const MAX_BACKUPS_PER_FILE: usize = 20;
+
+/// Makes temp file names unique when the same file is saved concurrently.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub path: String,           // Location of the backup copy
+    pub created_at: u64,        // Unix seconds
+    pub size: u64,
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_DATA_DIR: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Point `app_data_dir` at `dir` for the rest of the calling test, so
+/// backups of test files don't land in the real data directory.
+#[cfg(test)]
+pub fn set_test_data_dir(dir: &Path) {
+    TEST_DATA_DIR.with(|d| *d.borrow_mut() = Some(dir.to_path_buf()));
+}
+
+/// Per-user data directory for the app (backups, trash, profiles, ...).
+pub fn app_data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_DATA_DIR.with(|d| d.borrow().clone()) {
+        return dir;
+    }
+    dirs::data_dir()
+        .unwrap_or_else(crate::home_dir)
+        .join(APP_IDENTIFIER)
+}
+
+pub fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+/// Write `contents` to `path` without ever leaving a truncated file behind:
+/// the data goes to a temp file in the same directory, is fsynced, and is then
+/// renamed over the original.
+pub fn atomic_write(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    fs::create_dir_all(&parent).map_err(|e| e.to_string())?;
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?
+        .to_string_lossy();
+    let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = parent.join(format!(".{}.{}.{}.tmp", file_name, std::process::id(), counter));
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .map_err(|e| format!("Failed to create {}: {}", tmp_path.display(), e))?;
+
+    let result = (|| -> std::io::Result<()> {
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        // Keep the original file's permissions (e.g. 0600 on ~/.claude.json)
+        if let Ok(meta) = fs::metadata(path) {
+            fs::set_permissions(&tmp_path, meta.permissions())?;
+        }
+
+        fs::rename(&tmp_path, path)?;
+
+        // Persist the rename itself; not supported on every platform
+        #[cfg(unix)]
+        if let Ok(dir) = fs::File::open(&parent) {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.map_err(|e| e.to_string())
+}
+
+/// Opaque version token for a file's contents, used to detect edits made by
+/// another process (typically Claude Code itself) between a read and a save.
+pub fn content_version(contents: &[u8]) -> String {
+    Sha256::digest(contents).iter().take(16).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Version of the file currently on disk, or `None` if it doesn't exist.
+pub fn file_version(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|bytes| content_version(&bytes))
+}
+
+/// Directory holding the backups of a single file, keyed by a hash of its
+/// canonical path so every spelling of the same file shares one set.
+fn backup_dir_for(path: &Path) -> PathBuf {
+    let canonical = crate::sandbox::resolve(path).unwrap_or_else(|_| path.to_path_buf());
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    let key: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    app_data_dir().join("backups").join(key)
+}
+
+fn backup_entries(dir: &Path) -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if path.extension().and_then(|e| e.to_str()) != Some("bak") {
+                        return None;
+                    }
+                    let id = path.file_stem()?.to_string_lossy().into_owned();
+                    let millis: u128 = id.parse().ok()?;
+                    Some(BackupInfo {
+                        id,
+                        created_at: (millis / 1000) as u64,
+                        size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                        path: path.to_string_lossy().into_owned(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Newest first; ids are millisecond timestamps
+    backups.sort_by_key(|b| std::cmp::Reverse(b.id.parse::<u128>().unwrap_or(0)));
+    backups
+}
+
+/// Copy the current contents of `path` into its backup set, pruning old
+/// copies. Does nothing if the file doesn't exist yet.
+pub fn create_backup(path: &Path) -> Result<Option<String>, String> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let current = fs::read(path).map_err(|e| e.to_string())?;
+    let dir = backup_dir_for(path);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let existing = backup_entries(&dir);
+    if let Some(latest) = existing.first() {
+        if fs::read(&latest.path).map(|b| b == current).unwrap_or(false) {
+            return Ok(Some(latest.id.clone()));
+        }
+    }
+
+    let mut millis = unix_millis();
+    while dir.join(format!("{}.bak", millis)).exists() {
+        millis += 1;
+    }
+    let id = millis.to_string();
+    atomic_write(&dir.join(format!("{}.bak", id)), &current)?;
+    // Record which file this set belongs to, for anyone browsing app data
+    let _ = fs::write(dir.join("source.txt"), path.to_string_lossy().as_bytes());
+
+    for old in backup_entries(&dir).into_iter().skip(MAX_BACKUPS_PER_FILE) {
+        let _ = fs::remove_file(old.path);
+    }
+    Ok(Some(id))
+}
+
+/// Back up the existing file (if any) and atomically replace it.
+pub fn write_with_backup(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Ok(current) = fs::read(path) {
+        if current == contents {
+            return Ok(());
+        }
+    }
+    create_backup(path)?;
+    atomic_write(path, contents)
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_backups(path: String) -> Result<Vec<BackupInfo>, String> {
+    Ok(backup_entries(&backup_dir_for(&crate::sandbox::check_path(&path)?)))
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn restore_backup(path: String, backup_id: String) -> Result<(), String> {
+    if backup_id.is_empty() || !backup_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid backup id: {}", backup_id));
+    }
+    let target = crate::sandbox::check_path(&path)?;
+    let backup_path = backup_dir_for(&target).join(format!("{}.bak", backup_id));
+    let contents = fs::read(&backup_path)
+        .map_err(|_| format!("Backup {} not found for {}", backup_id, path))?;
+
+    // The current version becomes a backup too, so a restore can be undone
+    write_with_backup(&target, &contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_writes_use_separate_temp_files() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("settings.json");
+        let writers: Vec<_> = (0..8)
+            .map(|n| {
+                let path = path.clone();
+                std::thread::spawn(move || atomic_write(&path, format!("{{\"n\": {}}}", n).as_bytes()))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("{\"n\": ") && text.ends_with('}'));
+        let names: Vec<_> = fs::read_dir(temp.path()).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(names, ["settings.json"]);
+    }
+}
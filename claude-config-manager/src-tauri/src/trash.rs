@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::storage::{app_data_dir, atomic_write, unix_millis};
+
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+const DAY_MILLIS: u128 = 24 * 60 * 60 * 1000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashScope {
+    Enterprise,
+    User,
+    Project,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashEntry {
+    pub id: String,                     // Millisecond timestamp of the deletion
+    pub original_path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub scope: TrashScope,
+    pub deleted_at: u64,                // Unix millis
+    #[serde(default)]
+    pub expires_at: Option<u64>,        // Unix millis; None when retention is disabled
+}
+
+#[derive(Serialize, Deserialize)]
+struct TrashSettings {
+    retention_days: u32,                // 0 keeps items until the trash is emptied
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        TrashSettings { retention_days: DEFAULT_RETENTION_DAYS }
+    }
+}
+
+fn trash_dir() -> PathBuf {
+    app_data_dir().join("trash")
+}
+
+fn settings_path() -> PathBuf {
+    trash_dir().join("settings.json")
+}
+
+fn read_settings() -> TrashSettings {
+    fs::read_to_string(settings_path())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Each entry is `trash/<id>/meta.json` plus the deleted file or directory
+/// at `trash/<id>/item`.
+fn entry_dir(trash: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid trash id \"{}\"", id));
+    }
+    Ok(trash.join(id))
+}
+
+fn scope_of(path: &Path) -> TrashScope {
+    let paths = crate::get_config_paths();
+    let in_dir = |dir: &Path| crate::sandbox::resolve(dir).map(|dir| path.starts_with(dir)).unwrap_or(false);
+    if Path::new(&paths.enterprise.managed_settings.path).parent().is_some_and(in_dir) {
+        TrashScope::Enterprise
+    } else if in_dir(&crate::home_dir().join(".claude")) || path == Path::new(&paths.user.mcp.path) {
+        TrashScope::User
+    } else {
+        TrashScope::Project
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.file_type().is_symlink() {
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(fs::read_link(from)?, to);
+        #[cfg(not(unix))]
+        return fs::copy(from, to).map(|_| ());
+    }
+    if meta.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+    fs::copy(from, to).map(|_| ())
+}
+
+fn remove_any(path: &Path) -> std::io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+enum MoveError {
+    NotMoved(String),       // `from` is untouched and nothing is left at `to`
+    SourceLeft(String),     // `to` is a complete copy but `from` was only partly removed
+}
+
+/// Rename, falling back to copy-and-delete when the app data directory is
+/// on a different filesystem. The copy is only discarded if copying failed.
+fn move_path(from: &Path, to: &Path) -> Result<(), MoveError> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = copy_recursive(from, to) {
+        let _ = remove_any(to);
+        return Err(MoveError::NotMoved(format!("Failed to move {}: {}", from.display(), e)));
+    }
+    remove_any(from).map_err(|e| MoveError::SourceLeft(format!("Failed to remove {}: {}", from.display(), e)))
+}
+
+fn read_entry(dir: &Path) -> Option<TrashEntry> {
+    serde_json::from_str(&fs::read_to_string(dir.join("meta.json")).ok()?).ok()
+}
+
+fn with_expiry(mut entry: TrashEntry, retention_days: u32) -> TrashEntry {
+    entry.expires_at = (retention_days > 0).then(|| (entry.deleted_at as u128 + retention_days as u128 * DAY_MILLIS) as u64);
+    entry
+}
+
+/// Move `path` into the trash at `trash`.
+fn trash_item(trash: &Path, path: &Path, scope: TrashScope) -> Result<TrashEntry, String> {
+    let meta = fs::symlink_metadata(path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+
+    let mut millis = unix_millis();
+    while trash.join(millis.to_string()).exists() {
+        millis += 1;
+    }
+    let id = millis.to_string();
+    let dir = entry_dir(trash, &id)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let entry = TrashEntry {
+        id: id.clone(),
+        original_path: path.to_string_lossy().into_owned(),
+        name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        is_dir: meta.is_dir(),
+        scope,
+        deleted_at: millis as u64,
+        expires_at: None,
+    };
+    let text = serde_json::to_string_pretty(&entry).map_err(|e| e.to_string())?;
+    let result = atomic_write(&dir.join("meta.json"), text.as_bytes())
+        .map_err(MoveError::NotMoved)
+        .and_then(|_| move_path(path, &dir.join("item")));
+    match result {
+        Ok(()) => Ok(entry),
+        Err(MoveError::NotMoved(e)) => {
+            let _ = fs::remove_dir_all(&dir);
+            Err(e)
+        }
+        // The trash holds the only complete copy now, so the entry stays
+        Err(MoveError::SourceLeft(e)) => Err(format!(
+            "{} was only partly deleted ({}); a complete copy is in the trash as entry {}",
+            path.display(),
+            e,
+            id
+        )),
+    }
+}
+
+/// Move `path` (already checked against the path policy) into the trash.
+pub fn move_to_trash(path: &Path) -> Result<TrashEntry, String> {
+    let entry = trash_item(&trash_dir(), path, scope_of(path))?;
+    purge_expired();
+    Ok(with_expiry(entry, read_settings().retention_days))
+}
+
+/// Permanently delete entries in `trash` that expired by `now`.
+fn purge(trash: &Path, retention_days: u32, now: u64) {
+    if retention_days == 0 {
+        return;
+    }
+    for entry in trash_entries(trash) {
+        if with_expiry(entry.clone(), retention_days).expires_at.is_some_and(|expires| expires <= now) {
+            if let Ok(dir) = entry_dir(trash, &entry.id) {
+                let _ = fs::remove_dir_all(dir);
+            }
+        }
+    }
+}
+
+/// Permanently delete entries older than the retention period.
+pub fn purge_expired() {
+    purge(&trash_dir(), read_settings().retention_days, unix_millis() as u64);
+}
+
+fn trash_entries(trash: &Path) -> Vec<TrashEntry> {
+    let mut entries: Vec<TrashEntry> = fs::read_dir(trash)
+        .map(|dirs| dirs.flatten().filter_map(|d| read_entry(&d.path())).collect())
+        .unwrap_or_default();
+    // Newest first
+    entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+    entries
+}
+
+/// Deleted files and directories, newest first. Expired entries are purged
+/// before listing.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn list_trash() -> Vec<TrashEntry> {
+    purge_expired();
+    let retention_days = read_settings().retention_days;
+    trash_entries(&trash_dir()).into_iter().map(|e| with_expiry(e, retention_days)).collect()
+}
+
+/// Move entry `id` back to its original path, which `check` vets first.
+fn restore(trash: &Path, id: &str, check: impl Fn(&str) -> Result<PathBuf, String>) -> Result<TrashEntry, String> {
+    let dir = entry_dir(trash, id)?;
+    let entry = read_entry(&dir).ok_or_else(|| format!("No trash entry {}", id))?;
+    let original = check(&entry.original_path)?;
+    if fs::symlink_metadata(&original).is_ok() {
+        return Err(format!("{} already exists; move it away before restoring", entry.original_path));
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    match move_path(&dir.join("item"), &original) {
+        // Only leftovers in the trash remain; they go with the entry
+        Ok(()) | Err(MoveError::SourceLeft(_)) => {}
+        Err(MoveError::NotMoved(e)) => return Err(e),
+    }
+    let _ = fs::remove_dir_all(&dir);
+    Ok(entry)
+}
+
+/// Put a deleted item back where it was. Fails rather than overwrite if
+/// something has been created at the original path since.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn restore_from_trash(id: String) -> Result<TrashEntry, String> {
+    restore(&trash_dir(), &id, crate::sandbox::check_path)
+}
+
+/// Permanently delete the given entries, or everything when `ids` is omitted.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn empty_trash(ids: Option<Vec<String>>) -> Result<usize, String> {
+    let trash = trash_dir();
+    let ids = ids.unwrap_or_else(|| trash_entries(&trash).into_iter().map(|e| e.id).collect());
+    let mut removed = 0;
+    for id in ids {
+        let dir = entry_dir(&trash, &id)?;
+        if dir.is_dir() {
+            fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove trash entry {}: {}", id, e))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn get_trash_retention() -> u32 {
+    read_settings().retention_days
+}
+
+/// Days to keep deleted items; 0 keeps them until the trash is emptied.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn set_trash_retention(days: u32) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(&TrashSettings { retention_days: days }).map_err(|e| e.to_string())?;
+    atomic_write(&settings_path(), text.as_bytes())?;
+    purge_expired();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A temporary trash directory and a config directory holding `a.md`
+    /// and `agents/b.md`.
+    fn fixture() -> (TempDir, PathBuf, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let trash = temp.path().join("trash");
+        let config = temp.path().join("config");
+        fs::create_dir_all(config.join("agents")).unwrap();
+        fs::write(config.join("a.md"), "a").unwrap();
+        fs::write(config.join("agents/b.md"), "b").unwrap();
+        (temp, trash, config)
+    }
+
+    fn allow(path: &str) -> Result<PathBuf, String> {
+        Ok(PathBuf::from(path))
+    }
+
+    #[test]
+    fn moves_and_restores() {
+        let (_temp, trash, config) = fixture();
+        let file = trash_item(&trash, &config.join("a.md"), TrashScope::User).unwrap();
+        let dir = trash_item(&trash, &config.join("agents"), TrashScope::User).unwrap();
+        assert!(!config.join("a.md").exists() && !config.join("agents").exists());
+        assert!(dir.is_dir && !file.is_dir);
+        assert_eq!(fs::read_to_string(trash.join(&file.id).join("item")).unwrap(), "a");
+
+        let ids: Vec<String> = trash_entries(&trash).into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, [dir.id.clone(), file.id.clone()]);
+
+        restore(&trash, &dir.id, allow).unwrap();
+        assert_eq!(fs::read_to_string(config.join("agents/b.md")).unwrap(), "b");
+        assert!(!trash.join(&dir.id).exists());
+    }
+
+    #[test]
+    fn refuses_to_restore_over_an_existing_path() {
+        let (_temp, trash, config) = fixture();
+        let entry = trash_item(&trash, &config.join("a.md"), TrashScope::Project).unwrap();
+        fs::write(config.join("a.md"), "new").unwrap();
+        assert!(restore(&trash, &entry.id, allow).unwrap_err().contains("already exists"));
+        assert_eq!(fs::read_to_string(config.join("a.md")).unwrap(), "new");
+        assert!(trash.join(&entry.id).join("item").exists());
+    }
+
+    #[test]
+    fn refuses_restoring_where_the_policy_says_no() {
+        let (_temp, trash, config) = fixture();
+        let entry = trash_item(&trash, &config.join("a.md"), TrashScope::Project).unwrap();
+        let deny = |path: &str| Err(format!("{} is not allowed", path));
+        assert!(restore(&trash, &entry.id, deny).is_err());
+        assert!(trash.join(&entry.id).join("item").exists());
+    }
+
+    #[test]
+    fn purges_expired_entries() {
+        let (_temp, trash, config) = fixture();
+        let old = trash_item(&trash, &config.join("a.md"), TrashScope::User).unwrap();
+        let recent = trash_item(&trash, &config.join("agents"), TrashScope::User).unwrap();
+        let day = DAY_MILLIS as u64;
+        let now = recent.deleted_at + 10 * day;
+        let aged = TrashEntry { deleted_at: now - 31 * day, ..old.clone() };
+        fs::write(trash.join(&old.id).join("meta.json"), serde_json::to_string(&aged).unwrap()).unwrap();
+
+        purge(&trash, 0, now);
+        assert_eq!(trash_entries(&trash).len(), 2);
+        purge(&trash, 30, now);
+        let left: Vec<String> = trash_entries(&trash).into_iter().map(|e| e.id).collect();
+        assert_eq!(left, [recent.id]);
+    }
+
+    #[test]
+    fn rejects_invalid_ids() {
+        let (_temp, trash, _) = fixture();
+        for id in ["", "..", "../etc", "12a", "/1"] {
+            assert!(entry_dir(&trash, id).unwrap_err().starts_with("Invalid trash id"));
+        }
+        assert!(restore(&trash, "123", allow).unwrap_err().starts_with("No trash entry"));
+    }
+}
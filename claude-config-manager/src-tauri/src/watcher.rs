@@ -0,0 +1,307 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{get_config_paths, make_info, project_config_files, project_subdirs, ConfigPathInfo};
+
+/// Event emitted to the frontend with a batch of `ConfigChangeEvent`s.
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// Quiet period before a burst of filesystem events is reported.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ConfigChangeEvent {
+    pub kind: ConfigChangeKind,
+    pub info: ConfigPathInfo,
+}
+
+/// A config path we care about. Files are watched through their parent
+/// directory so they can be reported when created; directories such as
+/// `agents/` are watched recursively.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct WatchTarget {
+    path: PathBuf,
+    recursive: bool,
+}
+
+impl WatchTarget {
+    fn matches(&self, path: &Path) -> bool {
+        path == self.path || (self.recursive && path.starts_with(&self.path))
+    }
+}
+
+struct WatcherInner {
+    watcher: RecommendedWatcher,
+    watched: Vec<(PathBuf, RecursiveMode)>,
+}
+
+/// Managed state owning the filesystem watcher. Always present so commands
+/// can report a useful error if the platform watcher failed to start.
+pub struct ConfigWatcher {
+    inner: Mutex<Result<WatcherInner, String>>,
+    targets: Arc<Mutex<Vec<WatchTarget>>>,
+    projects: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+fn is_ignored(path: &Path) -> bool {
+    // Temp files from storage::atomic_write
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.starts_with('.') && n.ends_with(".tmp"))
+        .unwrap_or(false)
+}
+
+fn dir_target(path: PathBuf) -> WatchTarget {
+    WatchTarget { path, recursive: true }
+}
+
+fn file_target(path: PathBuf) -> WatchTarget {
+    WatchTarget { path, recursive: false }
+}
+
+fn user_targets() -> Vec<WatchTarget> {
+    let paths = get_config_paths();
+    let e = paths.enterprise;
+    let u = paths.user;
+    vec![
+        file_target(e.claude_md.path.into()),
+        file_target(e.managed_mcp.path.into()),
+        file_target(e.managed_settings.path.into()),
+        file_target(u.claude_md.path.into()),
+        file_target(u.claude_local_md.path.into()),
+        file_target(u.settings.path.into()),
+        file_target(u.settings_local.path.into()),
+        dir_target(u.agents.path.into()),
+        dir_target(u.commands.path.into()),
+        file_target(u.mcp.path.into()),
+        dir_target(u.skills.path.into()),
+    ]
+}
+
+fn project_targets(project: &Path) -> Vec<WatchTarget> {
+    let f = project_config_files(project);
+    let mut targets = vec![
+        file_target(f.claude_md_root.path.into()),
+        file_target(f.claude_md_dotclaude.path.into()),
+        file_target(f.claude_local_md.path.into()),
+        file_target(f.settings.path.into()),
+        file_target(f.settings_local.path.into()),
+        dir_target(f.rules.path.into()),
+        dir_target(f.commands.path.into()),
+        dir_target(f.agents.path.into()),
+        dir_target(f.skills.path.into()),
+        file_target(f.mcp.path.into()),
+    ];
+    // Every directory that could hold a nested CLAUDE.md, so new ones are
+    // reported as well as existing ones
+    for dir in project_subdirs(project, None) {
+        targets.push(file_target(dir.join("CLAUDE.md")));
+        targets.push(file_target(dir.join("CLAUDE.local.md")));
+    }
+    targets
+}
+
+/// The directory to hand to the OS watcher for a target: the target itself
+/// for existing directories, otherwise the nearest existing ancestor (so that
+/// creating `~/.claude/agents/` or `CLAUDE.md` is still noticed).
+fn watch_root(target: &WatchTarget) -> Option<(PathBuf, RecursiveMode)> {
+    if target.recursive && target.path.is_dir() {
+        return Some((target.path.clone(), RecursiveMode::Recursive));
+    }
+    let mut dir = target.path.parent()?;
+    while !dir.is_dir() {
+        dir = dir.parent()?;
+    }
+    Some((dir.to_path_buf(), RecursiveMode::NonRecursive))
+}
+
+fn classify(kind: &EventKind) -> Option<ConfigChangeKind> {
+    match kind {
+        EventKind::Create(_) => Some(ConfigChangeKind::Created),
+        EventKind::Modify(_) => Some(ConfigChangeKind::Modified),
+        EventKind::Remove(_) => Some(ConfigChangeKind::Deleted),
+        _ => None,
+    }
+}
+
+/// Whether a newly created directory changes what has to be watched: it
+/// lies inside a project (and may get a CLAUDE.md) or on the way to a
+/// target that didn't exist yet, such as ~/.claude/agents/.
+fn needs_rescan(dir: &Path, targets: &[WatchTarget], projects: &[PathBuf]) -> bool {
+    projects.iter().any(|p| dir.starts_with(p)) || targets.iter().any(|t| t.path.starts_with(dir))
+}
+
+fn is_memory_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with("CLAUDE") && name.ends_with(".md")
+}
+
+fn run_debouncer(
+    app: AppHandle,
+    rx: std::sync::mpsc::Receiver<Event>,
+    targets: Arc<Mutex<Vec<WatchTarget>>>,
+    projects: Arc<Mutex<Vec<PathBuf>>>,
+) {
+    // path -> whether a create was seen during this burst
+    let mut pending: BTreeMap<PathBuf, bool> = BTreeMap::new();
+    let mut rescan = false;
+    // Memory files created this burst that aren't targets yet; a rescan may
+    // make them one (`mkdir docs && touch docs/CLAUDE.md`)
+    let mut unmatched: Vec<PathBuf> = Vec::new();
+
+    loop {
+        match rx.recv_timeout(DEBOUNCE) {
+            Ok(event) => {
+                let Some(kind) = classify(&event.kind) else { continue };
+                let targets = targets.lock().unwrap();
+                let projects = projects.lock().unwrap();
+                for path in event.paths {
+                    if is_ignored(&path) {
+                        continue;
+                    }
+                    let created = kind == ConfigChangeKind::Created;
+                    if created && path.is_dir() && needs_rescan(&path, &targets, &projects) {
+                        rescan = true;
+                    }
+                    if !targets.iter().any(|t| t.matches(&path)) {
+                        if created && is_memory_file(&path) {
+                            unmatched.push(path);
+                        }
+                        continue;
+                    }
+                    *pending.entry(path).or_insert(false) |= created;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if std::mem::take(&mut rescan) {
+                    if let Some(watcher) = app.try_state::<ConfigWatcher>() {
+                        let _ = watcher.refresh();
+                    }
+                    let targets = targets.lock().unwrap();
+                    for path in unmatched.drain(..).filter(|p| targets.iter().any(|t| t.matches(p))) {
+                        pending.insert(path, true);
+                    }
+                }
+                unmatched.clear();
+                if pending.is_empty() {
+                    continue;
+                }
+                // Report the state at the end of the burst, e.g. an editor's
+                // delete-and-recreate save shows up as a single modification.
+                let batch: Vec<ConfigChangeEvent> = std::mem::take(&mut pending)
+                    .into_iter()
+                    .map(|(path, created)| {
+                        let info = make_info(path);
+                        let kind = if !info.exists {
+                            ConfigChangeKind::Deleted
+                        } else if created {
+                            ConfigChangeKind::Created
+                        } else {
+                            ConfigChangeKind::Modified
+                        };
+                        ConfigChangeEvent { kind, info }
+                    })
+                    .collect();
+                let _ = app.emit(CONFIG_CHANGED_EVENT, batch);
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+impl ConfigWatcher {
+    /// Start watching the user and enterprise config paths. Projects are
+    /// added later through `watch_projects`.
+    pub fn start(app: AppHandle) -> Self {
+        let targets = Arc::new(Mutex::new(Vec::new()));
+        let projects = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = channel::<Event>();
+
+        let inner = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let _ = tx.send(event);
+            }
+        })
+        .map(|watcher| WatcherInner { watcher, watched: Vec::new() })
+        .map_err(|e| format!("Failed to start file watcher: {}", e));
+
+        if inner.is_ok() {
+            let targets = targets.clone();
+            let projects = projects.clone();
+            thread::spawn(move || run_debouncer(app, rx, targets, projects));
+        }
+
+        let config_watcher = ConfigWatcher {
+            inner: Mutex::new(inner),
+            targets,
+            projects,
+        };
+        let _ = config_watcher.set_projects(&[]);
+        config_watcher
+    }
+
+    /// Replace the watched set with the user paths plus the given projects.
+    pub fn set_projects(&self, projects: &[PathBuf]) -> Result<(), String> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = guard.as_mut().map_err(|e| e.clone())?;
+
+        let mut targets = user_targets();
+        for project in projects {
+            targets.extend(project_targets(project));
+        }
+
+        let mut roots: Vec<(PathBuf, RecursiveMode)> = Vec::new();
+        let mut seen = HashSet::new();
+        for target in &targets {
+            if let Some((root, mode)) = watch_root(target) {
+                if seen.insert((root.clone(), mode == RecursiveMode::Recursive)) {
+                    roots.push((root, mode));
+                }
+            }
+        }
+
+        for (path, _) in inner.watched.drain(..) {
+            let _ = inner.watcher.unwatch(&path);
+        }
+        for (path, mode) in roots {
+            // Unreadable directories are skipped rather than failing the batch
+            if inner.watcher.watch(&path, mode).is_ok() {
+                inner.watched.push((path, mode));
+            }
+        }
+
+        *self.targets.lock().unwrap() = targets;
+        *self.projects.lock().unwrap() = projects.to_vec();
+        Ok(())
+    }
+
+    /// Rebuild the watched set for the current projects, picking up
+    /// directories created since the last call.
+    pub fn refresh(&self) -> Result<(), String> {
+        let projects = self.projects.lock().unwrap().clone();
+        self.set_projects(&projects)
+    }
+}
+
+/// Watch the config files of these projects (in addition to the user and
+/// enterprise paths, which are always watched). Replaces any previous list.
+#[cfg_attr(feature = "gui", tauri::command)]
+pub fn watch_projects(watcher: State<'_, ConfigWatcher>, project_paths: Vec<String>) -> Result<(), String> {
+    let projects: Vec<PathBuf> = project_paths.into_iter().map(PathBuf::from).collect();
+    watcher.set_projects(&projects)
+}
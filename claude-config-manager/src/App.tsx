@@ -5,9 +5,36 @@ import { AIAssistant } from './components/ai/AIAssistant';
 import { Toaster } from './components/ui/toaster';
 import { useConfigStore } from './stores/configStore';
 import { EmptyState } from './components/layout/EmptyState';
+import { onConfigChanged, onProfileActivated } from './lib/paths';
+import { toast } from './hooks/use-toast';
 
 function App() {
-  const { selectedFilePath, theme } = useConfigStore();
+  const { selectedFilePath, theme, initialize } = useConfigStore();
+
+  // Keep the tree in sync with edits made outside the app (editors, Claude itself)
+  useEffect(() => {
+    const unlisten = onConfigChanged(() => {
+      initialize();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [initialize]);
+
+  // Profiles can be switched from the tray while the window is hidden
+  useEffect(() => {
+    const unlisten = onProfileActivated(({ name, error }) => {
+      if (error) {
+        toast({ title: `Failed to switch to "${name}"`, description: error, variant: 'destructive' });
+      } else {
+        toast({ title: `Switched to profile "${name}"` });
+        initialize();
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [initialize]);
 
   useEffect(() => {
     const root = window.document.documentElement;
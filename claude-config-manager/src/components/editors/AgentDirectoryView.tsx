@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
-import { listDirectory, deletePath, readConfigFile, saveConfigFile, DirectoryEntry, createDirectory } from '@/lib/paths';
-import { parseFrontmatter, validateAgentFrontmatter, generateFrontmatter, AgentFrontmatter } from '@/lib/frontmatter';
+import { listDirectory, deletePath, restoreFromTrash, saveConfigFile, DirectoryEntry, createDirectory, parseDefinition } from '@/lib/paths';
+import { generateFrontmatter, AgentFrontmatter, diagnosticMessages } from '@/lib/frontmatter';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Textarea } from '@/components/ui/textarea';
@@ -27,6 +27,7 @@ import {
     Bot, Wrench, FileText, Pencil, Sparkles
 } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
+import { ToastAction } from '@/components/ui/toast';
 import { useConfigStore } from '@/stores/configStore';
 import { cn } from '@/lib/utils';
 import { generateFullConfigFile } from '@/lib/ai/generators';
@@ -91,16 +92,14 @@ export function AgentDirectoryView({ path }: AgentDirectoryViewProps) {
                 }
 
                 try {
-                    const content = await readConfigFile(entry.path);
-                    const parsed = parseFrontmatter<AgentFrontmatter>(content);
-                    const validationErrors = validateAgentFrontmatter(parsed.frontmatter);
+                    const parsed = await parseDefinition(entry.path, 'agent');
 
                     agentInfos.push({
                         entry,
-                        frontmatter: parsed.frontmatter,
-                        content: parsed.content,
-                        isValid: parsed.isValid && validationErrors.length === 0,
-                        errors: [...parsed.errors, ...validationErrors]
+                        frontmatter: parsed.frontmatter as AgentFrontmatter | null,
+                        content: parsed.body.trim(),
+                        isValid: parsed.valid,
+                        errors: diagnosticMessages(parsed.diagnostics)
                     });
                 } catch (err) {
                     agentInfos.push({
@@ -132,12 +131,24 @@ export function AgentDirectoryView({ path }: AgentDirectoryViewProps) {
     const handleDelete = async (agent: AgentInfo) => {
         setIsDeleting(agent.entry.path);
         try {
-            await deletePath(agent.entry.path);
+            const trashed = await deletePath(agent.entry.path);
             await loadAgents();
             await initialize();
             toast({
                 title: "Agent deleted",
-                description: `Successfully deleted ${agent.entry.name}`,
+                description: `Moved ${agent.entry.name} to the trash`,
+                action: (
+                    <ToastAction
+                        altText="Undo"
+                        onClick={async () => {
+                            await restoreFromTrash(trashed.id);
+                            await loadAgents();
+                            await initialize();
+                        }}
+                    >
+                        Undo
+                    </ToastAction>
+                ),
             });
         } catch (err) {
             toast({
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
-import { listDirectory, deletePath, readConfigFile, saveConfigFile, DirectoryEntry, createDirectory } from '@/lib/paths';
-import { parseFrontmatter, validateCommandFrontmatter, generateFrontmatter, CommandFrontmatter } from '@/lib/frontmatter';
+import { listDirectory, deletePath, restoreFromTrash, saveConfigFile, DirectoryEntry, createDirectory, parseDefinition } from '@/lib/paths';
+import { generateFrontmatter, CommandFrontmatter, diagnosticMessages } from '@/lib/frontmatter';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Textarea } from '@/components/ui/textarea';
@@ -20,6 +20,7 @@ import {
     Terminal, Wrench, FileText, Pencil, Sparkles, Zap
 } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
+import { ToastAction } from '@/components/ui/toast';
 import { useConfigStore } from '@/stores/configStore';
 import { cn } from '@/lib/utils';
 import { generateFullConfigFile } from '@/lib/ai/generators';
@@ -83,16 +84,15 @@ export function CommandDirectoryView({ path }: CommandDirectoryViewProps) {
                 }
 
                 try {
-                    const content = await readConfigFile(entry.path);
-                    const parsed = parseFrontmatter<CommandFrontmatter>(content);
-                    const validationErrors = validateCommandFrontmatter(parsed.frontmatter);
+                    const parsed = await parseDefinition(entry.path, 'command');
 
                     commandInfos.push({
                         entry,
-                        frontmatter: parsed.frontmatter,
-                        content: parsed.content,
-                        isValid: parsed.isValid && validationErrors.length === 0,
-                        errors: [...parsed.errors, ...validationErrors]
+                        frontmatter: parsed.frontmatter as CommandFrontmatter | null,
+                        content: parsed.body.trim(),
+                        // Commands without frontmatter work, but this view lists them for fixing
+                        isValid: parsed.valid && parsed.frontmatter !== null,
+                        errors: diagnosticMessages(parsed.diagnostics)
                     });
                 } catch (err) {
                     commandInfos.push({
@@ -124,12 +124,24 @@ export function CommandDirectoryView({ path }: CommandDirectoryViewProps) {
     const handleDelete = async (command: CommandInfo) => {
         setIsDeleting(command.entry.path);
         try {
-            await deletePath(command.entry.path);
+            const trashed = await deletePath(command.entry.path);
             await loadCommands();
             await initialize();
             toast({
                 title: "Command deleted",
-                description: `Successfully deleted ${command.entry.name}`,
+                description: `Moved ${command.entry.name} to the trash`,
+                action: (
+                    <ToastAction
+                        altText="Undo"
+                        onClick={async () => {
+                            await restoreFromTrash(trashed.id);
+                            await loadCommands();
+                            await initialize();
+                        }}
+                    >
+                        Undo
+                    </ToastAction>
+                ),
             });
         } catch (err) {
             toast({
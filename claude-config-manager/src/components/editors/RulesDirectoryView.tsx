@@ -1,5 +1,5 @@
 import { useState, useEffect } from 'react';
-import { listDirectory, deletePath, readConfigFile, saveConfigFile, DirectoryEntry, createDirectory } from '@/lib/paths';
+import { listDirectory, deletePath, restoreFromTrash, readConfigFile, saveConfigFile, DirectoryEntry, createDirectory } from '@/lib/paths';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Textarea } from '@/components/ui/textarea';
@@ -18,6 +18,7 @@ import {
     Pencil, Sparkles, ScrollText, FolderOpen
 } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
+import { ToastAction } from '@/components/ui/toast';
 import { useConfigStore } from '@/stores/configStore';
 import { cn } from '@/lib/utils';
 import { generateFullConfigFile } from '@/lib/ai/generators';
@@ -154,12 +155,24 @@ export function RulesDirectoryView({ path }: RulesDirectoryViewProps) {
     const handleDelete = async (rule: RuleInfo) => {
         setIsDeleting(rule.entry.path);
         try {
-            await deletePath(rule.entry.path);
+            const trashed = await deletePath(rule.entry.path);
             await loadRules();
             await initialize();
             toast({
                 title: "Rule deleted",
-                description: `Successfully deleted ${rule.entry.name}`,
+                description: `Moved ${rule.entry.name} to the trash`,
+                action: (
+                    <ToastAction
+                        altText="Undo"
+                        onClick={async () => {
+                            await restoreFromTrash(trashed.id);
+                            await loadRules();
+                            await initialize();
+                        }}
+                    >
+                        Undo
+                    </ToastAction>
+                ),
             });
         } catch (err) {
             toast({
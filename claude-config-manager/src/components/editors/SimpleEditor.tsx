@@ -1,5 +1,5 @@
 import { useState, useEffect, useMemo } from 'react';
-import { readConfigFile, saveConfigFile, getPathInfo, isEnterprisePath, listDirectory, deletePath, DirectoryEntry } from '@/lib/paths';
+import { readConfigFileVersioned, saveConfigFile, SaveConflictError, SaveSecretsError, getPathInfo, isEnterprisePath, listDirectory, deletePath, DirectoryEntry } from '@/lib/paths';
 import { generateFullConfigFile, validateConfigFile } from '@/lib/ai/generators';
 import { MonacoEditor } from './MonacoEditor';
 import { MarkdownEditor } from './MarkdownEditor';
@@ -19,6 +19,7 @@ import {
     AlertTriangle, RotateCcw, Trash2, FileText, Folder, Plug
 } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
+import { ToastAction } from '@/components/ui/toast';
 import { useConfigStore } from '@/stores/configStore';
 import { cn } from '@/lib/utils';
 import { ScrollArea } from '@/components/ui/scroll-area';
@@ -29,6 +30,7 @@ interface SimpleEditorProps {
 
 export function SimpleEditor({ path }: SimpleEditorProps) {
     const [content, setContent] = useState<string>('');
+    const [version, setVersion] = useState<string | undefined>(undefined);
     const [isLoading, setIsLoading] = useState(true);
     const [isSaving, setIsSaving] = useState(false);
     const [isGenerating, setIsGenerating] = useState(false);
@@ -43,6 +45,7 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
         async function load() {
             setIsLoading(true);
             setDirEntries([]);
+            setVersion(undefined);
             try {
                 const info = await getPathInfo(path);
                 setExists(info.exists);
@@ -53,8 +56,9 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
                     const entries = await listDirectory(path);
                     setDirEntries(entries);
                 } else if (info.exists && !info.is_dir) {
-                    const text = await readConfigFile(path);
-                    setContent(text);
+                    const file = await readConfigFileVersioned(path);
+                    setContent(file.content);
+                    setVersion(file.version);
                 }
             } catch (err) {
                 toast({
@@ -69,15 +73,37 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
         load();
     }, [path, toast]);
 
-    const handleSave = async () => {
+    const handleSave = async (allowSecrets = false) => {
         setIsSaving(true);
         try {
-            await saveConfigFile(path, content);
+            setVersion(await saveConfigFile(path, content, version, allowSecrets));
             toast({
                 title: "File saved",
                 description: "Your changes have been saved successfully.",
             });
         } catch (err) {
+            if (err instanceof SaveConflictError) {
+                toast({
+                    title: "File changed on disk",
+                    description: `${err.message}. Reload to pick up the latest version before saving.`,
+                    variant: "destructive",
+                });
+                return;
+            }
+            if (err instanceof SaveSecretsError) {
+                const lines = err.findings.map((f) => `line ${f.line}`).join(', ');
+                toast({
+                    title: "Possible secrets in a file git would commit",
+                    description: `Found ${err.findings.length} likely credential(s) at ${lines}. Move them to an environment variable, or save anyway.`,
+                    variant: "destructive",
+                    action: (
+                        <ToastAction altText="Save anyway" onClick={() => handleSave(true)}>
+                            Save anyway
+                        </ToastAction>
+                    ),
+                });
+                return;
+            }
             toast({
                 title: "Error saving file",
                 description: (err as any).toString(),
@@ -110,7 +136,7 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
         setIsSaving(true);
         try {
             const initialContent = language === 'json' ? '{}' : '';
-            await saveConfigFile(path, initialContent);
+            setVersion(await saveConfigFile(path, initialContent));
             setExists(true);
             setContent(initialContent);
             await initialize(); // Refresh tree
@@ -143,7 +169,7 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
         try {
             const fileName = path.split(/[/\\]/).pop() || 'file';
             const generated = await generateFullConfigFile(apiKey, fileName, path, "A software project", codingModel);
-            await saveConfigFile(path, generated);
+            setVersion(await saveConfigFile(path, generated));
             setExists(true);
             setContent(generated);
             await initialize(); // Refresh tree
@@ -719,7 +745,12 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
                         size="sm"
                         onClick={() => {
                             setIsLoading(true);
-                            readConfigFile(path).then(setContent).finally(() => setIsLoading(false));
+                            readConfigFileVersioned(path)
+                                .then((file) => {
+                                    setContent(file.content);
+                                    setVersion(file.version);
+                                })
+                                .finally(() => setIsLoading(false));
                         }}
                         disabled={isLoading || isSaving}
                         className="h-8 text-[11px] gap-1.5 hover:bg-muted"
@@ -728,7 +759,7 @@ export function SimpleEditor({ path }: SimpleEditorProps) {
                         Reload
                     </Button>
                     {!isReadOnly && (
-                        <Button size="sm" onClick={handleSave} disabled={isSaving} className="h-8 text-[11px] gap-1.5 shadow-sm">
+                        <Button size="sm" onClick={() => handleSave()} disabled={isSaving} className="h-8 text-[11px] gap-1.5 shadow-sm">
                             {isSaving ? <Loader2 className="w-3 h-3 animate-spin" /> : <Save className="w-3 h-3" />}
                             Save
                         </Button>
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
-import { listDirectory, deletePath, readConfigFile, saveConfigFile, DirectoryEntry, createDirectory } from '@/lib/paths';
-import { parseFrontmatter, validateSkillFrontmatter, generateFrontmatter, SkillFrontmatter } from '@/lib/frontmatter';
+import { listDirectory, deletePath, restoreFromTrash, saveConfigFile, DirectoryEntry, createDirectory, parseDefinition } from '@/lib/paths';
+import { generateFrontmatter, SkillFrontmatter, diagnosticMessages } from '@/lib/frontmatter';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
 import { Textarea } from '@/components/ui/textarea';
@@ -20,6 +20,7 @@ import {
     Sparkles, Wrench, FileText, Pencil, FolderOpen, Zap, Search
 } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
+import { ToastAction } from '@/components/ui/toast';
 import { useConfigStore } from '@/stores/configStore';
 import { cn } from '@/lib/utils';
 import { generateFullConfigFile } from '@/lib/ai/generators';
@@ -94,9 +95,7 @@ export function SkillDirectoryView({ path }: SkillDirectoryViewProps) {
                 // Check for SKILL.md inside the directory
                 const skillMdPath = `${entry.path}/SKILL.md`;
                 try {
-                    const content = await readConfigFile(skillMdPath);
-                    const parsed = parseFrontmatter<SkillFrontmatter>(content);
-                    const validationErrors = validateSkillFrontmatter(parsed.frontmatter);
+                    const parsed = await parseDefinition(skillMdPath, 'skill');
 
                     // Check for workflows/ and context/ subdirectories
                     let hasWorkflows = false;
@@ -111,11 +110,11 @@ export function SkillDirectoryView({ path }: SkillDirectoryViewProps) {
 
                     skillInfos.push({
                         entry,
-                        frontmatter: parsed.frontmatter,
-                        content: parsed.content,
+                        frontmatter: parsed.frontmatter as SkillFrontmatter | null,
+                        content: parsed.body.trim(),
                         skillMdPath,
-                        isValid: parsed.isValid && validationErrors.length === 0,
-                        errors: [...parsed.errors, ...validationErrors],
+                        isValid: parsed.valid,
+                        errors: diagnosticMessages(parsed.diagnostics),
                         hasWorkflows,
                         hasContext
                     });
@@ -153,12 +152,24 @@ export function SkillDirectoryView({ path }: SkillDirectoryViewProps) {
     const handleDelete = async (skill: SkillInfo) => {
         setIsDeleting(skill.entry.path);
         try {
-            await deletePath(skill.entry.path);
+            const trashed = await deletePath(skill.entry.path);
             await loadSkills();
             await initialize();
             toast({
                 title: "Skill deleted",
-                description: `Successfully deleted ${skill.entry.name}`,
+                description: `Moved ${skill.entry.name} to the trash`,
+                action: (
+                    <ToastAction
+                        altText="Undo"
+                        onClick={async () => {
+                            await restoreFromTrash(trashed.id);
+                            await loadSkills();
+                            await initialize();
+                        }}
+                    >
+                        Undo
+                    </ToastAction>
+                ),
             });
         } catch (err) {
             toast({
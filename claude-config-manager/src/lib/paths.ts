@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 
 export interface ConfigPathInfo {
     path: string;
@@ -40,12 +41,87 @@ export async function getPathInfo(path: string): Promise<ConfigPathInfo> {
     return await invoke<ConfigPathInfo>("get_path_info", { path });
 }
 
+export interface ConfigFileContents {
+    content: string;
+    version: string;        // Pass back to saveConfigFile to detect external edits
+}
+
+export type SaveError =
+    | { kind: 'conflict'; message: string; current_content: string | null; current_version: string | null }
+    | { kind: 'secrets_in_tracked_file'; message: string; findings: SecretMatch[] }
+    | { kind: 'path_not_allowed'; message: string }
+    | { kind: 'io'; message: string };
+
+/** Prefix of every error caused by the backend's path policy. */
+export const PATH_POLICY_ERROR_PREFIX = "Path not allowed";
+
+/**
+ * True when a file command was refused because the path is outside the
+ * enterprise, user and registered project config directories.
+ */
+export function isPathPolicyError(err: unknown): boolean {
+    const message = err instanceof Error ? err.message : String((err as SaveError)?.message ?? err);
+    return message.startsWith(PATH_POLICY_ERROR_PREFIX);
+}
+
+/**
+ * Thrown by saveConfigFile when the file changed on disk since it was read
+ * (e.g. Claude Code rewrote it). Nothing was written.
+ */
+export class SaveConflictError extends Error {
+    currentContent: string | null;
+    currentVersion: string | null;
+
+    constructor(error: Extract<SaveError, { kind: 'conflict' }>) {
+        super(error.message);
+        this.name = 'SaveConflictError';
+        this.currentContent = error.current_content;
+        this.currentVersion = error.current_version;
+    }
+}
+
+/**
+ * Thrown by saveConfigFile when the content looks like it contains secrets
+ * and the file is tracked by git, or is in a repository and not ignored.
+ * Nothing was written; retry with allowSecrets to save anyway.
+ */
+export class SaveSecretsError extends Error {
+    findings: SecretMatch[];
+
+    constructor(error: Extract<SaveError, { kind: 'secrets_in_tracked_file' }>) {
+        super(error.message);
+        this.name = 'SaveSecretsError';
+        this.findings = error.findings;
+    }
+}
+
+export async function readConfigFileVersioned(path: string): Promise<ConfigFileContents> {
+    return await invoke<ConfigFileContents>("read_config_file", { path });
+}
+
 export async function readConfigFile(path: string): Promise<string> {
-    return await invoke<string>("read_config_file", { path });
+    return (await readConfigFileVersioned(path)).content;
 }
 
-export async function saveConfigFile(path: string, content: string): Promise<void> {
-    await invoke("save_config_file", { path, content });
+/**
+ * Save a config file and return the new version. When expectedVersion is
+ * given the save is refused with a SaveConflictError if the file changed.
+ * Secrets in files git tracks or would pick up are refused with a
+ * SaveSecretsError unless allowSecrets is set.
+ */
+export async function saveConfigFile(path: string, content: string, expectedVersion?: string, allowSecrets?: boolean): Promise<string> {
+    try {
+        return await invoke<string>("save_config_file", { path, content, expectedVersion, allowSecrets });
+    } catch (err) {
+        const error = err as SaveError;
+        if (error?.kind === 'conflict') {
+            throw new SaveConflictError(error);
+        }
+        if (error?.kind === 'secrets_in_tracked_file') {
+            throw new SaveSecretsError(error);
+        }
+        throw new Error(error?.message ?? String(err));
+    }
 }
 
 export interface ProjectConfigFiles {
@@ -69,8 +145,31 @@ export interface ProjectInfo {
     config_files: ProjectConfigFiles;
 }
 
-export async function listProjects(baseDir: string): Promise<ProjectInfo[]> {
-    return await invoke<ProjectInfo[]>("list_projects", { baseDir });
+// Project discovery options; omitted fields use the backend defaults
+export interface ScanOptions {
+    max_depth?: number;               // 1 = immediate children of each root (default 3)
+    extra_roots?: string[];           // Scanned in addition to baseDir
+    include?: string[];               // Globs relative to the root; empty = everything
+    exclude?: string[];               // Globs relative to the root that are never entered
+    respect_gitignore?: boolean;
+    stop_at_project_root?: boolean;   // Don't look for projects inside projects
+    markers?: string[];               // Files/dirs that mark a project root
+}
+
+export async function listProjects(baseDir: string, options?: ScanOptions): Promise<ProjectInfo[]> {
+    return await invoke<ProjectInfo[]>("list_projects", { baseDir, options });
+}
+
+// Projects Claude Code has already been used in (~/.claude.json and ~/.claude/projects)
+export interface KnownProject extends ProjectInfo {
+    exists: boolean;                  // False if the directory has since been removed
+    in_claude_json: boolean;          // Listed in the `projects` map of ~/.claude.json
+    history_dir: string | null;       // ~/.claude/projects/<encoded-path>/
+    last_used: number | null;         // Unix seconds, from the newest session file
+}
+
+export async function listKnownProjects(): Promise<KnownProject[]> {
+    return await invoke<KnownProject[]>("list_known_projects");
 }
 
 // Subdirectory CLAUDE.md discovery
@@ -116,10 +215,718 @@ export async function listDirectory(path: string): Promise<DirectoryEntry[]> {
     return await invoke<DirectoryEntry[]>("list_directory", { path });
 }
 
-export async function deletePath(path: string): Promise<void> {
-    await invoke("delete_path", { path });
+/** Move a file or directory to the trash; it can be restored with restoreFromTrash. */
+export async function deletePath(path: string): Promise<TrashEntry> {
+    return await invoke<TrashEntry>("delete_path", { path });
 }
 
 export async function createDirectory(path: string): Promise<void> {
     await invoke("create_directory", { path });
 }
+
+// Effective settings (all layers merged with Claude Code's precedence)
+export type SettingsScope = 'user' | 'user_local' | 'project' | 'project_local' | 'managed';
+
+export interface SettingsLayer {
+    scope: SettingsScope;
+    path: string;
+    exists: boolean;
+    error: string | null;      // Read or parse failure; the layer is skipped
+}
+
+export interface SettingsSource {
+    scope: SettingsScope;
+    path: string;
+}
+
+export interface EffectiveSettings {
+    settings: Record<string, unknown>;
+    provenance: Record<string, SettingsSource>;   // JSON pointer -> file that supplied it
+    layers: SettingsLayer[];
+}
+
+export async function resolveEffectiveSettings(projectPath?: string): Promise<EffectiveSettings> {
+    return await invoke<EffectiveSettings>("resolve_effective_settings", { projectPath });
+}
+
+// Backups (taken automatically before every save)
+export interface BackupInfo {
+    id: string;
+    path: string;           // Location of the backup copy
+    created_at: number;     // Unix seconds
+    size: number;
+}
+
+export async function listBackups(path: string): Promise<BackupInfo[]> {
+    return await invoke<BackupInfo[]>("list_backups", { path });
+}
+
+export async function restoreBackup(path: string, backupId: string): Promise<void> {
+    await invoke("restore_backup", { path, backupId });
+}
+
+// Live updates from the backend file watcher
+export type ConfigChangeKind = 'created' | 'modified' | 'deleted';
+
+export interface ConfigChangeEvent {
+    kind: ConfigChangeKind;
+    info: ConfigPathInfo;
+}
+
+/**
+ * Watch these projects' config files in addition to the user and enterprise
+ * paths. Replaces the previously watched project list.
+ */
+export async function watchProjects(projectPaths: string[]): Promise<void> {
+    await invoke("watch_projects", { projectPaths });
+}
+
+export async function onConfigChanged(handler: (events: ConfigChangeEvent[]) => void): Promise<UnlistenFn> {
+    return await listen<ConfigChangeEvent[]>("config-changed", (event) => handler(event.payload));
+}
+
+// Diagnostics shared by the backend validators
+export type Severity = 'info' | 'warning' | 'error';
+
+export interface Diagnostic {
+    pointer: string;            // JSON pointer (JSON files) or field path (frontmatter)
+    line: number | null;        // 1-based
+    column: number | null;      // 1-based
+    severity: Severity;
+    message: string;
+    suggestion: string | null;
+}
+
+export interface SettingsValidation {
+    path: string;
+    valid: boolean;             // No error-severity diagnostics
+    diagnostics: Diagnostic[];
+}
+
+export async function validateSettings(path: string): Promise<SettingsValidation> {
+    return await invoke<SettingsValidation>("validate_settings", { path });
+}
+
+// Permission rule evaluation ("would this tool call be allowed?")
+export type PermissionDecision = 'allow' | 'ask' | 'deny';
+
+export type PermissionMode = 'default' | 'acceptEdits' | 'plan' | 'bypassPermissions' | 'dontAsk';
+
+export interface MatchedRule {
+    rule: string;
+    list: PermissionDecision;       // Which list the rule is in
+    scope: SettingsScope;
+    path: string;                   // Settings file the rule came from
+}
+
+export interface PermissionEvaluation {
+    decision: PermissionDecision;
+    matched: MatchedRule | null;    // The rule that decided, if any
+    matching: MatchedRule[];        // Every rule that matched, in any list
+    mode: PermissionMode;           // Effective defaultMode, already applied to decision
+    reason: string;
+}
+
+/**
+ * argument is the command for Bash, the file path for file tools and the URL
+ * for WebFetch.
+ */
+export async function evaluatePermission(
+    tool: string,
+    argument?: string,
+    projectPath?: string
+): Promise<PermissionEvaluation> {
+    return await invoke<PermissionEvaluation>("evaluate_permission", { projectPath, tool, argument });
+}
+
+// MCP server connectivity test (stdio servers)
+export interface McpToolInfo {
+    name: string;
+    description: string | null;
+    input_schema: Record<string, unknown> | null;
+}
+
+export interface McpProbeResult {
+    ok: boolean;
+    protocol_version: string | null;
+    server_info: { name?: string; version?: string } | null;
+    capabilities: Record<string, unknown> | null;
+    tools: McpToolInfo[];
+    stderr: string;
+    error: string | null;
+    duration_ms: number;
+}
+
+/**
+ * Start a configured server, run the initialize handshake and tools/list.
+ * projectPath selects the project's own servers inside ~/.claude.json.
+ */
+export async function testMcpServer(
+    configPath: string,
+    serverName: string,
+    projectPath?: string,
+    timeoutMs?: number
+): Promise<McpProbeResult> {
+    return await invoke<McpProbeResult>("test_mcp_server", { configPath, serverName, projectPath, timeoutMs });
+}
+
+// MCP server configuration
+export type McpScope = "managed" | "user" | "project" | "local";
+
+export interface McpServerConfig {
+    type?: "stdio" | "sse" | "http";    // Omitted means stdio
+    command?: string;
+    args?: string[];
+    env?: Record<string, string>;
+    url?: string;
+    headers?: Record<string, string>;
+    [key: string]: unknown;
+}
+
+export interface McpServerEntry {
+    name: string;
+    scope: McpScope;
+    source_path: string;
+    transport: string;
+    config: McpServerConfig;
+    overridden_by: McpScope | null;     // A higher-precedence scope defines the same name
+}
+
+export interface McpListingError {
+    scope: McpScope;
+    source_path: string;
+    name: string | null;                // null when the whole file is unreadable
+    message: string;
+}
+
+export interface McpServerListing {
+    servers: McpServerEntry[];
+    errors: McpListingError[];          // Skipped files and entries; the rest are still listed
+}
+
+/**
+ * Servers from managed-mcp.json and ~/.claude.json, plus the project's
+ * .mcp.json and local servers when a project is given.
+ */
+export async function listMcpServers(project?: string): Promise<McpServerListing> {
+    return await invoke<McpServerListing>("list_mcp_servers", { project });
+}
+
+/** projectPath is required for the project and local scopes. */
+export async function upsertMcpServer(
+    scope: McpScope,
+    name: string,
+    config: McpServerConfig,
+    projectPath?: string
+): Promise<void> {
+    await invoke("upsert_mcp_server", { scope, name, config, projectPath });
+}
+
+export async function removeMcpServer(scope: McpScope, name: string, projectPath?: string): Promise<void> {
+    await invoke("remove_mcp_server", { scope, name, projectPath });
+}
+
+// Hooks
+export interface HookEntry {
+    event: string;
+    matcher: string | null;
+    type: "command" | "prompt";
+    command?: string;
+    prompt?: string;
+    timeout?: number;                   // Seconds
+    scope: SettingsScope;
+    path: string;
+    pointer: string;                    // JSON pointer of the hook within its file
+    diagnostics: Diagnostic[];          // Problems with this hook or its matcher
+}
+
+export interface HookGroupError extends Diagnostic {
+    scope: SettingsScope;
+    path: string;
+}
+
+export interface HookList {
+    hooks: HookEntry[];
+    errors: HookGroupError[];           // Matcher groups that couldn't be read; their hooks aren't listed
+    disabled_by: SettingsSource | null; // Layer whose `disableAllHooks: true` wins
+    layers: SettingsLayer[];
+}
+
+/** Hooks from every settings layer, lowest precedence first. */
+export async function listHooks(project?: string): Promise<HookList> {
+    return await invoke<HookList>("list_hooks", { project });
+}
+
+export interface HookDryRun {
+    command: string;
+    event: string;
+    matcher?: string;                   // Reported against tool_name, not enforced
+    project_path?: string;              // Working directory and $CLAUDE_PROJECT_DIR
+    tool_name?: string;
+    tool_input?: unknown;
+    tool_response?: unknown;
+    prompt?: string;
+    overrides?: Record<string, unknown>;    // Merged over the generated event JSON
+    timeout_secs?: number;
+}
+
+export type HookOutcome = "proceed" | "approve" | "ask" | "block" | "stop" | "error" | "timed_out";
+
+export interface HookDecision {
+    outcome: HookOutcome;
+    reason: string | null;
+    additional_context: string | null;  // Added to Claude's context
+    system_message: string | null;      // Shown to the user
+    suppress_output: boolean;
+    json_output: Record<string, unknown> | null;
+}
+
+export interface HookDryRunResult {
+    input: Record<string, unknown>;     // Event JSON written to stdin
+    exit_code: number | null;           // null if killed (timeout or signal)
+    timed_out: boolean;
+    stdout: string;
+    stderr: string;
+    duration_ms: number;
+    matcher_matches: boolean | null;
+    decision: HookDecision;
+}
+
+/** Run a hook command against a synthetic event without involving Claude Code. */
+export async function dryRunHook(request: HookDryRun): Promise<HookDryRunResult> {
+    return await invoke<HookDryRunResult>("dry_run_hook", { request });
+}
+
+// CLAUDE.md imports
+export type ImportStatus = "resolved" | "missing" | "cycle" | "already_included" | "depth_exceeded";
+
+export interface ImportEdge {
+    from: string;
+    raw: string;                        // As written, without the leading '@'
+    line: number;                       // 1-based line in `from`
+    target: string;                     // Resolved absolute path
+    depth: number;                      // 1 for imports made by the root file
+    status: ImportStatus;
+}
+
+export interface ImportedFile {
+    path: string;
+    depth: number;                      // 0 for the root file
+    size: number;
+}
+
+export interface ImportGraph {
+    root: string;
+    files: ImportedFile[];              // Every file read, in expansion order
+    edges: ImportEdge[];
+    expanded: string;                   // Root text with each import's content inserted
+}
+
+/** Follow `@path` imports from a memory file, up to Claude Code's depth limit. */
+export async function resolveClaudeMdImports(path: string): Promise<ImportGraph> {
+    return await invoke<ImportGraph>("resolve_claude_md_imports", { path });
+}
+
+// Memory stack for a working directory
+export type MemorySource = "enterprise" | "user" | "user_local" | "ancestor" | "rule" | "subdirectory";
+
+export interface MemoryEntry {
+    source: MemorySource;
+    path: string;
+    directory: string;                  // Directory the file applies to
+    on_demand: boolean;                 // Not part of the initial context
+    size: number;                       // Bytes in the file itself
+    expanded_size: number;              // Bytes after imports are expanded
+    estimated_tokens: number;           // Of the expanded text
+    imports: ImportEdge[];
+    error: string | null;
+}
+
+export interface MemoryStack {
+    cwd: string;
+    entries: MemoryEntry[];             // In the order Claude Code loads them
+    startup_tokens: number;             // Loaded at session start
+    on_demand_tokens: number;           // Subdirectory files, if all were pulled in
+}
+
+/** Every memory file Claude Code would load for a session started in `path`. */
+export async function resolveMemoryForCwd(path: string): Promise<MemoryStack> {
+    return await invoke<MemoryStack>("resolve_memory_for_cwd", { path });
+}
+
+// Token budgeting
+export interface BudgetThresholds {
+    memory: number;                     // Each CLAUDE.md / CLAUDE.local.md
+    rule: number;
+    agent: number;
+    command: number;
+    skill: number;
+    total: number;                      // Everything loaded at session start
+}
+
+export type BudgetCategory = "memory" | "rule" | "agent" | "command" | "skill";
+
+export interface FileBudget {
+    path: string;
+    category: BudgetCategory;
+    level: "enterprise" | "user" | "project";
+    chars: number;
+    estimated_tokens: number;
+    threshold: number;
+    over_threshold: boolean;
+}
+
+export interface BudgetReport {
+    files: FileBudget[];                // Largest first
+    startup_tokens: number;             // Memory and rules, loaded at session start
+    total_tokens: number;
+    total_threshold: number;
+    over_total: boolean;
+    flagged: number;                    // Files over their threshold
+}
+
+/** Omitted thresholds fall back to the backend defaults. */
+export async function tokenBudgetReport(
+    projectPath?: string,
+    thresholds?: Partial<BudgetThresholds>
+): Promise<BudgetReport> {
+    return await invoke<BudgetReport>("token_budget_report", { projectPath, thresholds });
+}
+
+// Agent, command and skill definitions
+export type DefinitionKind = "agent" | "command" | "skill";
+
+export interface ParsedDefinition {
+    path: string;
+    kind: DefinitionKind;
+    frontmatter: Record<string, unknown> | null;    // null if the file has none
+    definition: { kind: DefinitionKind; fields: Record<string, unknown> } | null;
+    body: string;
+    body_line: number;                  // 1-based line where the body starts
+    valid: boolean;                     // No error-severity diagnostics
+    diagnostics: Diagnostic[];
+}
+
+export interface DefinitionValidation {
+    path: string;
+    kind: DefinitionKind;
+    valid: boolean;
+    diagnostics: Diagnostic[];
+}
+
+/** kind is inferred from the path (agents/, commands/, SKILL.md) when omitted. */
+export async function parseDefinition(path: string, kind?: DefinitionKind): Promise<ParsedDefinition> {
+    return await invoke<ParsedDefinition>("parse_definition", { path, kind });
+}
+
+/** Validate every definition under an agents, commands or skills directory, or a parent of them. */
+export async function validateDefinitions(dir: string): Promise<DefinitionValidation[]> {
+    return await invoke<DefinitionValidation[]>("validate_definitions", { dir });
+}
+
+// Agent, command and skill inventory
+export type InventoryScope = "user" | "project";
+export type ItemStatus = "active" | "shadowed" | "duplicate" | "unreachable";
+
+export interface InventoryItem {
+    kind: DefinitionKind;
+    name: string;                       // Agent/skill name, or command name like "frontend:component"
+    scope: InventoryScope;
+    path: string;
+    description: string | null;
+    definition: ParsedDefinition["definition"];
+    valid: boolean;
+    diagnostics: Diagnostic[];
+    status: ItemStatus;
+    reason: string | null;
+    related_path: string | null;        // The winning definition for shadowed/duplicate items
+}
+
+export interface Inventory {
+    items: InventoryItem[];
+    active: number;
+    shadowed: number;
+    duplicated: number;
+    unreachable: number;
+}
+
+/** Every agent, command and skill visible to a project, with shadowing detection. */
+export async function getInventory(project?: string): Promise<Inventory> {
+    return await invoke<Inventory>("inventory", { project });
+}
+
+// Configuration profiles (snapshots of the user-level config)
+export interface ProfileInfo {
+    name: string;
+    created_at: number;                 // Unix seconds
+    updated_at: number;
+    file_count: number;
+    active: boolean;
+    modified_since_activation: boolean; // Only meaningful for the active profile
+}
+
+export interface ProfileFileDiff {
+    path: string;                       // Logical path, e.g. "settings.json" or "agents/reviewer.md"
+    change: "added" | "removed" | "modified";
+    diff: string | null;                // Unified diff; null for binary files
+}
+
+export interface ActivationResult {
+    name: string;
+    written: number;
+    removed: number;
+    previous_snapshot: string;          // Config as it was before activation
+}
+
+export interface ProfileActivatedEvent {
+    name: string;
+    result: ActivationResult | null;
+    error: string | null;
+}
+
+export async function listProfiles(): Promise<ProfileInfo[]> {
+    return await invoke<ProfileInfo[]>("list_profiles");
+}
+
+/** Snapshot the current user-level config, replacing a profile of the same name. */
+export async function saveProfile(name: string): Promise<ProfileInfo> {
+    return await invoke<ProfileInfo>("save_profile", { name });
+}
+
+export async function deleteProfile(name: string): Promise<void> {
+    await invoke("delete_profile", { name });
+}
+
+/** Compare two profiles, or a profile against the current config when right is omitted. */
+export async function diffProfiles(left: string, right?: string): Promise<ProfileFileDiff[]> {
+    return await invoke<ProfileFileDiff[]>("diff_profiles", { left, right });
+}
+
+/** Switch to a profile; on failure the previous config is restored. */
+export async function activateProfile(name: string): Promise<ActivationResult> {
+    return await invoke<ActivationResult>("activate_profile", { name });
+}
+
+/** Fired when a profile is switched from the tray menu. */
+export async function onProfileActivated(handler: (event: ProfileActivatedEvent) => void): Promise<UnlistenFn> {
+    return await listen<ProfileActivatedEvent>("profile-activated", (event) => handler(event.payload));
+}
+
+// Configuration bundles (.tar.gz with a manifest)
+export type BundleScope = "user" | "project";
+export type BundleRole = "memory" | "settings" | "mcp" | "agents" | "commands" | "skills" | "rules";
+export type ImportStatus = "new" | "identical" | "conflict" | "unmapped";
+
+export interface BundleEntry {
+    path: string;                       // Path inside the archive: "<role>/<relative>"
+    role: BundleRole;
+    relative: string;
+    size: number;
+    version: string;
+    redacted: string[];                 // Values replaced with ${NAME} placeholders, e.g. "env.API_KEY"
+}
+
+export interface BundleManifest {
+    format: number;
+    created_at: number;                 // Unix seconds
+    scope: BundleScope;
+    source: string;                     // Project directory name, or "user"
+    entries: BundleEntry[];
+}
+
+export interface BundleExport {
+    path: string;
+    manifest: BundleManifest;
+}
+
+export interface ImportItem {
+    id: string;                         // Archive path, or "mcp/mcp.json#<server>" for MCP servers
+    role: BundleRole;
+    destination: string | null;
+    status: ImportStatus;
+    diff: string | null;                // Unified diff from the existing file to the bundled one
+    redacted: string[];                 // Placeholders to fill in after importing
+    applied: boolean;
+}
+
+export interface ImportReport {
+    manifest: BundleManifest;
+    items: ImportItem[];
+}
+
+/** Export a scope to a .tar.gz; dest may be a directory. paths limits the export to files under them. */
+export async function exportBundle(scope: BundleScope, dest: string, projectPath?: string, paths?: string[]): Promise<BundleExport> {
+    return await invoke<BundleExport>("export_bundle", { scope, paths, dest, projectPath });
+}
+
+/** Preview importing a bundle into a scope without writing anything. */
+export async function previewBundleImport(archive: string, targetScope: BundleScope, projectPath?: string): Promise<ImportReport> {
+    return await invoke<ImportReport>("import_bundle", { archive, targetScope, projectPath, apply: false });
+}
+
+/** Import a bundle. Conflicting items are only replaced when their id is listed in overwrite. */
+export async function importBundle(archive: string, targetScope: BundleScope, overwrite: string[], projectPath?: string): Promise<ImportReport> {
+    return await invoke<ImportReport>("import_bundle", { archive, targetScope, projectPath, apply: true, overwrite });
+}
+
+// Secret scanning
+export type SecretKind =
+    | "anthropic_key" | "open_ai_key" | "github_token" | "aws_access_key" | "aws_secret_key"
+    | "slack_token" | "slack_webhook" | "private_key" | "bearer_token" | "generic" | "high_entropy";
+
+export interface SecretMatch {
+    kind: SecretKind;
+    line: number;                       // 1-based
+    column: number;                     // 1-based
+    key: string | null;                 // Field the value is assigned to, when recognizable
+    preview: string;                    // Masked value, safe to display
+}
+
+export interface SecretFinding extends SecretMatch {
+    path: string;
+    scope: "enterprise" | "user" | "project";
+    tracked: boolean;                   // The file is tracked by git
+    committable: boolean;               // Tracked, or in a work tree and not ignored; saving it is refused
+}
+
+export interface SecretScanReport {
+    findings: SecretFinding[];
+    files_scanned: number;
+    tracked_findings: number;
+    committable_findings: number;
+}
+
+/** Look for API keys and tokens in user, enterprise and project config files. */
+export async function scanSecrets(project?: string): Promise<SecretScanReport> {
+    return await invoke<SecretScanReport>("scan_secrets", { project });
+}
+
+// Trash for deleted config files
+export interface TrashEntry {
+    id: string;
+    original_path: string;
+    name: string;
+    is_dir: boolean;
+    scope: "enterprise" | "user" | "project";
+    deleted_at: number;                 // Unix millis
+    expires_at: number | null;          // Unix millis; null when retention is disabled
+}
+
+export async function listTrash(): Promise<TrashEntry[]> {
+    return await invoke<TrashEntry[]>("list_trash");
+}
+
+/** Put an item back at its original path; fails if something now exists there. */
+export async function restoreFromTrash(id: string): Promise<TrashEntry> {
+    return await invoke<TrashEntry>("restore_from_trash", { id });
+}
+
+/** Permanently delete the given entries, or everything. Returns how many were removed. */
+export async function emptyTrash(ids?: string[]): Promise<number> {
+    return await invoke<number>("empty_trash", { ids });
+}
+
+export async function getTrashRetention(): Promise<number> {
+    return await invoke<number>("get_trash_retention");
+}
+
+/** Days to keep deleted items; 0 keeps them until the trash is emptied. */
+export async function setTrashRetention(days: number): Promise<void> {
+    await invoke("set_trash_retention", { days });
+}
+
+// Doctor
+export type DoctorCategory =
+    | "paths" | "validation" | "mcp" | "hooks" | "settings" | "permissions" | "secrets" | "inventory" | "budget";
+
+export interface DoctorFinding {
+    category: DoctorCategory;
+    severity: Severity;
+    path: string | null;
+    line: number | null;                // 1-based
+    message: string;
+    suggestion: string | null;          // How to fix it
+}
+
+export interface DoctorReport {
+    project: string | null;
+    findings: DoctorFinding[];          // Most severe first
+    files_checked: number;
+    errors: number;
+    warnings: number;
+    infos: number;
+}
+
+/** Check config locations, files, MCP commands, hooks and conflicting settings. */
+export async function runDoctor(project?: string): Promise<DoctorReport> {
+    return await invoke<DoctorReport>("run_doctor", { project });
+}
+
+// Enterprise policy authoring
+export type EnterpriseFile = "claude_md" | "managed_settings" | "managed_mcp";
+
+/** How installing gets write access to the enterprise directory. */
+export type Elevation = "none" | "pkexec" | "sudo" | "osascript" | "unavailable";
+
+export interface EnterprisePreview {
+    file: EnterpriseFile;
+    path: string;
+    exists: boolean;
+    current_version: string | null;
+    changed: boolean;
+    diff: string;                       // Unified diff from the installed file
+    diagnostics: Diagnostic[];
+    valid: boolean;                     // No errors; required to install
+    elevation: Elevation;
+}
+
+export interface EnterpriseInstall {
+    path: string;
+    version: string;
+    backup_id: string | null;           // Backup of the replaced file
+    elevation: Elevation;
+}
+
+/** Validate and diff new content for an enterprise file without writing it. */
+export async function previewEnterpriseFile(file: EnterpriseFile, content: string): Promise<EnterprisePreview> {
+    return await invoke<EnterprisePreview>("preview_enterprise_file", { file, content });
+}
+
+/**
+ * Install an enterprise file, prompting for administrator rights when needed.
+ * Pass `current_version` from the preview to refuse if the file changed since.
+ */
+export async function writeEnterpriseFile(file: EnterpriseFile, content: string, expectedVersion?: string): Promise<EnterpriseInstall> {
+    return await invoke<EnterpriseInstall>("write_enterprise_file", { file, content, expectedVersion });
+}
+
+// Policy compliance audit
+export type PolicyIssueKind = "overridden" | "blocked" | "ignored" | "disallowed" | "unchecked";
+
+export interface PolicyIssue {
+    kind: PolicyIssueKind;
+    scope: "user" | "user_local" | "project" | "project_local" | "local";
+    path: string;
+    pointer: string;                    // JSON pointer of the setting, rule or server
+    value: unknown;                     // What the user or project asked for
+    policy_pointer: string;             // Managed setting responsible
+    policy_value: unknown | null;
+    message: string;
+}
+
+export interface PolicyAudit {
+    managed_settings_path: string;
+    managed_settings_exists: boolean;
+    managed_mcp_exists: boolean;        // managed-mcp.json takes exclusive control of MCP servers
+    issues: PolicyIssue[];
+    overridden: number;
+    blocked: number;
+    ignored: number;
+    disallowed: number;
+    unchecked: number;
+}
+
+/** User, project and local settings that managed policy overrides, blocks or ignores. */
+export async function auditPolicy(project?: string): Promise<PolicyAudit> {
+    return await invoke<PolicyAudit>("audit_policy", { project });
+}
@@ -1,5 +1,5 @@
 import { create } from 'zustand';
-import { getConfigPaths, ConfigPaths, listProjects, ProjectConfigFiles } from '@/lib/paths';
+import { getConfigPaths, ConfigPaths, listProjects, ProjectConfigFiles, ScanOptions, watchProjects } from '@/lib/paths';
 
 interface Project {
     id: string;
@@ -17,6 +17,7 @@ interface ConfigState {
     error: string | null;
     apiKey: string | null;
     scanBaseDir: string | null;
+    scanOptions: ScanOptions;
     theme: 'light' | 'dark' | 'system';
     isSettingsOpen: boolean;
     primaryModel: string;
@@ -29,6 +30,7 @@ interface ConfigState {
     setSelectedFilePath: (path: string | null) => void;
     setApiKey: (key: string) => void;
     setScanBaseDir: (dir: string) => void;
+    setScanOptions: (options: ScanOptions) => void;
     setTheme: (theme: 'light' | 'dark' | 'system') => void;
     setSettingsOpen: (open: boolean) => void;
     setPrimaryModel: (model: string) => void;
@@ -45,6 +47,7 @@ export const useConfigStore = create<ConfigState>((set, get) => ({
     error: null,
     apiKey: localStorage.getItem('anthropic_api_key'),
     scanBaseDir: localStorage.getItem('scan_base_dir'),
+    scanOptions: JSON.parse(localStorage.getItem('scan_options') || '{}'),
     theme: (localStorage.getItem('app_theme') as any) || 'system',
     isSettingsOpen: false,
     primaryModel: localStorage.getItem('anthropic_primary_model') || 'claude-sonnet-4-5',
@@ -76,7 +79,7 @@ export const useConfigStore = create<ConfigState>((set, get) => ({
 
         set({ isLoading: true, error: null });
         try {
-            const projectsData = await listProjects(dir);
+            const projectsData = await listProjects(dir, get().scanOptions);
             const projects = projectsData.map(p => ({
                 id: p.id,
                 path: p.path,
@@ -85,6 +88,7 @@ export const useConfigStore = create<ConfigState>((set, get) => ({
                 config_files: p.config_files
             }));
             set({ projects, isLoading: false });
+            await watchProjects(projects.map(p => p.path));
         } catch (err) {
             set({ error: (err as any).toString(), isLoading: false });
         }
@@ -102,6 +106,11 @@ export const useConfigStore = create<ConfigState>((set, get) => ({
         set({ scanBaseDir: dir });
     },
 
+    setScanOptions: (options: ScanOptions) => {
+        localStorage.setItem('scan_options', JSON.stringify(options));
+        set({ scanOptions: options });
+    },
+
     setTheme: (theme) => {
         localStorage.setItem('app_theme', theme);
         set({ theme });